@@ -1,35 +1,70 @@
-use anyhow::Result;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use anyhow::{anyhow, Result};
+use bytes::BytesMut;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
 use tokio::net::TcpStream;
 
+use crate::redis::{Frame, FrameScanner, Protocol};
+
 #[derive(Debug)]
 pub(crate) struct Connection {
-    stream: TcpStream,
-    buffer_size: usize,
+    stream: BufWriter<TcpStream>,
+    // bytes read from the socket that are not parsed into frames yet
+    buffer: BytesMut,
+    // how much of the frame at the start of `buffer` is known to be there
+    scanner: FrameScanner,
+    // reused for every reply, so encoding doesn't allocate once it has grown
+    write_buffer: BytesMut,
 }
 
 impl Connection {
     pub fn new(stream: TcpStream, buffer_size: usize) -> Connection {
         Connection {
-            stream: stream,
-            buffer_size: buffer_size, // BytesMut::with_capacity(buffer_size),
+            stream: BufWriter::new(stream),
+            buffer: BytesMut::with_capacity(buffer_size),
+            scanner: FrameScanner::default(),
+            write_buffer: BytesMut::with_capacity(buffer_size),
         }
     }
 
-    pub async fn read(&mut self) -> Result<Vec<u8>> {
-        // TODO: handle closed conn
+    /// Reads the next frame, `None` means that the client closed the connection.
+    ///
+    /// Frames that are already in the buffer are returned without touching the socket,
+    /// so pipelined commands are served one by one and a partial frame waits for the
+    /// rest of its bytes.
+    pub async fn read_frame(&mut self) -> Result<Option<Frame>> {
         loop {
-            let mut buffer = Vec::with_capacity(self.buffer_size);
-            self.stream.read_buf(&mut buffer).await?;
+            if let Some(length) = self.scanner.scan(&self.buffer)? {
+                // cut off the buffer, so bulk strings of the frame point into it
+                let frame = Frame::decode(self.buffer.split_to(length).freeze())?;
+                return Ok(Some(frame));
+            }
+
+            if self.stream.read_buf(&mut self.buffer).await? == 0 {
+                return match self.buffer.is_empty() {
+                    true => Ok(None),
+                    false => Err(anyhow!("connection closed in the middle of a frame")),
+                };
+            }
+        }
+    }
 
-            if !buffer.is_empty() {
-                return Ok(buffer);
+    /// Resolves when the client closes the connection. Whatever the client sends
+    /// in the meantime stays in the buffer for `read_frame`.
+    pub async fn closed(&mut self) {
+        loop {
+            match self.stream.read_buf(&mut self.buffer).await {
+                Ok(0) | Err(_) => return,
+                Ok(_) => {}
             }
         }
     }
 
-    pub async fn write(&mut self, response: &[u8]) -> Result<()> {
-        self.stream.write(response).await?;
+    pub async fn write_frame(&mut self, frame: &Frame, protocol: Protocol) -> Result<()> {
+        self.write_buffer.clear();
+        frame.encode(&mut self.write_buffer, protocol);
+
+        self.stream.write_all(&self.write_buffer).await?;
+        self.stream.flush().await?;
         Ok(())
     }
 }
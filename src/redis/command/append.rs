@@ -0,0 +1,37 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct Append {
+    key: Bytes,
+    value: Bytes,
+    result: usize,
+}
+
+impl Append {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.string_append(&self.key, &self.value).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for Append {
+    const NAME: &'static str = "append";
+
+    fn parse(args: &mut CommandArgs) -> Result<Append> {
+        let key = args.next_bytes()?;
+        let value = args.next_bytes()?;
+        Ok(Append {
+            key,
+            value,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::BitUnit;
+use crate::redis::{Frame, Storage};
+
+/// Optional `BYTE` or `BIT` after a range, bytes by default.
+pub(super) fn parse_bit_unit(args: &mut CommandArgs) -> Result<BitUnit> {
+    match args.next_optional_bytes()? {
+        None => Ok(BitUnit::Byte),
+        Some(unit) if unit.eq_ignore_ascii_case(b"byte") => Ok(BitUnit::Byte),
+        Some(unit) if unit.eq_ignore_ascii_case(b"bit") => Ok(BitUnit::Bit),
+        Some(unit) => Err(args.syntax_error(&unit)),
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BitCount {
+    key: Bytes,
+    range: Option<(i64, i64, BitUnit)>,
+    result: u64,
+}
+
+impl BitCount {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.bit_count(&self.key, self.range).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for BitCount {
+    const NAME: &'static str = "bitcount";
+
+    fn parse(args: &mut CommandArgs) -> Result<BitCount> {
+        let key = args.next_bytes()?;
+        let range = match args.next_optional_integer()? {
+            Some(start) => Some((start, args.next_integer()?, parse_bit_unit(args)?)),
+            None => None,
+        };
+        Ok(BitCount {
+            key,
+            range,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::setbit::parse_bit_offset;
+use super::{parse_integer, CommandArgs, RESPCommand};
+use crate::redis::storage::{BitOverflow, BitfieldOp, BitfieldType, BITMAP_MAX_BITS};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// `i` or `u` and a width, unsigned fields are limited to 63 bits like in Redis.
+fn parse_type(value: &[u8]) -> Result<BitfieldType, CmdErrors> {
+    let (signed, max_bits) = match value.first() {
+        Some(b'i' | b'I') => (true, 64),
+        Some(b'u' | b'U') => (false, 63),
+        _ => return Err(CmdErrors::InvalidBitfieldType),
+    };
+    parse_integer(&value[1..])
+        .filter(|bits| (1..=max_bits).contains(bits))
+        .map(|bits| BitfieldType {
+            signed,
+            bits: bits as usize,
+        })
+        .ok_or(CmdErrors::InvalidBitfieldType)
+}
+
+/// Offset in bits, or in fields of the type when prefixed with `#`.
+fn parse_offset(value: &[u8], field: BitfieldType) -> Result<usize, CmdErrors> {
+    let offset = match value.strip_prefix(b"#") {
+        Some(index) => parse_bit_offset(index)?.checked_mul(field.bits),
+        None => Some(parse_bit_offset(value)?),
+    };
+    offset
+        .filter(|offset| offset + field.bits <= BITMAP_MAX_BITS)
+        .ok_or(CmdErrors::BitOffsetOutOfRange)
+}
+
+/// Shared by `BITFIELD` and `BITFIELD_RO`, the latter takes only `GET` operations.
+#[derive(Debug)]
+struct BitFieldArgs {
+    key: Bytes,
+    ops: Vec<BitfieldOp>,
+    result: Vec<Option<i64>>,
+}
+
+impl BitFieldArgs {
+    fn parse(args: &mut CommandArgs, read_only: bool) -> Result<BitFieldArgs> {
+        let key = args.next_bytes()?;
+        let mut ops = vec![];
+        // applies to the writes that follow it
+        let mut overflow = BitOverflow::Wrap;
+        while let Some(subcommand) = args.next_optional_bytes()? {
+            let subcommand = subcommand.to_ascii_lowercase();
+            if subcommand == b"overflow" {
+                let kind = args.next_bytes()?;
+                overflow = match &kind.to_ascii_lowercase()[..] {
+                    b"wrap" => BitOverflow::Wrap,
+                    b"sat" => BitOverflow::Sat,
+                    b"fail" => BitOverflow::Fail,
+                    _ => return Err(CmdErrors::InvalidOverflowType.into()),
+                };
+                continue;
+            }
+
+            let field = parse_type(&args.next_bytes()?)?;
+            let offset = parse_offset(&args.next_bytes()?, field)?;
+            let op = match &subcommand[..] {
+                b"get" => BitfieldOp::Get { field, offset },
+                _ if read_only => return Err(CmdErrors::BitfieldReadOnly.into()),
+                b"set" => BitfieldOp::Set {
+                    field,
+                    offset,
+                    value: args.next_integer()?,
+                    overflow,
+                },
+                b"incrby" => BitfieldOp::IncrBy {
+                    field,
+                    offset,
+                    increment: args.next_integer()?,
+                    overflow,
+                },
+                _ => return Err(args.syntax_error(&subcommand)),
+            };
+            ops.push(op);
+        }
+        Ok(BitFieldArgs {
+            key,
+            ops,
+            result: vec![],
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.bitfield(&self.key, &self.ops).await?;
+        Ok(())
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Array(
+            self.result
+                .iter()
+                .map(|value| match value {
+                    Some(value) => Frame::Integer(*value),
+                    None => Frame::Null,
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BitField(BitFieldArgs);
+
+impl BitField {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for BitField {
+    const NAME: &'static str = "bitfield";
+
+    fn parse(args: &mut CommandArgs) -> Result<BitField> {
+        Ok(BitField(BitFieldArgs::parse(args, false)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BitFieldRo(BitFieldArgs);
+
+impl BitFieldRo {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for BitFieldRo {
+    const NAME: &'static str = "bitfield_ro";
+
+    fn parse(args: &mut CommandArgs) -> Result<BitFieldRo> {
+        Ok(BitFieldRo(BitFieldArgs::parse(args, true)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::BitOp as Operation;
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct BitOp {
+    op: Operation,
+    destination: Bytes,
+    keys: Vec<Bytes>,
+    result: usize,
+}
+
+impl BitOp {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .bit_op(self.op, &self.destination, &self.keys)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for BitOp {
+    const NAME: &'static str = "bitop";
+
+    fn parse(args: &mut CommandArgs) -> Result<BitOp> {
+        let op = args.next_bytes()?;
+        let op = match &op.to_ascii_lowercase()[..] {
+            b"and" => Operation::And,
+            b"or" => Operation::Or,
+            b"xor" => Operation::Xor,
+            b"not" => Operation::Not,
+            _ => return Err(args.syntax_error(&op)),
+        };
+        let destination = args.next_bytes()?;
+        let mut keys = vec![args.next_bytes()?];
+        while let Some(key) = args.next_optional_bytes()? {
+            keys.push(key);
+        }
+        if op == Operation::Not && keys.len() != 1 {
+            return Err(CmdErrors::BitOpNotSingleKey.into());
+        }
+        Ok(BitOp {
+            op,
+            destination,
+            keys,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
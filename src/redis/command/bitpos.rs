@@ -0,0 +1,54 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::bitcount::parse_bit_unit;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::BitUnit;
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct BitPos {
+    key: Bytes,
+    bit: bool,
+    start: i64,
+    end: Option<i64>,
+    unit: BitUnit,
+    result: i64,
+}
+
+impl BitPos {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .bit_pos(&self.key, self.bit, self.start, self.end, self.unit)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for BitPos {
+    const NAME: &'static str = "bitpos";
+
+    fn parse(args: &mut CommandArgs) -> Result<BitPos> {
+        let key = args.next_bytes()?;
+        let bit = match args.next_integer()? {
+            0 => false,
+            1 => true,
+            _ => return Err(CmdErrors::BitArgument.into()),
+        };
+        let start = args.next_optional_integer()?.unwrap_or(0);
+        let end = args.next_optional_integer()?;
+        let unit = parse_bit_unit(args)?;
+        Ok(BitPos {
+            key,
+            bit,
+            start,
+            end,
+            unit,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result)
+    }
+}
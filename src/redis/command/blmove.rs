@@ -0,0 +1,69 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::time::Duration;
+
+use super::lmove::parse_list_end;
+use super::{parse_timeout, CommandArgs, RESPCommand};
+use crate::redis::storage::{BlockingOp, ListEnd, PoppedItems};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct BLMove {
+    source: Bytes,
+    destination: Bytes,
+    from: ListEnd,
+    to: ListEnd,
+    timeout: Option<Duration>,
+    result: Option<Bytes>,
+}
+
+impl BLMove {
+    pub(crate) async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        let op = BlockingOp::ListMove {
+            from: self.from,
+            destination: self.destination.clone(),
+            to: self.to,
+        };
+        let popped = storage
+            .blocking_pop(
+                client_id,
+                std::slice::from_ref(&self.source),
+                op,
+                self.timeout,
+            )
+            .await?;
+        self.result = popped.and_then(|popped| match popped.items {
+            PoppedItems::Values(values) => values.into_iter().next(),
+            _ => None,
+        });
+        Ok(())
+    }
+}
+
+impl RESPCommand for BLMove {
+    const NAME: &'static str = "blmove";
+
+    fn parse(args: &mut CommandArgs) -> Result<BLMove> {
+        let source = args.next_bytes()?;
+        let destination = args.next_bytes()?;
+        let from = parse_list_end(args)?;
+        let to = parse_list_end(args)?;
+        let timeout = parse_timeout(&args.next_bytes()?)?;
+
+        Ok(BLMove {
+            source,
+            destination,
+            from,
+            to,
+            timeout,
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(value) => Frame::BulkString(value.clone()),
+            None => Frame::Null,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::time::Duration;
+
+use super::lmove::parse_list_end;
+use super::{parse_timeout, CommandArgs, RESPCommand};
+use crate::redis::storage::{BlockingOp, ListEnd, Popped, PoppedItems};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// `numkeys key [key ...]` arguments of the commands that pop from one of several keys.
+pub(super) fn parse_keys(args: &mut CommandArgs) -> Result<Vec<Bytes>> {
+    let num_keys = match args.next_integer()? {
+        num_keys if num_keys > 0 => num_keys,
+        _ => return Err(CmdErrors::NotGreaterThanZero("numkeys").into()),
+    };
+    (0..num_keys).map(|_| args.next_bytes()).collect()
+}
+
+/// `[COUNT count]` option of the commands that pop from one of several keys.
+pub(super) fn parse_count(args: &mut CommandArgs) -> Result<usize> {
+    let Some(option) = args.next_optional_bytes()? else {
+        return Ok(1);
+    };
+    if !option.eq_ignore_ascii_case(b"count") {
+        return Err(args.syntax_error(&option));
+    }
+    match args.next_integer()? {
+        count if count > 0 => Ok(count as usize),
+        _ => Err(CmdErrors::NotGreaterThanZero("count").into()),
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BLMPop {
+    timeout: Option<Duration>,
+    keys: Vec<Bytes>,
+    end: ListEnd,
+    count: usize,
+    result: Option<Popped>,
+}
+
+impl BLMPop {
+    pub(crate) async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        let op = BlockingOp::ListPop {
+            end: self.end,
+            count: self.count,
+        };
+        self.result = storage
+            .blocking_pop(client_id, &self.keys, op, self.timeout)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for BLMPop {
+    const NAME: &'static str = "blmpop";
+
+    fn parse(args: &mut CommandArgs) -> Result<BLMPop> {
+        let timeout = parse_timeout(&args.next_bytes()?)?;
+        let keys = parse_keys(args)?;
+        let end = parse_list_end(args)?;
+        let count = parse_count(args)?;
+
+        Ok(BLMPop {
+            timeout,
+            keys,
+            end,
+            count,
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(Popped {
+                key,
+                items: PoppedItems::Values(values),
+            }) => Frame::Array(vec![
+                Frame::BulkString(key.clone()),
+                Frame::Array(values.iter().cloned().map(Frame::BulkString).collect()),
+            ]),
+            _ => Frame::NullArray,
+        }
+    }
+}
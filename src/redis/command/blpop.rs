@@ -0,0 +1,103 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::time::Duration;
+
+use super::{parse_timeout, CommandArgs, RESPCommand};
+use crate::redis::storage::{BlockingOp, ListEnd, Popped, PoppedItems};
+use crate::redis::{Frame, Storage};
+
+/// Shared by `BLPOP` and `BRPOP`, they differ only in the end of the list they pop from.
+#[derive(Debug)]
+struct BlockingPopArgs {
+    keys: Vec<Bytes>,
+    end: ListEnd,
+    timeout: Option<Duration>,
+    result: Option<Popped>,
+}
+
+impl BlockingPopArgs {
+    fn parse(args: &mut CommandArgs, end: ListEnd) -> Result<BlockingPopArgs> {
+        // keys come first, the timeout is the last argument
+        let mut keys = vec![args.next_bytes()?];
+        let mut timeout = args.next_bytes()?;
+        while let Some(arg) = args.next_optional_bytes()? {
+            keys.push(std::mem::replace(&mut timeout, arg));
+        }
+
+        Ok(BlockingPopArgs {
+            keys,
+            end,
+            timeout: parse_timeout(&timeout)?,
+            result: None,
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        let op = BlockingOp::ListPop {
+            end: self.end,
+            count: 1,
+        };
+        self.result = storage
+            .blocking_pop(client_id, &self.keys, op, self.timeout)
+            .await?;
+        Ok(())
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(Popped {
+                key,
+                items: PoppedItems::Values(values),
+            }) => Frame::Array(
+                std::iter::once(key)
+                    .chain(values)
+                    .cloned()
+                    .map(Frame::BulkString)
+                    .collect(),
+            ),
+            _ => Frame::NullArray,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BLPop(BlockingPopArgs);
+
+impl BLPop {
+    pub(crate) async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        self.0.run(storage, client_id).await
+    }
+}
+
+impl RESPCommand for BLPop {
+    const NAME: &'static str = "blpop";
+
+    fn parse(args: &mut CommandArgs) -> Result<BLPop> {
+        Ok(BLPop(BlockingPopArgs::parse(args, ListEnd::Left)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BRPop(BlockingPopArgs);
+
+impl BRPop {
+    pub(crate) async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        self.0.run(storage, client_id).await
+    }
+}
+
+impl RESPCommand for BRPop {
+    const NAME: &'static str = "brpop";
+
+    fn parse(args: &mut CommandArgs) -> Result<BRPop> {
+        Ok(BRPop(BlockingPopArgs::parse(args, ListEnd::Right)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::time::Duration;
+
+use super::blmpop::{parse_count, parse_keys};
+use super::{parse_timeout, CommandArgs, RESPCommand};
+use crate::redis::storage::{BlockingOp, Popped, PoppedItems, ZSetEnd};
+use crate::redis::{Frame, Storage};
+
+/// `MIN | MAX` argument of the commands that pop from sorted sets.
+fn parse_zset_end(args: &mut CommandArgs) -> Result<ZSetEnd> {
+    let end = args.next_bytes()?;
+    match &end.to_ascii_lowercase()[..] {
+        b"min" => Ok(ZSetEnd::Min),
+        b"max" => Ok(ZSetEnd::Max),
+        _ => Err(args.syntax_error(&end)),
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BZMPop {
+    timeout: Option<Duration>,
+    keys: Vec<Bytes>,
+    end: ZSetEnd,
+    count: usize,
+    result: Option<Popped>,
+}
+
+impl BZMPop {
+    pub(crate) async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        let op = BlockingOp::ZSetPop {
+            end: self.end,
+            count: self.count,
+        };
+        self.result = storage
+            .blocking_pop(client_id, &self.keys, op, self.timeout)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for BZMPop {
+    const NAME: &'static str = "bzmpop";
+
+    fn parse(args: &mut CommandArgs) -> Result<BZMPop> {
+        let timeout = parse_timeout(&args.next_bytes()?)?;
+        let keys = parse_keys(args)?;
+        let end = parse_zset_end(args)?;
+        let count = parse_count(args)?;
+
+        Ok(BZMPop {
+            timeout,
+            keys,
+            end,
+            count,
+            result: None,
+        })
+    }
+
+    // the key and an array of member and score pairs
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(Popped {
+                key,
+                items: PoppedItems::Scored(members),
+            }) => Frame::Array(vec![
+                Frame::BulkString(key.clone()),
+                Frame::Array(
+                    members
+                        .iter()
+                        .map(|(member, score)| {
+                            Frame::Array(vec![
+                                Frame::BulkString(member.clone()),
+                                Frame::Double(*score),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ]),
+            _ => Frame::NullArray,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::time::Duration;
+
+use super::{parse_timeout, CommandArgs, RESPCommand};
+use crate::redis::storage::{BlockingOp, Popped, PoppedItems, ZSetEnd};
+use crate::redis::{Frame, Storage};
+
+/// Shared by `BZPOPMIN` and `BZPOPMAX`, they differ only in the end they pop from.
+#[derive(Debug)]
+struct BlockingZPopArgs {
+    keys: Vec<Bytes>,
+    end: ZSetEnd,
+    timeout: Option<Duration>,
+    result: Option<Popped>,
+}
+
+impl BlockingZPopArgs {
+    fn parse(args: &mut CommandArgs, end: ZSetEnd) -> Result<BlockingZPopArgs> {
+        // keys come first, the timeout is the last argument
+        let mut keys = vec![args.next_bytes()?];
+        let mut timeout = args.next_bytes()?;
+        while let Some(arg) = args.next_optional_bytes()? {
+            keys.push(std::mem::replace(&mut timeout, arg));
+        }
+
+        Ok(BlockingZPopArgs {
+            keys,
+            end,
+            timeout: parse_timeout(&timeout)?,
+            result: None,
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        let op = BlockingOp::ZSetPop {
+            end: self.end,
+            count: 1,
+        };
+        self.result = storage
+            .blocking_pop(client_id, &self.keys, op, self.timeout)
+            .await?;
+        Ok(())
+    }
+
+    // key, member and score in one array
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(Popped {
+                key,
+                items: PoppedItems::Scored(members),
+            }) => {
+                let mut items = vec![Frame::BulkString(key.clone())];
+                for (member, score) in members {
+                    items.push(Frame::BulkString(member.clone()));
+                    items.push(Frame::Double(*score));
+                }
+                Frame::Array(items)
+            }
+            _ => Frame::NullArray,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BZPopMin(BlockingZPopArgs);
+
+impl BZPopMin {
+    pub(crate) async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        self.0.run(storage, client_id).await
+    }
+}
+
+impl RESPCommand for BZPopMin {
+    const NAME: &'static str = "bzpopmin";
+
+    fn parse(args: &mut CommandArgs) -> Result<BZPopMin> {
+        Ok(BZPopMin(BlockingZPopArgs::parse(args, ZSetEnd::Min)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BZPopMax(BlockingZPopArgs);
+
+impl BZPopMax {
+    pub(crate) async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        self.0.run(storage, client_id).await
+    }
+}
+
+impl RESPCommand for BZPopMax {
+    const NAME: &'static str = "bzpopmax";
+
+    fn parse(args: &mut CommandArgs) -> Result<BZPopMax> {
+        Ok(BZPopMax(BlockingZPopArgs::parse(args, ZSetEnd::Max)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
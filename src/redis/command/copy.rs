@@ -0,0 +1,60 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct Copy {
+    source: Bytes,
+    destination: Bytes,
+    // `REPLACE`: overwrite the destination when it exists
+    replace: bool,
+    result: bool,
+}
+
+impl Copy {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .copy(&self.source, &self.destination, self.replace)
+            .await;
+        Ok(())
+    }
+}
+
+impl RESPCommand for Copy {
+    const NAME: &'static str = "copy";
+
+    fn parse(args: &mut CommandArgs) -> Result<Copy> {
+        let source = args.next_bytes()?;
+        let destination = args.next_bytes()?;
+
+        let mut replace = false;
+        while let Some(option) = args.next_optional_bytes()? {
+            match &option.to_ascii_lowercase()[..] {
+                b"replace" => replace = true,
+                // there is only database 0
+                b"db" => {
+                    if args.next_integer()? != 0 {
+                        return Err(CmdErrors::DbIndexOutOfRange.into());
+                    }
+                }
+                _ => return Err(args.syntax_error(&option)),
+            }
+        }
+        if source == destination {
+            return Err(CmdErrors::SameObject.into());
+        }
+
+        Ok(Copy {
+            source,
+            destination,
+            replace,
+            result: false,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::ExpireCondition;
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug, Clone, Copy)]
+pub(super) enum TimeUnit {
+    Seconds,
+    Milliseconds,
+}
+
+/// Turns an expire argument into a deadline: relative time (`EX`, `EXPIRE`) is counted from now,
+/// absolute one (`EXAT`, `EXPIREAT`) from the unix epoch. `None` means it doesn't fit into `SystemTime`.
+pub(super) fn deadline(amount: i64, unit: TimeUnit, absolute: bool) -> Option<SystemTime> {
+    let offset = match unit {
+        TimeUnit::Seconds => Duration::from_secs(amount.unsigned_abs()),
+        TimeUnit::Milliseconds => Duration::from_millis(amount.unsigned_abs()),
+    };
+    let base = match absolute {
+        true => UNIX_EPOCH,
+        false => SystemTime::now(),
+    };
+
+    match amount >= 0 {
+        true => base.checked_add(offset),
+        false => base.checked_sub(offset),
+    }
+}
+
+/// Shared by `EXPIRE`, `PEXPIRE`, `EXPIREAT` and `PEXPIREAT`, they differ only in how
+/// the time argument is read.
+#[derive(Debug)]
+struct ExpireArgs {
+    key: Bytes,
+    deadline: SystemTime,
+    condition: ExpireCondition,
+    result: bool,
+}
+
+impl ExpireArgs {
+    fn parse(
+        args: &mut CommandArgs,
+        command_name: &str,
+        unit: TimeUnit,
+        absolute: bool,
+    ) -> Result<ExpireArgs> {
+        let key = args.next_bytes()?;
+        let amount = args.next_integer()?;
+        let deadline = deadline(amount, unit, absolute).ok_or(CmdErrors::InvalidExpireTime {
+            command_name: command_name.to_string(),
+        })?;
+
+        let (mut nx, mut xx, mut gt, mut lt) = (false, false, false, false);
+        while let Some(option) = args.next_optional_bytes()? {
+            match &option.to_ascii_lowercase()[..] {
+                b"nx" => nx = true,
+                b"xx" => xx = true,
+                b"gt" => gt = true,
+                b"lt" => lt = true,
+                _ => return Err(args.syntax_error(&option)),
+            }
+        }
+        if nx && (xx || gt || lt) {
+            return Err(CmdErrors::ExpireNxWithOthers.into());
+        }
+        if gt && lt {
+            return Err(CmdErrors::ExpireGtAndLt.into());
+        }
+        let condition = match (nx, xx, gt, lt) {
+            (true, ..) => ExpireCondition::NotExists,
+            (_, true, _, true) => ExpireCondition::ExistsLessThan,
+            // no expiry is never earlier, so `XX` adds nothing to `GT`
+            (_, _, true, _) => ExpireCondition::GreaterThan,
+            (_, _, _, true) => ExpireCondition::LessThan,
+            (_, true, ..) => ExpireCondition::Exists,
+            _ => ExpireCondition::Always,
+        };
+
+        Ok(ExpireArgs {
+            key,
+            deadline,
+            condition,
+            result: false,
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) {
+        self.result = storage
+            .expire(&self.key, self.deadline, self.condition)
+            .await;
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(i64::from(self.result))
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Expire(ExpireArgs);
+
+impl Expire {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.0.run(storage).await;
+    }
+}
+
+impl RESPCommand for Expire {
+    const NAME: &'static str = "expire";
+
+    fn parse(args: &mut CommandArgs) -> Result<Expire> {
+        let args = ExpireArgs::parse(args, Self::NAME, TimeUnit::Seconds, false)?;
+        Ok(Expire(args))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct PExpire(ExpireArgs);
+
+impl PExpire {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.0.run(storage).await;
+    }
+}
+
+impl RESPCommand for PExpire {
+    const NAME: &'static str = "pexpire";
+
+    fn parse(args: &mut CommandArgs) -> Result<PExpire> {
+        let args = ExpireArgs::parse(args, Self::NAME, TimeUnit::Milliseconds, false)?;
+        Ok(PExpire(args))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ExpireAt(ExpireArgs);
+
+impl ExpireAt {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.0.run(storage).await;
+    }
+}
+
+impl RESPCommand for ExpireAt {
+    const NAME: &'static str = "expireat";
+
+    fn parse(args: &mut CommandArgs) -> Result<ExpireAt> {
+        let args = ExpireArgs::parse(args, Self::NAME, TimeUnit::Seconds, true)?;
+        Ok(ExpireAt(args))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct PExpireAt(ExpireArgs);
+
+impl PExpireAt {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.0.run(storage).await;
+    }
+}
+
+impl RESPCommand for PExpireAt {
+    const NAME: &'static str = "pexpireat";
+
+    fn parse(args: &mut CommandArgs) -> Result<PExpireAt> {
+        let args = ExpireArgs::parse(args, Self::NAME, TimeUnit::Milliseconds, true)?;
+        Ok(PExpireAt(args))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{parse_float, CommandArgs, RESPCommand};
+use crate::redis::storage::{geo_score, geo_valid, ZAddOptions, ZAdded};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// Longitude and latitude arguments, checked to be a position Redis can index.
+pub(super) fn parse_position(longitude: &[u8], latitude: &[u8]) -> Result<(f64, f64), CmdErrors> {
+    let longitude = parse_float(longitude).ok_or(CmdErrors::NotAFloat)?;
+    let latitude = parse_float(latitude).ok_or(CmdErrors::NotAFloat)?;
+    match geo_valid(longitude, latitude) {
+        true => Ok((longitude, latitude)),
+        false => Err(CmdErrors::InvalidLonLat {
+            longitude,
+            latitude,
+        }),
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct GeoAdd {
+    key: Bytes,
+    pairs: Vec<(f64, Bytes)>,
+    options: ZAddOptions,
+    // `CH`: reply with the number of added and moved members
+    changed: bool,
+    result: ZAdded,
+}
+
+impl GeoAdd {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .zset_add(&self.key, &self.pairs, self.options)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for GeoAdd {
+    const NAME: &'static str = "geoadd";
+
+    fn parse(args: &mut CommandArgs) -> Result<GeoAdd> {
+        let key = args.next_bytes()?;
+
+        let mut options = ZAddOptions::default();
+        let mut changed = false;
+        loop {
+            let flag = if args.next_is(b"nx") {
+                &mut options.only_new
+            } else if args.next_is(b"xx") {
+                &mut options.only_existing
+            } else if args.next_is(b"ch") {
+                &mut changed
+            } else {
+                break;
+            };
+            *flag = true;
+            args.next_bytes()?;
+        }
+        if options.only_new && options.only_existing {
+            return Err(CmdErrors::NxAndXx.into());
+        }
+
+        let mut pairs = vec![];
+        while let Some(longitude) = args.next_optional_bytes()? {
+            let (longitude, latitude) = parse_position(&longitude, &args.next_bytes()?)?;
+            pairs.push((geo_score(longitude, latitude), args.next_bytes()?));
+        }
+        if pairs.is_empty() {
+            return Err(CmdErrors::MissingCommandArg {
+                command_name: Self::NAME.to_string(),
+            }
+            .into());
+        }
+
+        Ok(GeoAdd {
+            key,
+            pairs,
+            options,
+            changed,
+            result: ZAdded::default(),
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        let count = match self.changed {
+            true => self.result.added + self.result.updated,
+            false => self.result.added,
+        };
+        Frame::Integer(count as i64)
+    }
+}
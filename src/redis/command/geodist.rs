@@ -0,0 +1,64 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::geo_distance;
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// Meters in the unit.
+pub(super) fn parse_unit(unit: &[u8]) -> Result<f64, CmdErrors> {
+    match &unit.to_ascii_lowercase()[..] {
+        b"m" => Ok(1.0),
+        b"km" => Ok(1000.0),
+        b"ft" => Ok(0.3048),
+        b"mi" => Ok(1609.34),
+        _ => Err(CmdErrors::UnsupportedUnit),
+    }
+}
+
+/// Distances are replied with four decimals, like in Redis.
+pub(super) fn distance_frame(distance: f64) -> Frame {
+    Frame::BulkString(Bytes::from(format!("{:.4}", distance)))
+}
+
+#[derive(Debug)]
+pub(crate) struct GeoDist {
+    key: Bytes,
+    members: [Bytes; 2],
+    unit: f64,
+    result: Option<f64>,
+}
+
+impl GeoDist {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let positions = storage.geo_positions(&self.key, &self.members).await?;
+        self.result = match positions[..] {
+            [Some(from), Some(to)] => Some(geo_distance(from, to) / self.unit),
+            _ => None,
+        };
+        Ok(())
+    }
+}
+
+impl RESPCommand for GeoDist {
+    const NAME: &'static str = "geodist";
+
+    fn parse(args: &mut CommandArgs) -> Result<GeoDist> {
+        let key = args.next_bytes()?;
+        let members = [args.next_bytes()?, args.next_bytes()?];
+        let unit = match args.next_optional_bytes()? {
+            Some(unit) => parse_unit(&unit)?,
+            None => 1.0,
+        };
+        Ok(GeoDist {
+            key,
+            members,
+            unit,
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        self.result.map_or(Frame::Null, distance_frame)
+    }
+}
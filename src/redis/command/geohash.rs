@@ -0,0 +1,53 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::geohash;
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct GeoHash {
+    key: Bytes,
+    members: Vec<Bytes>,
+    result: Vec<Option<String>>,
+}
+
+impl GeoHash {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let positions = storage.geo_positions(&self.key, &self.members).await?;
+        self.result = positions
+            .into_iter()
+            .map(|position| position.map(|(longitude, latitude)| geohash(longitude, latitude)))
+            .collect();
+        Ok(())
+    }
+}
+
+impl RESPCommand for GeoHash {
+    const NAME: &'static str = "geohash";
+
+    fn parse(args: &mut CommandArgs) -> Result<GeoHash> {
+        let key = args.next_bytes()?;
+        let mut members = vec![];
+        while let Some(member) = args.next_optional_bytes()? {
+            members.push(member);
+        }
+        Ok(GeoHash {
+            key,
+            members,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Array(
+            self.result
+                .iter()
+                .map(|hash| match hash {
+                    Some(hash) => Frame::BulkString(Bytes::from(hash.clone())),
+                    None => Frame::Null,
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+/// Coordinates are replied with up to 17 decimals, like in Redis.
+pub(super) fn position_frame((longitude, latitude): (f64, f64)) -> Frame {
+    let coordinate = |value: f64| {
+        let formatted = format!("{:.17}", value);
+        let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+        Frame::BulkString(Bytes::from(trimmed.to_string()))
+    };
+    Frame::Array(vec![coordinate(longitude), coordinate(latitude)])
+}
+
+#[derive(Debug)]
+pub(crate) struct GeoPos {
+    key: Bytes,
+    members: Vec<Bytes>,
+    result: Vec<Option<(f64, f64)>>,
+}
+
+impl GeoPos {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.geo_positions(&self.key, &self.members).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for GeoPos {
+    const NAME: &'static str = "geopos";
+
+    fn parse(args: &mut CommandArgs) -> Result<GeoPos> {
+        let key = args.next_bytes()?;
+        let mut members = vec![];
+        while let Some(member) = args.next_optional_bytes()? {
+            members.push(member);
+        }
+        Ok(GeoPos {
+            key,
+            members,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Array(
+            self.result
+                .iter()
+                .map(|position| position.map_or(Frame::NullArray, position_frame))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::geoadd::parse_position;
+use super::geodist::{distance_frame, parse_unit};
+use super::geopos::position_frame;
+use super::{parse_float, CommandArgs, RESPCommand};
+use crate::redis::storage::{GeoMatch, GeoOrder, GeoOrigin, GeoQuery, GeoShape};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// Query and options of `GEOSEARCH` and `GEOSEARCHSTORE` after their keys, each
+/// command checks the options it takes.
+struct SearchArgs {
+    query: GeoQuery,
+    with_coord: bool,
+    with_dist: bool,
+    with_hash: bool,
+    store_distance: bool,
+}
+
+fn parse_search(args: &mut CommandArgs, command: &'static str) -> Result<SearchArgs> {
+    let (mut origin, mut shape, mut unit) = (None, None, 1.0);
+    let (mut order, mut count, mut any) = (None, None, false);
+    let (mut with_coord, mut with_dist, mut with_hash) = (false, false, false);
+    let mut store_distance = false;
+    let exactly_one = |options| CmdErrors::GeoExactlyOne { options, command };
+
+    while let Some(option) = args.next_optional_bytes()? {
+        match &option.to_ascii_lowercase()[..] {
+            b"frommember" | b"fromlonlat" if origin.is_some() => {
+                return Err(exactly_one("FROMMEMBER or FROMLONLAT").into())
+            }
+            b"frommember" => origin = Some(GeoOrigin::Member(args.next_bytes()?)),
+            b"fromlonlat" => {
+                let (longitude, latitude) =
+                    parse_position(&args.next_bytes()?, &args.next_bytes()?)?;
+                origin = Some(GeoOrigin::Point {
+                    longitude,
+                    latitude,
+                });
+            }
+            b"byradius" | b"bybox" if shape.is_some() => {
+                return Err(exactly_one("BYRADIUS and BYBOX").into())
+            }
+            b"byradius" => {
+                let radius = parse_float(&args.next_bytes()?).ok_or(CmdErrors::NotAFloat)?;
+                if radius < 0.0 {
+                    return Err(CmdErrors::NegativeRadius.into());
+                }
+                shape = Some(GeoShape::Radius(radius));
+                unit = parse_unit(&args.next_bytes()?)?;
+            }
+            b"bybox" => {
+                let width = parse_float(&args.next_bytes()?).ok_or(CmdErrors::NotAFloat)?;
+                let height = parse_float(&args.next_bytes()?).ok_or(CmdErrors::NotAFloat)?;
+                if width < 0.0 || height < 0.0 {
+                    return Err(CmdErrors::NegativeBox.into());
+                }
+                shape = Some(GeoShape::Box { width, height });
+                unit = parse_unit(&args.next_bytes()?)?;
+            }
+            b"asc" => order = Some(GeoOrder::Asc),
+            b"desc" => order = Some(GeoOrder::Desc),
+            b"count" => {
+                let value = args.next_integer()?;
+                let value = usize::try_from(value)
+                    .ok()
+                    .filter(|value| *value > 0)
+                    .ok_or(CmdErrors::NotGreaterThanZero("COUNT"))?;
+                count = Some(value);
+                if args.next_is(b"any") {
+                    args.next_bytes()?;
+                    any = true;
+                }
+            }
+            b"withcoord" => with_coord = true,
+            b"withdist" => with_dist = true,
+            b"withhash" => with_hash = true,
+            b"storedist" => store_distance = true,
+            _ => return Err(args.syntax_error(&option)),
+        }
+    }
+
+    let origin = origin.ok_or_else(|| exactly_one("FROMMEMBER or FROMLONLAT"))?;
+    let shape = shape.ok_or_else(|| exactly_one("BYRADIUS and BYBOX"))?;
+    Ok(SearchArgs {
+        query: GeoQuery {
+            origin,
+            shape,
+            unit,
+            order,
+            count,
+            any,
+        },
+        with_coord,
+        with_dist,
+        with_hash,
+        store_distance,
+    })
+}
+
+#[derive(Debug)]
+pub(crate) struct GeoSearch {
+    key: Bytes,
+    query: GeoQuery,
+    with_coord: bool,
+    with_dist: bool,
+    with_hash: bool,
+    result: Vec<GeoMatch>,
+}
+
+impl GeoSearch {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.geo_search(&self.key, &self.query).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for GeoSearch {
+    const NAME: &'static str = "geosearch";
+
+    fn parse(args: &mut CommandArgs) -> Result<GeoSearch> {
+        let key = args.next_bytes()?;
+        let search = parse_search(args, "GEOSEARCH")?;
+        if search.store_distance {
+            return Err(args.syntax_error(b"STOREDIST"));
+        }
+        Ok(GeoSearch {
+            key,
+            query: search.query,
+            with_coord: search.with_coord,
+            with_dist: search.with_dist,
+            with_hash: search.with_hash,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        let items = self.result.iter().map(|found| {
+            let member = Frame::BulkString(found.member.clone());
+            if !self.with_dist && !self.with_hash && !self.with_coord {
+                return member;
+            }
+            let mut item = vec![member];
+            if self.with_dist {
+                item.push(distance_frame(found.distance));
+            }
+            if self.with_hash {
+                item.push(Frame::Integer(found.score as i64));
+            }
+            if self.with_coord {
+                item.push(position_frame((found.longitude, found.latitude)));
+            }
+            Frame::Array(item)
+        });
+        Frame::Array(items.collect())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct GeoSearchStore {
+    destination: Bytes,
+    key: Bytes,
+    query: GeoQuery,
+    // `STOREDIST`: scores are the distances instead of the positions
+    store_distance: bool,
+    result: usize,
+}
+
+impl GeoSearchStore {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .geo_search_store(
+                &self.destination,
+                &self.key,
+                &self.query,
+                self.store_distance,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for GeoSearchStore {
+    const NAME: &'static str = "geosearchstore";
+
+    fn parse(args: &mut CommandArgs) -> Result<GeoSearchStore> {
+        let destination = args.next_bytes()?;
+        let key = args.next_bytes()?;
+        let search = parse_search(args, "GEOSEARCHSTORE")?;
+        if search.with_coord || search.with_dist || search.with_hash {
+            return Err(CmdErrors::GeoStoreWithReplyOptions("GEOSEARCHSTORE").into());
+        }
+        Ok(GeoSearchStore {
+            destination,
+            key,
+            query: search.query,
+            store_distance: search.store_distance,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -6,13 +6,14 @@ use crate::redis::{Frame, Storage};
 
 #[derive(Debug)]
 pub(crate) struct Get {
-    key: String,
+    key: Bytes,
     result: Option<Bytes>,
 }
 
 impl Get {
-    pub(crate) async fn run(&mut self, storage: &Storage) {
-        self.result = storage.get(&self.key).await;
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.get(&self.key).await?;
+        Ok(())
     }
 }
 
@@ -21,10 +22,7 @@ impl RESPCommand for Get {
 
     fn parse(args: &mut CommandArgs) -> Result<Get> {
         let key = args.next_bytes()?;
-        Ok(Get {
-            key: String::from_utf8(key.to_vec())?,
-            result: None,
-        })
+        Ok(Get { key, result: None })
     }
 
     fn to_response(&self) -> Frame {
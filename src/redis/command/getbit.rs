@@ -0,0 +1,38 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::setbit::parse_bit_offset;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct GetBit {
+    key: Bytes,
+    offset: usize,
+    result: bool,
+}
+
+impl GetBit {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.bit_get(&self.key, self.offset).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for GetBit {
+    const NAME: &'static str = "getbit";
+
+    fn parse(args: &mut CommandArgs) -> Result<GetBit> {
+        let key = args.next_bytes()?;
+        let offset = parse_bit_offset(&args.next_bytes()?)?;
+        Ok(GetBit {
+            key,
+            offset,
+            result: false,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(i64::from(self.result))
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct GetDel {
+    key: Bytes,
+    result: Option<Bytes>,
+}
+
+impl GetDel {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.string_get_delete(&self.key).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for GetDel {
+    const NAME: &'static str = "getdel";
+
+    fn parse(args: &mut CommandArgs) -> Result<GetDel> {
+        let key = args.next_bytes()?;
+        Ok(GetDel { key, result: None })
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(value) => Frame::BulkString(value.clone()),
+            None => Frame::Null,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::expire::{deadline, TimeUnit};
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::SetExpiry;
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct GetEx {
+    key: Bytes,
+    // `Keep` without options, `Discard` for `PERSIST`
+    expiry: SetExpiry,
+    result: Option<Bytes>,
+}
+
+impl GetEx {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.string_get_expire(&self.key, self.expiry).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for GetEx {
+    const NAME: &'static str = "getex";
+
+    fn parse(args: &mut CommandArgs) -> Result<GetEx> {
+        let key = args.next_bytes()?;
+
+        let mut expiry = None;
+        while let Some(option) = args.next_optional_bytes()? {
+            let time_option = match &option.to_ascii_lowercase()[..] {
+                b"ex" => Some((TimeUnit::Seconds, false)),
+                b"px" => Some((TimeUnit::Milliseconds, false)),
+                b"exat" => Some((TimeUnit::Seconds, true)),
+                b"pxat" => Some((TimeUnit::Milliseconds, true)),
+                b"persist" => None,
+                _ => return Err(args.syntax_error(&option)),
+            };
+            // only one of EX, PX, EXAT, PXAT and PERSIST
+            if expiry.is_some() {
+                return Err(args.syntax_error(&option));
+            }
+
+            expiry = Some(match time_option {
+                None => SetExpiry::Discard,
+                Some((unit, absolute)) => {
+                    let invalid_expire_time = || CmdErrors::InvalidExpireTime {
+                        command_name: Self::NAME.to_string(),
+                    };
+                    let amount = args.next_integer()?;
+                    if amount <= 0 {
+                        return Err(invalid_expire_time().into());
+                    }
+                    SetExpiry::At(deadline(amount, unit, absolute).ok_or_else(invalid_expire_time)?)
+                }
+            });
+        }
+
+        Ok(GetEx {
+            key,
+            expiry: expiry.unwrap_or(SetExpiry::Keep),
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(value) => Frame::BulkString(value.clone()),
+            None => Frame::Null,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct GetRange {
+    key: Bytes,
+    start: i64,
+    end: i64,
+    result: Bytes,
+}
+
+impl GetRange {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .string_range(&self.key, self.start, self.end)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for GetRange {
+    const NAME: &'static str = "getrange";
+
+    fn parse(args: &mut CommandArgs) -> Result<GetRange> {
+        let key = args.next_bytes()?;
+        let start = args.next_integer()?;
+        let end = args.next_integer()?;
+        Ok(GetRange {
+            key,
+            start,
+            end,
+            result: Bytes::new(),
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::BulkString(self.result.clone())
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::{SetCondition, SetExpiry};
+use crate::redis::{Frame, Storage};
+
+/// Same as `SET key value GET`.
+#[derive(Debug)]
+pub(crate) struct GetSet {
+    key: Bytes,
+    value: Bytes,
+    result: Option<Bytes>,
+}
+
+impl GetSet {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        (_, self.result) = storage
+            .set(
+                &self.key,
+                &self.value,
+                SetExpiry::Discard,
+                &SetCondition::Always,
+                true,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for GetSet {
+    const NAME: &'static str = "getset";
+
+    fn parse(args: &mut CommandArgs) -> Result<GetSet> {
+        let key = args.next_bytes()?;
+        let value = args.next_bytes()?;
+        Ok(GetSet {
+            key,
+            value,
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(value) => Frame::BulkString(value.clone()),
+            None => Frame::Null,
+        }
+    }
+}
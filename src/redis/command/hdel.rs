@@ -0,0 +1,41 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct HDel {
+    key: Bytes,
+    fields: Vec<Bytes>,
+    result: usize,
+}
+
+impl HDel {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.hash_delete(&self.key, &self.fields).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for HDel {
+    const NAME: &'static str = "hdel";
+
+    fn parse(args: &mut CommandArgs) -> Result<HDel> {
+        let key = args.next_bytes()?;
+        let mut fields = vec![args.next_bytes()?];
+        while let Some(field) = args.next_optional_bytes()? {
+            fields.push(field);
+        }
+
+        Ok(HDel {
+            key,
+            fields,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
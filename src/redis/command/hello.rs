@@ -0,0 +1,78 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{CmdErrors, Frame, Protocol};
+
+#[derive(Debug)]
+pub(crate) struct Hello {
+    // `None` keeps the protocol the connection already uses
+    protocol: Option<Protocol>,
+    client_id: u64,
+}
+
+impl Hello {
+    pub(crate) fn run(&mut self, protocol: &mut Protocol, client_id: u64) {
+        match self.protocol {
+            Some(requested) => *protocol = requested,
+            None => self.protocol = Some(*protocol),
+        }
+        self.client_id = client_id;
+    }
+}
+
+impl RESPCommand for Hello {
+    const NAME: &'static str = "hello";
+
+    fn parse(args: &mut CommandArgs) -> Result<Hello> {
+        let protocol = match args.next_optional_bytes()?.as_deref() {
+            None => None,
+            Some(b"2") => Some(Protocol::Resp2),
+            Some(b"3") => Some(Protocol::Resp3),
+            Some(_) => return Err(CmdErrors::UnsupportedProtocol.into()),
+        };
+
+        // there are no users and client names yet, but clients send them anyway
+        while let Some(option) = args.next_optional_bytes()? {
+            match &option.to_ascii_lowercase()[..] {
+                b"auth" => {
+                    args.next_bytes()?;
+                    args.next_bytes()?;
+                }
+                b"setname" => {
+                    args.next_bytes()?;
+                }
+                _ => {
+                    return Err(CmdErrors::IncorrectCommandArg {
+                        command_name: Self::NAME.to_string(),
+                        arg: String::from_utf8_lossy(&option).into_owned(),
+                    }
+                    .into())
+                }
+            }
+        }
+
+        Ok(Hello {
+            protocol,
+            client_id: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        let proto = match self.protocol {
+            Some(Protocol::Resp3) => 3,
+            _ => 2,
+        };
+        let field = |name: &'static str| Frame::BulkString(Bytes::from_static(name.as_bytes()));
+
+        Frame::Map(vec![
+            (field("server"), field("rredis")),
+            (field("version"), field(env!("CARGO_PKG_VERSION"))),
+            (field("proto"), Frame::Integer(proto)),
+            (field("id"), Frame::Integer(self.client_id as i64)),
+            (field("mode"), field("standalone")),
+            (field("role"), field("master")),
+            (field("modules"), Frame::Array(vec![])),
+        ])
+    }
+}
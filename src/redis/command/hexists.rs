@@ -0,0 +1,40 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct HExists {
+    key: Bytes,
+    field: Bytes,
+    result: bool,
+}
+
+impl HExists {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let values = storage
+            .hash_get(&self.key, std::slice::from_ref(&self.field))
+            .await?;
+        self.result = values.iter().any(Option::is_some);
+        Ok(())
+    }
+}
+
+impl RESPCommand for HExists {
+    const NAME: &'static str = "hexists";
+
+    fn parse(args: &mut CommandArgs) -> Result<HExists> {
+        let key = args.next_bytes()?;
+        let field = args.next_bytes()?;
+        Ok(HExists {
+            key,
+            field,
+            result: false,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(i64::from(self.result))
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::time::SystemTime;
+
+use super::expire::{deadline, TimeUnit};
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::{ExpireCondition, FieldExpire};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// `FIELDS numfields field [field ...]` arguments of the hash field expiry commands,
+/// they always come last.
+pub(super) fn parse_fields(args: &mut CommandArgs) -> Result<Vec<Bytes>> {
+    if !args.next_is(b"fields") {
+        return Err(CmdErrors::MissingFields.into());
+    }
+    args.next_bytes()?;
+    let num_fields = match args.next_integer()? {
+        num_fields if num_fields > 0 => num_fields,
+        _ => return Err(CmdErrors::NotGreaterThanZero("Parameter `numFields`").into()),
+    };
+
+    let mut fields = vec![];
+    while let Some(field) = args.next_optional_bytes()? {
+        fields.push(field);
+    }
+    match fields.len() as i64 == num_fields {
+        true => Ok(fields),
+        false => Err(CmdErrors::NumFieldsMismatch.into()),
+    }
+}
+
+/// Shared by `HEXPIRE`, `HPEXPIRE`, `HEXPIREAT` and `HPEXPIREAT`, they differ only in
+/// how the time argument is read.
+#[derive(Debug)]
+struct HExpireArgs {
+    key: Bytes,
+    deadline: SystemTime,
+    condition: ExpireCondition,
+    fields: Vec<Bytes>,
+    result: Vec<FieldExpire>,
+}
+
+impl HExpireArgs {
+    fn parse(
+        args: &mut CommandArgs,
+        command_name: &str,
+        unit: TimeUnit,
+        absolute: bool,
+    ) -> Result<HExpireArgs> {
+        let key = args.next_bytes()?;
+        let invalid_expire_time = || CmdErrors::InvalidExpireTime {
+            command_name: command_name.to_string(),
+        };
+        let amount = args.next_integer()?;
+        if amount < 0 {
+            return Err(invalid_expire_time().into());
+        }
+        let deadline = deadline(amount, unit, absolute).ok_or_else(invalid_expire_time)?;
+
+        let condition = match args.next_is(b"fields") {
+            true => ExpireCondition::Always,
+            false => match &args.next_bytes()?.to_ascii_lowercase()[..] {
+                b"nx" => ExpireCondition::NotExists,
+                b"xx" => ExpireCondition::Exists,
+                b"gt" => ExpireCondition::GreaterThan,
+                b"lt" => ExpireCondition::LessThan,
+                _ => return Err(CmdErrors::MissingFields.into()),
+            },
+        };
+        let fields = parse_fields(args)?;
+
+        Ok(HExpireArgs {
+            key,
+            deadline,
+            condition,
+            fields,
+            result: vec![],
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .hash_expire(&self.key, &self.fields, self.deadline, self.condition)
+            .await?;
+        Ok(())
+    }
+
+    fn to_response(&self) -> Frame {
+        let code = |result: &FieldExpire| match result {
+            FieldExpire::NoSuchField => -2,
+            FieldExpire::ConditionNotMet => 0,
+            FieldExpire::Set => 1,
+            FieldExpire::Deleted => 2,
+        };
+        Frame::Array(
+            self.result
+                .iter()
+                .map(|result| Frame::Integer(code(result)))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HExpire(HExpireArgs);
+
+impl HExpire {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HExpire {
+    const NAME: &'static str = "hexpire";
+
+    fn parse(args: &mut CommandArgs) -> Result<HExpire> {
+        let args = HExpireArgs::parse(args, Self::NAME, TimeUnit::Seconds, false)?;
+        Ok(HExpire(args))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HPExpire(HExpireArgs);
+
+impl HPExpire {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HPExpire {
+    const NAME: &'static str = "hpexpire";
+
+    fn parse(args: &mut CommandArgs) -> Result<HPExpire> {
+        let args = HExpireArgs::parse(args, Self::NAME, TimeUnit::Milliseconds, false)?;
+        Ok(HPExpire(args))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HExpireAt(HExpireArgs);
+
+impl HExpireAt {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HExpireAt {
+    const NAME: &'static str = "hexpireat";
+
+    fn parse(args: &mut CommandArgs) -> Result<HExpireAt> {
+        let args = HExpireArgs::parse(args, Self::NAME, TimeUnit::Seconds, true)?;
+        Ok(HExpireAt(args))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HPExpireAt(HExpireArgs);
+
+impl HPExpireAt {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HPExpireAt {
+    const NAME: &'static str = "hpexpireat";
+
+    fn parse(args: &mut CommandArgs) -> Result<HPExpireAt> {
+        let args = HExpireArgs::parse(args, Self::NAME, TimeUnit::Milliseconds, true)?;
+        Ok(HPExpireAt(args))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct HGet {
+    key: Bytes,
+    field: Bytes,
+    result: Option<Bytes>,
+}
+
+impl HGet {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let values = storage
+            .hash_get(&self.key, std::slice::from_ref(&self.field))
+            .await?;
+        self.result = values.into_iter().next().flatten();
+        Ok(())
+    }
+}
+
+impl RESPCommand for HGet {
+    const NAME: &'static str = "hget";
+
+    fn parse(args: &mut CommandArgs) -> Result<HGet> {
+        let key = args.next_bytes()?;
+        let field = args.next_bytes()?;
+        Ok(HGet {
+            key,
+            field,
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(value) => Frame::BulkString(value.clone()),
+            None => Frame::Null,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+/// Shared by `HGETALL`, `HKEYS` and `HVALS`, they differ only in what they reply with.
+#[derive(Debug)]
+struct GetAllArgs {
+    key: Bytes,
+    result: Vec<(Bytes, Bytes)>,
+}
+
+impl GetAllArgs {
+    fn parse(args: &mut CommandArgs) -> Result<GetAllArgs> {
+        let key = args.next_bytes()?;
+        Ok(GetAllArgs {
+            key,
+            result: vec![],
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.hash_get_all(&self.key).await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HGetAll(GetAllArgs);
+
+impl HGetAll {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HGetAll {
+    const NAME: &'static str = "hgetall";
+
+    fn parse(args: &mut CommandArgs) -> Result<HGetAll> {
+        Ok(HGetAll(GetAllArgs::parse(args)?))
+    }
+
+    // a map for RESP3 clients, RESP2 ones get it flattened into an array
+    fn to_response(&self) -> Frame {
+        Frame::Map(
+            self.0
+                .result
+                .iter()
+                .map(|(field, value)| {
+                    (
+                        Frame::BulkString(field.clone()),
+                        Frame::BulkString(value.clone()),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HKeys(GetAllArgs);
+
+impl HKeys {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HKeys {
+    const NAME: &'static str = "hkeys";
+
+    fn parse(args: &mut CommandArgs) -> Result<HKeys> {
+        Ok(HKeys(GetAllArgs::parse(args)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Array(
+            self.0
+                .result
+                .iter()
+                .map(|(field, _)| Frame::BulkString(field.clone()))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HVals(GetAllArgs);
+
+impl HVals {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HVals {
+    const NAME: &'static str = "hvals";
+
+    fn parse(args: &mut CommandArgs) -> Result<HVals> {
+        Ok(HVals(GetAllArgs::parse(args)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Array(
+            self.0
+                .result
+                .iter()
+                .map(|(_, value)| Frame::BulkString(value.clone()))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{parse_float, parse_integer, CommandArgs, RESPCommand};
+use crate::redis::frame::format_human_double;
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct HIncrBy {
+    key: Bytes,
+    field: Bytes,
+    increment: i64,
+    result: i64,
+}
+
+impl HIncrBy {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let mut result = 0;
+        storage
+            .hash_update(&self.key, &self.field, |current| {
+                let current = match current {
+                    Some(current) => {
+                        parse_integer(current).ok_or(CmdErrors::HashValueNotAnInteger)?
+                    }
+                    None => 0,
+                };
+                result = current
+                    .checked_add(self.increment)
+                    .ok_or(CmdErrors::Overflow)?;
+                Ok(Bytes::from(result.to_string()))
+            })
+            .await?;
+        self.result = result;
+        Ok(())
+    }
+}
+
+impl RESPCommand for HIncrBy {
+    const NAME: &'static str = "hincrby";
+
+    fn parse(args: &mut CommandArgs) -> Result<HIncrBy> {
+        let key = args.next_bytes()?;
+        let field = args.next_bytes()?;
+        let increment = args.next_integer()?;
+        Ok(HIncrBy {
+            key,
+            field,
+            increment,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HIncrByFloat {
+    key: Bytes,
+    field: Bytes,
+    increment: f64,
+    result: Bytes,
+}
+
+impl HIncrByFloat {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let mut result = Bytes::new();
+        storage
+            .hash_update(&self.key, &self.field, |current| {
+                let current = match current {
+                    Some(current) => parse_float(current).ok_or(CmdErrors::HashValueNotAFloat)?,
+                    None => 0.0,
+                };
+                let sum = current + self.increment;
+                if !sum.is_finite() {
+                    return Err(CmdErrors::NaNOrInfinity);
+                }
+                result = Bytes::from(format_human_double(sum));
+                Ok(result.clone())
+            })
+            .await?;
+        self.result = result;
+        Ok(())
+    }
+}
+
+impl RESPCommand for HIncrByFloat {
+    const NAME: &'static str = "hincrbyfloat";
+
+    fn parse(args: &mut CommandArgs) -> Result<HIncrByFloat> {
+        let key = args.next_bytes()?;
+        let field = args.next_bytes()?;
+        let increment = args.next_float()?;
+        Ok(HIncrByFloat {
+            key,
+            field,
+            increment,
+            result: Bytes::new(),
+        })
+    }
+
+    // the new value as it is stored, not as a RESP3 double
+    fn to_response(&self) -> Frame {
+        Frame::BulkString(self.result.clone())
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct HLen {
+    key: Bytes,
+    result: usize,
+}
+
+impl HLen {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.hash_len(&self.key).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for HLen {
+    const NAME: &'static str = "hlen";
+
+    fn parse(args: &mut CommandArgs) -> Result<HLen> {
+        let key = args.next_bytes()?;
+        Ok(HLen { key, result: 0 })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
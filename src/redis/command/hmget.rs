@@ -0,0 +1,49 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct HMGet {
+    key: Bytes,
+    fields: Vec<Bytes>,
+    result: Vec<Option<Bytes>>,
+}
+
+impl HMGet {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.hash_get(&self.key, &self.fields).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for HMGet {
+    const NAME: &'static str = "hmget";
+
+    fn parse(args: &mut CommandArgs) -> Result<HMGet> {
+        let key = args.next_bytes()?;
+        let mut fields = vec![args.next_bytes()?];
+        while let Some(field) = args.next_optional_bytes()? {
+            fields.push(field);
+        }
+
+        Ok(HMGet {
+            key,
+            fields,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Array(
+            self.result
+                .iter()
+                .map(|value| match value {
+                    Some(value) => Frame::BulkString(value.clone()),
+                    None => Frame::Null,
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::hexpire::parse_fields;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::KeyExpiry;
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct HPersist {
+    key: Bytes,
+    fields: Vec<Bytes>,
+    // expiry of the fields before the command
+    result: Vec<KeyExpiry>,
+}
+
+impl HPersist {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.hash_persist(&self.key, &self.fields).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for HPersist {
+    const NAME: &'static str = "hpersist";
+
+    fn parse(args: &mut CommandArgs) -> Result<HPersist> {
+        let key = args.next_bytes()?;
+        let fields = parse_fields(args)?;
+        Ok(HPersist {
+            key,
+            fields,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        let code = |expiry: &KeyExpiry| match expiry {
+            KeyExpiry::Missing => -2,
+            KeyExpiry::Persistent => -1,
+            KeyExpiry::At(_) => 1,
+        };
+        Frame::Array(
+            self.result
+                .iter()
+                .map(|expiry| Frame::Integer(code(expiry)))
+                .collect(),
+        )
+    }
+}
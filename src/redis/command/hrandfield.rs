@@ -0,0 +1,66 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct HRandField {
+    key: Bytes,
+    // without a count the reply is a single field instead of an array
+    count: Option<i64>,
+    with_values: bool,
+    result: Vec<(Bytes, Bytes)>,
+}
+
+impl HRandField {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .hash_random_fields(&self.key, self.count.unwrap_or(1))
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for HRandField {
+    const NAME: &'static str = "hrandfield";
+
+    fn parse(args: &mut CommandArgs) -> Result<HRandField> {
+        let key = args.next_bytes()?;
+        let count = args.next_optional_integer()?;
+        // like Redis, so `-count` with values still fits
+        if count.is_some_and(|count| count < -(i64::MAX / 2)) {
+            return Err(CmdErrors::ValueOutOfRange.into());
+        }
+        let with_values = match args.next_optional_bytes()? {
+            None => false,
+            Some(option) if option.eq_ignore_ascii_case(b"withvalues") => true,
+            Some(option) => return Err(args.syntax_error(&option)),
+        };
+
+        Ok(HRandField {
+            key,
+            count,
+            with_values,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        if self.count.is_none() {
+            return match self.result.first() {
+                Some((field, _)) => Frame::BulkString(field.clone()),
+                None => Frame::Null,
+            };
+        }
+
+        let mut items = vec![];
+        for (field, value) in &self.result {
+            items.push(Frame::BulkString(field.clone()));
+            if self.with_values {
+                items.push(Frame::BulkString(value.clone()));
+            }
+        }
+        Frame::Array(items)
+    }
+}
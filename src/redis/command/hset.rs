@@ -0,0 +1,41 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct HSet {
+    key: Bytes,
+    pairs: Vec<(Bytes, Bytes)>,
+    result: usize,
+}
+
+impl HSet {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.hash_set(&self.key, &self.pairs).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for HSet {
+    const NAME: &'static str = "hset";
+
+    fn parse(args: &mut CommandArgs) -> Result<HSet> {
+        let key = args.next_bytes()?;
+        let mut pairs = vec![(args.next_bytes()?, args.next_bytes()?)];
+        while let Some(field) = args.next_optional_bytes()? {
+            pairs.push((field, args.next_bytes()?));
+        }
+
+        Ok(HSet {
+            key,
+            pairs,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct HSetNx {
+    key: Bytes,
+    field: Bytes,
+    value: Bytes,
+    result: bool,
+}
+
+impl HSetNx {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .hash_set_if_missing(&self.key, &self.field, &self.value)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for HSetNx {
+    const NAME: &'static str = "hsetnx";
+
+    fn parse(args: &mut CommandArgs) -> Result<HSetNx> {
+        let key = args.next_bytes()?;
+        let field = args.next_bytes()?;
+        let value = args.next_bytes()?;
+        Ok(HSetNx {
+            key,
+            field,
+            value,
+            result: false,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(i64::from(self.result))
+    }
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::expire::TimeUnit;
+use super::hexpire::parse_fields;
+use super::ttl::expiry_frame;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::KeyExpiry;
+use crate::redis::{Frame, Storage};
+
+/// Shared by `HTTL`, `HPTTL`, `HEXPIRETIME` and `HPEXPIRETIME`, they reply per field
+/// the same way the TTL family replies for keys.
+#[derive(Debug)]
+struct HTtlArgs {
+    key: Bytes,
+    fields: Vec<Bytes>,
+    result: Vec<KeyExpiry>,
+}
+
+impl HTtlArgs {
+    fn parse(args: &mut CommandArgs) -> Result<HTtlArgs> {
+        let key = args.next_bytes()?;
+        let fields = parse_fields(args)?;
+        Ok(HTtlArgs {
+            key,
+            fields,
+            result: vec![],
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.hash_expiry(&self.key, &self.fields).await?;
+        Ok(())
+    }
+
+    fn to_response(&self, unit: TimeUnit, absolute: bool) -> Frame {
+        Frame::Array(
+            self.result
+                .iter()
+                .map(|expiry| expiry_frame(*expiry, unit, absolute))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HTtl(HTtlArgs);
+
+impl HTtl {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HTtl {
+    const NAME: &'static str = "httl";
+
+    fn parse(args: &mut CommandArgs) -> Result<HTtl> {
+        Ok(HTtl(HTtlArgs::parse(args)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response(TimeUnit::Seconds, false)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HPTtl(HTtlArgs);
+
+impl HPTtl {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HPTtl {
+    const NAME: &'static str = "hpttl";
+
+    fn parse(args: &mut CommandArgs) -> Result<HPTtl> {
+        Ok(HPTtl(HTtlArgs::parse(args)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response(TimeUnit::Milliseconds, false)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HExpireTime(HTtlArgs);
+
+impl HExpireTime {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HExpireTime {
+    const NAME: &'static str = "hexpiretime";
+
+    fn parse(args: &mut CommandArgs) -> Result<HExpireTime> {
+        Ok(HExpireTime(HTtlArgs::parse(args)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response(TimeUnit::Seconds, true)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HPExpireTime(HTtlArgs);
+
+impl HPExpireTime {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for HPExpireTime {
+    const NAME: &'static str = "hpexpiretime";
+
+    fn parse(args: &mut CommandArgs) -> Result<HPExpireTime> {
+        Ok(HPExpireTime(HTtlArgs::parse(args)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response(TimeUnit::Milliseconds, true)
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{parse_integer, CommandArgs, RESPCommand};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// Shared by `INCR`, `DECR`, `INCRBY` and `DECRBY`, decrements are negative increments.
+#[derive(Debug)]
+struct IncrArgs {
+    key: Bytes,
+    increment: i64,
+    result: i64,
+}
+
+impl IncrArgs {
+    fn new(key: Bytes, increment: i64) -> IncrArgs {
+        IncrArgs {
+            key,
+            increment,
+            result: 0,
+        }
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let mut result = 0;
+        storage
+            .string_update(&self.key, |current| {
+                let current = match current {
+                    Some(current) => parse_integer(current).ok_or(CmdErrors::NotAnInteger)?,
+                    None => 0,
+                };
+                result = current
+                    .checked_add(self.increment)
+                    .ok_or(CmdErrors::Overflow)?;
+                Ok(Bytes::from(result.to_string()))
+            })
+            .await?;
+        self.result = result;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Incr(IncrArgs);
+
+impl Incr {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for Incr {
+    const NAME: &'static str = "incr";
+
+    fn parse(args: &mut CommandArgs) -> Result<Incr> {
+        Ok(Incr(IncrArgs::new(args.next_bytes()?, 1)))
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.0.result)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Decr(IncrArgs);
+
+impl Decr {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for Decr {
+    const NAME: &'static str = "decr";
+
+    fn parse(args: &mut CommandArgs) -> Result<Decr> {
+        Ok(Decr(IncrArgs::new(args.next_bytes()?, -1)))
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.0.result)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct IncrBy(IncrArgs);
+
+impl IncrBy {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for IncrBy {
+    const NAME: &'static str = "incrby";
+
+    fn parse(args: &mut CommandArgs) -> Result<IncrBy> {
+        let key = args.next_bytes()?;
+        let increment = args.next_integer()?;
+        Ok(IncrBy(IncrArgs::new(key, increment)))
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.0.result)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DecrBy(IncrArgs);
+
+impl DecrBy {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for DecrBy {
+    const NAME: &'static str = "decrby";
+
+    fn parse(args: &mut CommandArgs) -> Result<DecrBy> {
+        let key = args.next_bytes()?;
+        let decrement = args.next_integer()?;
+        // `i64::MIN` has no positive counterpart
+        let increment = decrement
+            .checked_neg()
+            .ok_or(CmdErrors::DecrementOverflow)?;
+        Ok(DecrBy(IncrArgs::new(key, increment)))
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.0.result)
+    }
+}
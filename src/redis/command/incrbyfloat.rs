@@ -0,0 +1,54 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{parse_float, CommandArgs, RESPCommand};
+use crate::redis::frame::format_human_double;
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct IncrByFloat {
+    key: Bytes,
+    increment: f64,
+    result: Bytes,
+}
+
+impl IncrByFloat {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let mut result = Bytes::new();
+        storage
+            .string_update(&self.key, |current| {
+                let current = match current {
+                    Some(current) => parse_float(current).ok_or(CmdErrors::NotAFloat)?,
+                    None => 0.0,
+                };
+                let sum = current + self.increment;
+                if !sum.is_finite() {
+                    return Err(CmdErrors::NaNOrInfinity);
+                }
+                result = Bytes::from(format_human_double(sum));
+                Ok(result.clone())
+            })
+            .await?;
+        self.result = result;
+        Ok(())
+    }
+}
+
+impl RESPCommand for IncrByFloat {
+    const NAME: &'static str = "incrbyfloat";
+
+    fn parse(args: &mut CommandArgs) -> Result<IncrByFloat> {
+        let key = args.next_bytes()?;
+        let increment = args.next_float()?;
+        Ok(IncrByFloat {
+            key,
+            increment,
+            result: Bytes::new(),
+        })
+    }
+
+    // the new value as it is stored, not as a RESP3 double
+    fn to_response(&self) -> Frame {
+        Frame::BulkString(self.result.clone())
+    }
+}
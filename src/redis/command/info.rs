@@ -0,0 +1,98 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::fmt::Write;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::StorageInfo;
+use crate::redis::{Frame, Storage};
+
+const SECTIONS: [&str; 3] = ["server", "stats", "keyspace"];
+
+#[derive(Debug)]
+pub(crate) struct Info {
+    sections: Vec<String>,
+    result: StorageInfo,
+}
+
+impl Info {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.result = storage.info().await;
+    }
+
+    fn server_section(&self, text: &mut String) {
+        let _ = writeln!(text, "# Server\r");
+        let _ = writeln!(text, "rredis_version:{}\r", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(text, "process_id:{}\r", std::process::id());
+    }
+
+    fn stats_section(&self, text: &mut String) {
+        let stats = &self.result.stats;
+        let _ = writeln!(text, "# Stats\r");
+        let _ = writeln!(text, "expired_keys:{}\r", stats.expired_keys);
+        let _ = writeln!(text, "expired_subkeys:{}\r", stats.expired_subkeys);
+        let _ = writeln!(text, "expired_stale_perc:{:.2}\r", stats.expired_stale_perc);
+        let _ = writeln!(
+            text,
+            "active_expire_cycles:{}\r",
+            stats.active_expire_cycles
+        );
+    }
+
+    fn keyspace_section(&self, text: &mut String) {
+        let _ = writeln!(text, "# Keyspace\r");
+        if self.result.keys > 0 {
+            let _ = writeln!(
+                text,
+                "db0:keys={},expires={}\r",
+                self.result.keys, self.result.volatile_keys
+            );
+        }
+    }
+}
+
+impl RESPCommand for Info {
+    const NAME: &'static str = "info";
+
+    fn parse(args: &mut CommandArgs) -> Result<Info> {
+        let mut sections = Vec::new();
+        while let Some(section) = args.next_optional_bytes()? {
+            match &section.to_ascii_lowercase()[..] {
+                b"all" | b"default" | b"everything" => {
+                    sections.extend(SECTIONS.iter().map(|section| section.to_string()))
+                }
+                // unknown sections are skipped, like in Redis
+                section => sections.push(String::from_utf8_lossy(section).into_owned()),
+            }
+        }
+        if sections.is_empty() {
+            sections.extend(SECTIONS.iter().map(|section| section.to_string()));
+        }
+
+        Ok(Info {
+            sections,
+            result: StorageInfo::default(),
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        let mut text = String::new();
+        for section in SECTIONS {
+            if !self.sections.iter().any(|requested| requested == section) {
+                continue;
+            }
+            if !text.is_empty() {
+                text.push_str("\r\n");
+            }
+            match section {
+                "server" => self.server_section(&mut text),
+                "stats" => self.stats_section(&mut text),
+                _ => self.keyspace_section(&mut text),
+            }
+        }
+
+        Frame::VerbatimString {
+            format: *b"txt",
+            data: Bytes::from(text),
+        }
+    }
+}
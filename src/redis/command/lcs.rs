@@ -0,0 +1,212 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+// the table of lengths is limited like the biggest string, as in Redis
+const LCS_MAX_TABLE_SIZE: usize = 512 * 1024 * 1024;
+
+/// Common ranges of both strings, inclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Match {
+    first: (usize, usize),
+    second: (usize, usize),
+}
+
+impl Match {
+    fn len(&self) -> usize {
+        self.first.1 - self.first.0 + 1
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Subsequence {
+    common: Vec<u8>,
+    // from the end of the strings to their start, like in Redis
+    matches: Vec<Match>,
+}
+
+/// Longest common subsequence with dynamic programming, matching ranges are
+/// found walking the table back from the end.
+fn longest_common_subsequence(first: &[u8], second: &[u8]) -> Result<Subsequence, CmdErrors> {
+    let width = second.len() + 1;
+    let cells = (first.len() + 1)
+        .checked_mul(width)
+        .filter(|cells| cells.saturating_mul(size_of::<u32>()) <= LCS_MAX_TABLE_SIZE)
+        .ok_or(CmdErrors::LcsTooBig)?;
+    let mut table = vec![0u32; cells];
+    for i in 1..=first.len() {
+        for j in 1..=second.len() {
+            table[i * width + j] = match first[i - 1] == second[j - 1] {
+                true => table[(i - 1) * width + j - 1] + 1,
+                false => table[(i - 1) * width + j].max(table[i * width + j - 1]),
+            };
+        }
+    }
+
+    let mut lcs = Subsequence::default();
+    // the range being extended backwards, `None` between ranges
+    let mut current: Option<Match> = None;
+    let (mut i, mut j) = (first.len(), second.len());
+    while i > 0 && j > 0 {
+        let mut emit = false;
+        if first[i - 1] == second[j - 1] {
+            lcs.common.push(first[i - 1]);
+            match &mut current {
+                None => {
+                    current = Some(Match {
+                        first: (i - 1, i - 1),
+                        second: (j - 1, j - 1),
+                    })
+                }
+                Some(range) if range.first.0 == i && range.second.0 == j => {
+                    range.first.0 -= 1;
+                    range.second.0 -= 1;
+                }
+                Some(_) => emit = true,
+            }
+            // the first byte of one of the strings, nothing comes before it
+            if current.is_some_and(|range| range.first.0 == 0 || range.second.0 == 0) {
+                emit = true;
+            }
+            i -= 1;
+            j -= 1;
+        } else {
+            match table[(i - 1) * width + j] > table[i * width + j - 1] {
+                true => i -= 1,
+                false => j -= 1,
+            }
+            emit = current.is_some();
+        }
+
+        if emit {
+            lcs.matches.extend(current.take());
+        }
+    }
+
+    lcs.common.reverse();
+    Ok(lcs)
+}
+
+#[derive(Debug)]
+pub(crate) struct Lcs {
+    first: Bytes,
+    second: Bytes,
+    // `LEN`: only the length of the subsequence
+    len: bool,
+    // `IDX`: ranges of the matches instead of the subsequence
+    idx: bool,
+    min_match_len: usize,
+    with_match_len: bool,
+    result: Subsequence,
+}
+
+impl Lcs {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let (first, second) = storage.string_pair(&self.first, &self.second).await?;
+        self.result = longest_common_subsequence(&first, &second)?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for Lcs {
+    const NAME: &'static str = "lcs";
+
+    fn parse(args: &mut CommandArgs) -> Result<Lcs> {
+        let first = args.next_bytes()?;
+        let second = args.next_bytes()?;
+
+        let (mut len, mut idx, mut min_match_len, mut with_match_len) = (false, false, 0, false);
+        while let Some(option) = args.next_optional_bytes()? {
+            match &option.to_ascii_lowercase()[..] {
+                b"len" => len = true,
+                b"idx" => idx = true,
+                // a negative length is no limit at all
+                b"minmatchlen" => {
+                    min_match_len = usize::try_from(args.next_integer()?).unwrap_or(0)
+                }
+                b"withmatchlen" => with_match_len = true,
+                _ => return Err(args.syntax_error(&option)),
+            }
+        }
+        if len && idx {
+            return Err(CmdErrors::LcsLenAndIdx.into());
+        }
+
+        Ok(Lcs {
+            first,
+            second,
+            len,
+            idx,
+            min_match_len,
+            with_match_len,
+            result: Subsequence::default(),
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        let common_len = Frame::Integer(self.result.common.len() as i64);
+        if self.len {
+            return common_len;
+        }
+        if !self.idx {
+            return Frame::BulkString(Bytes::from(self.result.common.clone()));
+        }
+
+        let range = |(start, end): (usize, usize)| {
+            Frame::Array(vec![
+                Frame::Integer(start as i64),
+                Frame::Integer(end as i64),
+            ])
+        };
+        let matches = self
+            .result
+            .matches
+            .iter()
+            .filter(|found| found.len() >= self.min_match_len)
+            .map(|found| {
+                let mut items = vec![range(found.first), range(found.second)];
+                if self.with_match_len {
+                    items.push(Frame::Integer(found.len() as i64));
+                }
+                Frame::Array(items)
+            })
+            .collect();
+        Frame::Map(vec![
+            (
+                Frame::BulkString(Bytes::from_static(b"matches")),
+                Frame::Array(matches),
+            ),
+            (Frame::BulkString(Bytes::from_static(b"len")), common_len),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_common_subsequence() {
+        let lcs = longest_common_subsequence(b"ohmytext", b"mynewtext").unwrap();
+        assert_eq!(lcs.common, b"mytext");
+        assert_eq!(
+            lcs.matches,
+            vec![
+                Match {
+                    first: (4, 7),
+                    second: (5, 8)
+                },
+                Match {
+                    first: (2, 3),
+                    second: (0, 1)
+                },
+            ]
+        );
+        assert_eq!(
+            longest_common_subsequence(b"", b"text"),
+            Ok(Subsequence::default())
+        );
+    }
+}
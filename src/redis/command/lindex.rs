@@ -0,0 +1,40 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct LIndex {
+    key: Bytes,
+    index: i64,
+    result: Option<Bytes>,
+}
+
+impl LIndex {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.list_index(&self.key, self.index).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for LIndex {
+    const NAME: &'static str = "lindex";
+
+    fn parse(args: &mut CommandArgs) -> Result<LIndex> {
+        let key = args.next_bytes()?;
+        let index = args.next_integer()?;
+        Ok(LIndex {
+            key,
+            index,
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(value) => Frame::BulkString(value.clone()),
+            None => Frame::Null,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct LInsert {
+    key: Bytes,
+    // `BEFORE` or `AFTER` the pivot
+    before: bool,
+    pivot: Bytes,
+    value: Bytes,
+    result: i64,
+}
+
+impl LInsert {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .list_insert(&self.key, self.before, &self.pivot, &self.value)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for LInsert {
+    const NAME: &'static str = "linsert";
+
+    fn parse(args: &mut CommandArgs) -> Result<LInsert> {
+        let key = args.next_bytes()?;
+        let position = args.next_bytes()?;
+        let before = match &position.to_ascii_lowercase()[..] {
+            b"before" => true,
+            b"after" => false,
+            _ => return Err(args.syntax_error(&position)),
+        };
+        let pivot = args.next_bytes()?;
+        let value = args.next_bytes()?;
+
+        Ok(LInsert {
+            key,
+            before,
+            pivot,
+            value,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result)
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct LLen {
+    key: Bytes,
+    result: usize,
+}
+
+impl LLen {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.list_len(&self.key).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for LLen {
+    const NAME: &'static str = "llen";
+
+    fn parse(args: &mut CommandArgs) -> Result<LLen> {
+        let key = args.next_bytes()?;
+        Ok(LLen { key, result: 0 })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::ListEnd;
+use crate::redis::{Frame, Storage};
+
+/// `LEFT | RIGHT` argument of the commands that move elements between lists.
+pub(super) fn parse_list_end(args: &mut CommandArgs) -> Result<ListEnd> {
+    let end = args.next_bytes()?;
+    match &end.to_ascii_lowercase()[..] {
+        b"left" => Ok(ListEnd::Left),
+        b"right" => Ok(ListEnd::Right),
+        _ => Err(args.syntax_error(&end)),
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct LMove {
+    source: Bytes,
+    destination: Bytes,
+    from: ListEnd,
+    to: ListEnd,
+    result: Option<Bytes>,
+}
+
+impl LMove {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .list_move(&self.source, &self.destination, self.from, self.to)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for LMove {
+    const NAME: &'static str = "lmove";
+
+    fn parse(args: &mut CommandArgs) -> Result<LMove> {
+        let source = args.next_bytes()?;
+        let destination = args.next_bytes()?;
+        let from = parse_list_end(args)?;
+        let to = parse_list_end(args)?;
+
+        Ok(LMove {
+            source,
+            destination,
+            from,
+            to,
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Some(value) => Frame::BulkString(value.clone()),
+            None => Frame::Null,
+        }
+    }
+}
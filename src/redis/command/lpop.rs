@@ -0,0 +1,92 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::ListEnd;
+use crate::redis::{Frame, Storage};
+
+/// Shared by `LPOP` and `RPOP`, they differ only in the end of the list they pop from.
+#[derive(Debug)]
+struct PopArgs {
+    key: Bytes,
+    end: ListEnd,
+    // without `COUNT` the reply is a single element instead of an array
+    count: Option<usize>,
+    result: Option<Vec<Bytes>>,
+}
+
+impl PopArgs {
+    fn parse(args: &mut CommandArgs, end: ListEnd) -> Result<PopArgs> {
+        let key = args.next_bytes()?;
+        let count = args.next_optional_count()?;
+
+        Ok(PopArgs {
+            key,
+            end,
+            count,
+            result: None,
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .list_pop(&self.key, self.end, self.count.unwrap_or(1))
+            .await?;
+        Ok(())
+    }
+
+    fn to_response(&self) -> Frame {
+        match (&self.result, self.count) {
+            (None, None) => Frame::Null,
+            (None, Some(_)) => Frame::NullArray,
+            (Some(values), None) => values
+                .first()
+                .map_or(Frame::Null, |value| Frame::BulkString(value.clone())),
+            (Some(values), Some(_)) => {
+                Frame::Array(values.iter().cloned().map(Frame::BulkString).collect())
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct LPop(PopArgs);
+
+impl LPop {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for LPop {
+    const NAME: &'static str = "lpop";
+
+    fn parse(args: &mut CommandArgs) -> Result<LPop> {
+        Ok(LPop(PopArgs::parse(args, ListEnd::Left)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RPop(PopArgs);
+
+impl RPop {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for RPop {
+    const NAME: &'static str = "rpop";
+
+    fn parse(args: &mut CommandArgs) -> Result<RPop> {
+        Ok(RPop(PopArgs::parse(args, ListEnd::Right)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct LPos {
+    key: Bytes,
+    value: Bytes,
+    rank: i64,
+    // without `COUNT` the reply is a single index instead of an array
+    count: Option<usize>,
+    max_len: usize,
+    result: Vec<usize>,
+}
+
+impl LPos {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .list_position(
+                &self.key,
+                &self.value,
+                self.rank,
+                self.count.unwrap_or(1),
+                self.max_len,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for LPos {
+    const NAME: &'static str = "lpos";
+
+    fn parse(args: &mut CommandArgs) -> Result<LPos> {
+        let key = args.next_bytes()?;
+        let value = args.next_bytes()?;
+
+        let mut rank = 1;
+        let mut count = None;
+        let mut max_len = 0;
+        let non_negative = |value: i64, name| {
+            usize::try_from(value).map_err(|_| CmdErrors::NegativeArgument(name))
+        };
+        while let Some(option) = args.next_optional_bytes()? {
+            match &option.to_ascii_lowercase()[..] {
+                b"rank" => {
+                    rank = match args.next_integer()? {
+                        0 => return Err(CmdErrors::ZeroRank.into()),
+                        // its opposite doesn't fit, the same as in Redis
+                        i64::MIN => return Err(CmdErrors::NotAnInteger.into()),
+                        rank => rank,
+                    }
+                }
+                b"count" => count = Some(non_negative(args.next_integer()?, "COUNT")?),
+                b"maxlen" => max_len = non_negative(args.next_integer()?, "MAXLEN")?,
+                _ => return Err(args.syntax_error(&option)),
+            }
+        }
+
+        Ok(LPos {
+            key,
+            value,
+            rank,
+            count,
+            max_len,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        let index = |index: &usize| Frame::Integer(*index as i64);
+        match self.count {
+            None => self.result.first().map_or(Frame::Null, index),
+            Some(_) => Frame::Array(self.result.iter().map(index).collect()),
+        }
+    }
+}
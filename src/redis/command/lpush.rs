@@ -0,0 +1,83 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::ListEnd;
+use crate::redis::{Frame, Storage};
+
+/// Shared by `LPUSH` and `RPUSH`, they differ only in the end of the list they push to.
+#[derive(Debug)]
+struct PushArgs {
+    key: Bytes,
+    values: Vec<Bytes>,
+    end: ListEnd,
+    result: usize,
+}
+
+impl PushArgs {
+    fn parse(args: &mut CommandArgs, end: ListEnd) -> Result<PushArgs> {
+        let key = args.next_bytes()?;
+        let mut values = vec![args.next_bytes()?];
+        while let Some(value) = args.next_optional_bytes()? {
+            values.push(value);
+        }
+
+        Ok(PushArgs {
+            key,
+            values,
+            end,
+            result: 0,
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.list_push(&self.key, &self.values, self.end).await?;
+        Ok(())
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct LPush(PushArgs);
+
+impl LPush {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for LPush {
+    const NAME: &'static str = "lpush";
+
+    fn parse(args: &mut CommandArgs) -> Result<LPush> {
+        Ok(LPush(PushArgs::parse(args, ListEnd::Left)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RPush(PushArgs);
+
+impl RPush {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for RPush {
+    const NAME: &'static str = "rpush";
+
+    fn parse(args: &mut CommandArgs) -> Result<RPush> {
+        Ok(RPush(PushArgs::parse(args, ListEnd::Right)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct LRange {
+    key: Bytes,
+    start: i64,
+    stop: i64,
+    result: Vec<Bytes>,
+}
+
+impl LRange {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.list_range(&self.key, self.start, self.stop).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for LRange {
+    const NAME: &'static str = "lrange";
+
+    fn parse(args: &mut CommandArgs) -> Result<LRange> {
+        let key = args.next_bytes()?;
+        let start = args.next_integer()?;
+        let stop = args.next_integer()?;
+        Ok(LRange {
+            key,
+            start,
+            stop,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Array(self.result.iter().cloned().map(Frame::BulkString).collect())
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct LRem {
+    key: Bytes,
+    count: i64,
+    value: Bytes,
+    result: usize,
+}
+
+impl LRem {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .list_remove(&self.key, self.count, &self.value)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for LRem {
+    const NAME: &'static str = "lrem";
+
+    fn parse(args: &mut CommandArgs) -> Result<LRem> {
+        let key = args.next_bytes()?;
+        let count = args.next_integer()?;
+        let value = args.next_bytes()?;
+        Ok(LRem {
+            key,
+            count,
+            value,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
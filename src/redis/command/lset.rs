@@ -0,0 +1,34 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct LSet {
+    key: Bytes,
+    index: i64,
+    value: Bytes,
+}
+
+impl LSet {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        storage.list_set(&self.key, self.index, &self.value).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for LSet {
+    const NAME: &'static str = "lset";
+
+    fn parse(args: &mut CommandArgs) -> Result<LSet> {
+        let key = args.next_bytes()?;
+        let index = args.next_integer()?;
+        let value = args.next_bytes()?;
+        Ok(LSet { key, index, value })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::SimpleString(Bytes::from_static(b"OK"))
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct LTrim {
+    key: Bytes,
+    start: i64,
+    stop: i64,
+}
+
+impl LTrim {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        storage.list_trim(&self.key, self.start, self.stop).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for LTrim {
+    const NAME: &'static str = "ltrim";
+
+    fn parse(args: &mut CommandArgs) -> Result<LTrim> {
+        let key = args.next_bytes()?;
+        let start = args.next_integer()?;
+        let stop = args.next_integer()?;
+        Ok(LTrim { key, start, stop })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::SimpleString(Bytes::from_static(b"OK"))
+    }
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct MGet {
+    keys: Vec<Bytes>,
+    result: Vec<Option<Bytes>>,
+}
+
+impl MGet {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.string_get_many(&self.keys).await;
+        Ok(())
+    }
+}
+
+impl RESPCommand for MGet {
+    const NAME: &'static str = "mget";
+
+    fn parse(args: &mut CommandArgs) -> Result<MGet> {
+        let mut keys = vec![args.next_bytes()?];
+        while let Some(key) = args.next_optional_bytes()? {
+            keys.push(key);
+        }
+        Ok(MGet {
+            keys,
+            result: vec![],
+        })
+    }
+
+    // keys that don't hold strings are nulls
+    fn to_response(&self) -> Frame {
+        Frame::Array(
+            self.result
+                .iter()
+                .map(|value| match value {
+                    Some(value) => Frame::BulkString(value.clone()),
+                    None => Frame::Null,
+                })
+                .collect(),
+        )
+    }
+}
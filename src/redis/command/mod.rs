@@ -1,9 +1,10 @@
 use anyhow::Result;
 use bytes::Bytes;
 use std::slice::Iter;
+use std::time::Duration;
 
 use crate::redis::CmdErrors;
-use crate::redis::Frame;
+use crate::redis::{Frame, Storage};
 
 mod ping;
 use ping::Ping;
@@ -13,26 +14,317 @@ mod set;
 use set::Set;
 mod get;
 use get::Get;
+mod hello;
+use hello::Hello;
+mod expire;
+use expire::{Expire, ExpireAt, PExpire, PExpireAt};
+mod ttl;
+use ttl::{ExpireTime, PExpireTime, PTtl, Ttl};
+mod persist;
+use persist::Persist;
+mod info;
+use info::Info;
+mod r#type;
+use r#type::Type;
+mod lpush;
+use lpush::{LPush, RPush};
+mod lpop;
+use lpop::{LPop, RPop};
+mod llen;
+use llen::LLen;
+mod lrange;
+use lrange::LRange;
+mod lindex;
+use lindex::LIndex;
+mod lset;
+use lset::LSet;
+mod lrem;
+use lrem::LRem;
+mod ltrim;
+use ltrim::LTrim;
+mod linsert;
+use linsert::LInsert;
+mod lpos;
+use lpos::LPos;
+mod lmove;
+use lmove::LMove;
+mod blpop;
+use blpop::{BLPop, BRPop};
+mod blmove;
+use blmove::BLMove;
+mod blmpop;
+use blmpop::BLMPop;
+mod hset;
+use hset::HSet;
+mod hsetnx;
+use hsetnx::HSetNx;
+mod hget;
+use hget::HGet;
+mod hmget;
+use hmget::HMGet;
+mod hdel;
+use hdel::HDel;
+mod hgetall;
+use hgetall::{HGetAll, HKeys, HVals};
+mod hlen;
+use hlen::HLen;
+mod hexists;
+use hexists::HExists;
+mod hincrby;
+use hincrby::{HIncrBy, HIncrByFloat};
+mod hrandfield;
+use hrandfield::HRandField;
+mod hexpire;
+use hexpire::{HExpire, HExpireAt, HPExpire, HPExpireAt};
+mod httl;
+use httl::{HExpireTime, HPExpireTime, HPTtl, HTtl};
+mod hpersist;
+use hpersist::HPersist;
+mod sadd;
+use sadd::SAdd;
+mod srem;
+use srem::SRem;
+mod smembers;
+use smembers::SMembers;
+mod sismember;
+use sismember::{SIsMember, SMIsMember};
+mod scard;
+use scard::SCard;
+mod spop;
+use spop::SPop;
+mod srandmember;
+use srandmember::SRandMember;
+mod sinter;
+use sinter::{SDiff, SInter, SUnion};
+mod sinterstore;
+use sinterstore::{SDiffStore, SInterStore, SUnionStore};
+mod zadd;
+use zadd::ZAdd;
+mod zrange;
+use zrange::ZRange;
+mod zrank;
+use zrank::ZRank;
+mod zscore;
+use zscore::ZScore;
+mod zincrby;
+use zincrby::ZIncrBy;
+mod zrem;
+use zrem::ZRem;
+mod zcard;
+use zcard::ZCard;
+mod zcount;
+use zcount::ZCount;
+mod zpopmin;
+use zpopmin::{ZPopMax, ZPopMin};
+mod zunionstore;
+use zunionstore::{ZInterStore, ZUnionStore};
+mod bzpopmin;
+use bzpopmin::{BZPopMax, BZPopMin};
+mod bzmpop;
+use bzmpop::BZMPop;
+mod xack;
+use xack::XAck;
+mod xadd;
+use xadd::XAdd;
+mod xautoclaim;
+use xautoclaim::XAutoClaim;
+mod xclaim;
+use xclaim::XClaim;
+mod xdel;
+use xdel::XDel;
+mod xgroup;
+use xgroup::XGroup;
+mod xinfo;
+use xinfo::XInfo;
+mod incr;
+use incr::{Decr, DecrBy, Incr, IncrBy};
+mod incrbyfloat;
+use incrbyfloat::IncrByFloat;
+mod append;
+use append::Append;
+mod strlen;
+use strlen::StrLen;
+mod getrange;
+use getrange::GetRange;
+mod setrange;
+use setrange::SetRange;
+mod mget;
+use mget::MGet;
+mod mset;
+use mset::{MSet, MSetNx};
+mod getdel;
+use getdel::GetDel;
+mod getex;
+use getex::GetEx;
+mod getset;
+use getset::GetSet;
+mod copy;
+use copy::Copy;
+mod lcs;
+use lcs::Lcs;
+mod setbit;
+use setbit::SetBit;
+mod getbit;
+use getbit::GetBit;
+mod bitcount;
+use bitcount::BitCount;
+mod bitpos;
+use bitpos::BitPos;
+mod bitop;
+use bitop::BitOp;
+mod bitfield;
+use bitfield::{BitField, BitFieldRo};
+mod pfadd;
+use pfadd::PfAdd;
+mod pfcount;
+use pfcount::PfCount;
+mod pfmerge;
+use pfmerge::PfMerge;
+mod geoadd;
+use geoadd::GeoAdd;
+mod geodist;
+use geodist::GeoDist;
+mod geohash;
+use geohash::GeoHash;
+mod geopos;
+use geopos::GeoPos;
+mod geosearch;
+use geosearch::{GeoSearch, GeoSearchStore};
+mod xlen;
+use xlen::XLen;
+mod xpending;
+use xpending::XPending;
+mod xrange;
+use xrange::{XRange, XRevRange};
+mod xread;
+use xread::XRead;
+mod xreadgroup;
+use xreadgroup::XReadGroup;
+mod xtrim;
+use xtrim::XTrim;
 
-pub(crate) struct CommandArgs<'a>(Iter<'a, Frame>);
+pub(crate) struct CommandArgs<'a> {
+    // lowercased name the client used, for error messages
+    command_name: &'a str,
+    items: Iter<'a, Frame>,
+}
 
 impl CommandArgs<'_> {
     pub fn next_bytes(&mut self) -> Result<Bytes> {
-        match self.0.next() {
+        match self.items.next() {
             Some(Frame::BulkString(value)) => Ok(value.clone()),
             Some(wrong_frame) => Err(CmdErrors::IncorrectCommandArg {
-                command_name: Get::NAME,
+                command_name: self.command_name.to_string(),
                 arg: format!("{}", wrong_frame),
             }
             .into()),
             None => Err(CmdErrors::MissingCommandArg {
-                command_name: Get::NAME,
-                // TODO
-                arg_name: "value",
+                command_name: self.command_name.to_string(),
             }
             .into()),
         }
     }
+
+    pub fn next_optional_bytes(&mut self) -> Result<Option<Bytes>> {
+        match self.items.len() {
+            0 => Ok(None),
+            _ => self.next_bytes().map(Some),
+        }
+    }
+
+    pub fn next_integer(&mut self) -> Result<i64> {
+        let value = self.next_bytes()?;
+        Ok(parse_integer(&value).ok_or(CmdErrors::NotAnInteger)?)
+    }
+
+    pub fn next_optional_integer(&mut self) -> Result<Option<i64>> {
+        match self.items.len() {
+            0 => Ok(None),
+            _ => self.next_integer().map(Some),
+        }
+    }
+
+    pub fn next_float(&mut self) -> Result<f64> {
+        let value = self.next_bytes()?;
+        Ok(parse_float(&value).ok_or(CmdErrors::NotAFloat)?)
+    }
+
+    /// Non-negative integer, like the `COUNT` argument of pops.
+    pub fn next_count(&mut self) -> Result<usize> {
+        let value = self.next_integer()?;
+        Ok(usize::try_from(value).map_err(|_| CmdErrors::NotPositive)?)
+    }
+
+    pub fn next_optional_count(&mut self) -> Result<Option<usize>> {
+        match self.items.len() {
+            0 => Ok(None),
+            _ => self.next_count().map(Some),
+        }
+    }
+
+    /// Whether the next argument is `keyword`, in any case. Nothing is consumed.
+    pub fn next_is(&self, keyword: &[u8]) -> bool {
+        matches!(
+            self.items.clone().next(),
+            Some(Frame::BulkString(arg)) if arg.eq_ignore_ascii_case(keyword)
+        )
+    }
+
+    pub fn syntax_error(&self, arg: &[u8]) -> anyhow::Error {
+        CmdErrors::IncorrectCommandArg {
+            command_name: self.command_name.to_string(),
+            arg: String::from_utf8_lossy(arg).into_owned(),
+        }
+        .into()
+    }
+
+    fn finish(&self) -> Result<(), CmdErrors> {
+        match self.items.len() {
+            0 => Ok(()),
+            _ => Err(CmdErrors::MissingCommandArg {
+                command_name: self.command_name.to_string(),
+            }),
+        }
+    }
+}
+
+/// Strict integer parsing, the same rules Redis uses for arguments and stored values:
+/// optional `-`, no `+`, no spaces, no leading zeros and no `-0`.
+pub(crate) fn parse_integer(value: &[u8]) -> Option<i64> {
+    let digits = value.strip_prefix(b"-").unwrap_or(value);
+    match digits {
+        [] => None,
+        [b'0', _, ..] => None,
+        [b'0'] if digits.len() < value.len() => None,
+        _ if !digits.iter().all(u8::is_ascii_digit) => None,
+        _ => std::str::from_utf8(value).ok()?.parse().ok(),
+    }
+}
+
+/// Float parsing for arguments and stored values: no spaces around and no NaN,
+/// infinities are fine.
+pub(crate) fn parse_float(value: &[u8]) -> Option<f64> {
+    std::str::from_utf8(value)
+        .ok()?
+        .parse()
+        .ok()
+        .filter(|value: &f64| !value.is_nan())
+}
+
+/// Timeout of blocking commands in seconds, fractions allowed. Zero means no timeout.
+pub(crate) fn parse_timeout(value: &[u8]) -> Result<Option<Duration>, CmdErrors> {
+    let seconds: f64 = std::str::from_utf8(value)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|seconds: &f64| seconds.is_finite())
+        .ok_or(CmdErrors::InvalidTimeout)?;
+    match seconds {
+        _ if seconds < 0.0 => Err(CmdErrors::NegativeTimeout),
+        0.0 => Ok(None),
+        _ => Duration::try_from_secs_f64(seconds)
+            .map(Some)
+            .map_err(|_| CmdErrors::InvalidTimeout),
+    }
 }
 
 pub(crate) trait RESPCommand: Sized {
@@ -49,39 +341,583 @@ pub(crate) enum Command {
     Echo(Echo),
     Set(Set),
     Get(Get),
+    Hello(Hello),
+    Expire(Expire),
+    PExpire(PExpire),
+    ExpireAt(ExpireAt),
+    PExpireAt(PExpireAt),
+    Ttl(Ttl),
+    PTtl(PTtl),
+    ExpireTime(ExpireTime),
+    PExpireTime(PExpireTime),
+    Persist(Persist),
+    Info(Info),
+    Type(Type),
+    LPush(LPush),
+    RPush(RPush),
+    LPop(LPop),
+    RPop(RPop),
+    LLen(LLen),
+    LRange(LRange),
+    LIndex(LIndex),
+    LSet(LSet),
+    LRem(LRem),
+    LTrim(LTrim),
+    LInsert(LInsert),
+    LPos(LPos),
+    LMove(LMove),
+    BLPop(BLPop),
+    BRPop(BRPop),
+    BLMove(BLMove),
+    BLMPop(BLMPop),
+    HSet(HSet),
+    HSetNx(HSetNx),
+    HGet(HGet),
+    HMGet(HMGet),
+    HDel(HDel),
+    HGetAll(HGetAll),
+    HKeys(HKeys),
+    HVals(HVals),
+    HLen(HLen),
+    HExists(HExists),
+    HIncrBy(HIncrBy),
+    HIncrByFloat(HIncrByFloat),
+    HRandField(HRandField),
+    HExpire(HExpire),
+    HPExpire(HPExpire),
+    HExpireAt(HExpireAt),
+    HPExpireAt(HPExpireAt),
+    HTtl(HTtl),
+    HPTtl(HPTtl),
+    HExpireTime(HExpireTime),
+    HPExpireTime(HPExpireTime),
+    HPersist(HPersist),
+    SAdd(SAdd),
+    SRem(SRem),
+    SMembers(SMembers),
+    SIsMember(SIsMember),
+    SMIsMember(SMIsMember),
+    SCard(SCard),
+    SPop(SPop),
+    SRandMember(SRandMember),
+    SInter(SInter),
+    SUnion(SUnion),
+    SDiff(SDiff),
+    SInterStore(SInterStore),
+    SUnionStore(SUnionStore),
+    SDiffStore(SDiffStore),
+    ZAdd(ZAdd),
+    ZRange(ZRange),
+    ZRank(ZRank),
+    ZScore(ZScore),
+    ZIncrBy(ZIncrBy),
+    ZRem(ZRem),
+    ZCard(ZCard),
+    ZCount(ZCount),
+    ZPopMin(ZPopMin),
+    ZPopMax(ZPopMax),
+    ZUnionStore(ZUnionStore),
+    ZInterStore(ZInterStore),
+    BZPopMin(BZPopMin),
+    BZPopMax(BZPopMax),
+    BZMPop(BZMPop),
+    XAdd(XAdd),
+    XRange(XRange),
+    XRevRange(XRevRange),
+    XLen(XLen),
+    XTrim(XTrim),
+    XDel(XDel),
+    XRead(XRead),
+    XGroup(XGroup),
+    XReadGroup(XReadGroup),
+    XAck(XAck),
+    XPending(XPending),
+    XClaim(XClaim),
+    XAutoClaim(XAutoClaim),
+    XInfo(XInfo),
+    Incr(Incr),
+    Decr(Decr),
+    IncrBy(IncrBy),
+    DecrBy(DecrBy),
+    IncrByFloat(IncrByFloat),
+    Append(Append),
+    StrLen(StrLen),
+    GetRange(GetRange),
+    SetRange(SetRange),
+    MGet(MGet),
+    MSet(MSet),
+    MSetNx(MSetNx),
+    GetDel(GetDel),
+    GetEx(GetEx),
+    GetSet(GetSet),
+    Copy(Copy),
+    Lcs(Lcs),
+    SetBit(SetBit),
+    GetBit(GetBit),
+    BitCount(BitCount),
+    BitPos(BitPos),
+    BitOp(BitOp),
+    BitField(BitField),
+    BitFieldRo(BitFieldRo),
+    PfAdd(PfAdd),
+    PfCount(PfCount),
+    PfMerge(PfMerge),
+    GeoAdd(GeoAdd),
+    GeoDist(GeoDist),
+    GeoHash(GeoHash),
+    GeoPos(GeoPos),
+    GeoSearch(GeoSearch),
+    GeoSearchStore(GeoSearchStore),
 }
 
 impl Command {
     pub fn from_frame(frame: &Frame) -> Result<Command> {
-        let parts = Command::validate(&frame)?;
+        let parts = Command::validate(frame)?;
+        let command_name = parts[0]
+            .as_string()
+            .map_err(|_| CmdErrors::UnknownCommand(format!("{}", parts[0])))?
+            .to_lowercase();
         let mut args = CommandArgs {
-            0: parts[1..].iter(),
+            command_name: &command_name,
+            items: parts[1..].iter(),
         };
-        let cmd = match &parts[0].as_string()?.to_lowercase()[..] {
+        let cmd = match &command_name[..] {
             Ping::NAME => Command::Ping(Ping::parse(&mut args)?),
             Echo::NAME => Command::Echo(Echo::parse(&mut args)?),
             Set::NAME => Command::Set(Set::parse(&mut args)?),
             Get::NAME => Command::Get(Get::parse(&mut args)?),
+            Hello::NAME => Command::Hello(Hello::parse(&mut args)?),
+            Expire::NAME => Command::Expire(Expire::parse(&mut args)?),
+            PExpire::NAME => Command::PExpire(PExpire::parse(&mut args)?),
+            ExpireAt::NAME => Command::ExpireAt(ExpireAt::parse(&mut args)?),
+            PExpireAt::NAME => Command::PExpireAt(PExpireAt::parse(&mut args)?),
+            Ttl::NAME => Command::Ttl(Ttl::parse(&mut args)?),
+            PTtl::NAME => Command::PTtl(PTtl::parse(&mut args)?),
+            ExpireTime::NAME => Command::ExpireTime(ExpireTime::parse(&mut args)?),
+            PExpireTime::NAME => Command::PExpireTime(PExpireTime::parse(&mut args)?),
+            Persist::NAME => Command::Persist(Persist::parse(&mut args)?),
+            Info::NAME => Command::Info(Info::parse(&mut args)?),
+            Type::NAME => Command::Type(Type::parse(&mut args)?),
+            LPush::NAME => Command::LPush(LPush::parse(&mut args)?),
+            RPush::NAME => Command::RPush(RPush::parse(&mut args)?),
+            LPop::NAME => Command::LPop(LPop::parse(&mut args)?),
+            RPop::NAME => Command::RPop(RPop::parse(&mut args)?),
+            LLen::NAME => Command::LLen(LLen::parse(&mut args)?),
+            LRange::NAME => Command::LRange(LRange::parse(&mut args)?),
+            LIndex::NAME => Command::LIndex(LIndex::parse(&mut args)?),
+            LSet::NAME => Command::LSet(LSet::parse(&mut args)?),
+            LRem::NAME => Command::LRem(LRem::parse(&mut args)?),
+            LTrim::NAME => Command::LTrim(LTrim::parse(&mut args)?),
+            LInsert::NAME => Command::LInsert(LInsert::parse(&mut args)?),
+            LPos::NAME => Command::LPos(LPos::parse(&mut args)?),
+            LMove::NAME => Command::LMove(LMove::parse(&mut args)?),
+            BLPop::NAME => Command::BLPop(BLPop::parse(&mut args)?),
+            BRPop::NAME => Command::BRPop(BRPop::parse(&mut args)?),
+            BLMove::NAME => Command::BLMove(BLMove::parse(&mut args)?),
+            BLMPop::NAME => Command::BLMPop(BLMPop::parse(&mut args)?),
+            HSet::NAME => Command::HSet(HSet::parse(&mut args)?),
+            HSetNx::NAME => Command::HSetNx(HSetNx::parse(&mut args)?),
+            HGet::NAME => Command::HGet(HGet::parse(&mut args)?),
+            HMGet::NAME => Command::HMGet(HMGet::parse(&mut args)?),
+            HDel::NAME => Command::HDel(HDel::parse(&mut args)?),
+            HGetAll::NAME => Command::HGetAll(HGetAll::parse(&mut args)?),
+            HKeys::NAME => Command::HKeys(HKeys::parse(&mut args)?),
+            HVals::NAME => Command::HVals(HVals::parse(&mut args)?),
+            HLen::NAME => Command::HLen(HLen::parse(&mut args)?),
+            HExists::NAME => Command::HExists(HExists::parse(&mut args)?),
+            HIncrBy::NAME => Command::HIncrBy(HIncrBy::parse(&mut args)?),
+            HIncrByFloat::NAME => Command::HIncrByFloat(HIncrByFloat::parse(&mut args)?),
+            HRandField::NAME => Command::HRandField(HRandField::parse(&mut args)?),
+            HExpire::NAME => Command::HExpire(HExpire::parse(&mut args)?),
+            HPExpire::NAME => Command::HPExpire(HPExpire::parse(&mut args)?),
+            HExpireAt::NAME => Command::HExpireAt(HExpireAt::parse(&mut args)?),
+            HPExpireAt::NAME => Command::HPExpireAt(HPExpireAt::parse(&mut args)?),
+            HTtl::NAME => Command::HTtl(HTtl::parse(&mut args)?),
+            HPTtl::NAME => Command::HPTtl(HPTtl::parse(&mut args)?),
+            HExpireTime::NAME => Command::HExpireTime(HExpireTime::parse(&mut args)?),
+            HPExpireTime::NAME => Command::HPExpireTime(HPExpireTime::parse(&mut args)?),
+            HPersist::NAME => Command::HPersist(HPersist::parse(&mut args)?),
+            SAdd::NAME => Command::SAdd(SAdd::parse(&mut args)?),
+            SRem::NAME => Command::SRem(SRem::parse(&mut args)?),
+            SMembers::NAME => Command::SMembers(SMembers::parse(&mut args)?),
+            SIsMember::NAME => Command::SIsMember(SIsMember::parse(&mut args)?),
+            SMIsMember::NAME => Command::SMIsMember(SMIsMember::parse(&mut args)?),
+            SCard::NAME => Command::SCard(SCard::parse(&mut args)?),
+            SPop::NAME => Command::SPop(SPop::parse(&mut args)?),
+            SRandMember::NAME => Command::SRandMember(SRandMember::parse(&mut args)?),
+            SInter::NAME => Command::SInter(SInter::parse(&mut args)?),
+            SUnion::NAME => Command::SUnion(SUnion::parse(&mut args)?),
+            SDiff::NAME => Command::SDiff(SDiff::parse(&mut args)?),
+            SInterStore::NAME => Command::SInterStore(SInterStore::parse(&mut args)?),
+            SUnionStore::NAME => Command::SUnionStore(SUnionStore::parse(&mut args)?),
+            SDiffStore::NAME => Command::SDiffStore(SDiffStore::parse(&mut args)?),
+            ZAdd::NAME => Command::ZAdd(ZAdd::parse(&mut args)?),
+            ZRange::NAME => Command::ZRange(ZRange::parse(&mut args)?),
+            ZRank::NAME => Command::ZRank(ZRank::parse(&mut args)?),
+            ZScore::NAME => Command::ZScore(ZScore::parse(&mut args)?),
+            ZIncrBy::NAME => Command::ZIncrBy(ZIncrBy::parse(&mut args)?),
+            ZRem::NAME => Command::ZRem(ZRem::parse(&mut args)?),
+            ZCard::NAME => Command::ZCard(ZCard::parse(&mut args)?),
+            ZCount::NAME => Command::ZCount(ZCount::parse(&mut args)?),
+            ZPopMin::NAME => Command::ZPopMin(ZPopMin::parse(&mut args)?),
+            ZPopMax::NAME => Command::ZPopMax(ZPopMax::parse(&mut args)?),
+            ZUnionStore::NAME => Command::ZUnionStore(ZUnionStore::parse(&mut args)?),
+            ZInterStore::NAME => Command::ZInterStore(ZInterStore::parse(&mut args)?),
+            BZPopMin::NAME => Command::BZPopMin(BZPopMin::parse(&mut args)?),
+            BZPopMax::NAME => Command::BZPopMax(BZPopMax::parse(&mut args)?),
+            BZMPop::NAME => Command::BZMPop(BZMPop::parse(&mut args)?),
+            XAdd::NAME => Command::XAdd(XAdd::parse(&mut args)?),
+            XRange::NAME => Command::XRange(XRange::parse(&mut args)?),
+            XRevRange::NAME => Command::XRevRange(XRevRange::parse(&mut args)?),
+            XLen::NAME => Command::XLen(XLen::parse(&mut args)?),
+            XTrim::NAME => Command::XTrim(XTrim::parse(&mut args)?),
+            XDel::NAME => Command::XDel(XDel::parse(&mut args)?),
+            XRead::NAME => Command::XRead(XRead::parse(&mut args)?),
+            XGroup::NAME => Command::XGroup(XGroup::parse(&mut args)?),
+            XReadGroup::NAME => Command::XReadGroup(XReadGroup::parse(&mut args)?),
+            XAck::NAME => Command::XAck(XAck::parse(&mut args)?),
+            XPending::NAME => Command::XPending(XPending::parse(&mut args)?),
+            XClaim::NAME => Command::XClaim(XClaim::parse(&mut args)?),
+            XAutoClaim::NAME => Command::XAutoClaim(XAutoClaim::parse(&mut args)?),
+            XInfo::NAME => Command::XInfo(XInfo::parse(&mut args)?),
+            Incr::NAME => Command::Incr(Incr::parse(&mut args)?),
+            Decr::NAME => Command::Decr(Decr::parse(&mut args)?),
+            IncrBy::NAME => Command::IncrBy(IncrBy::parse(&mut args)?),
+            DecrBy::NAME => Command::DecrBy(DecrBy::parse(&mut args)?),
+            IncrByFloat::NAME => Command::IncrByFloat(IncrByFloat::parse(&mut args)?),
+            Append::NAME => Command::Append(Append::parse(&mut args)?),
+            StrLen::NAME => Command::StrLen(StrLen::parse(&mut args)?),
+            GetRange::NAME => Command::GetRange(GetRange::parse(&mut args)?),
+            SetRange::NAME => Command::SetRange(SetRange::parse(&mut args)?),
+            MGet::NAME => Command::MGet(MGet::parse(&mut args)?),
+            MSet::NAME => Command::MSet(MSet::parse(&mut args)?),
+            MSetNx::NAME => Command::MSetNx(MSetNx::parse(&mut args)?),
+            GetDel::NAME => Command::GetDel(GetDel::parse(&mut args)?),
+            GetEx::NAME => Command::GetEx(GetEx::parse(&mut args)?),
+            GetSet::NAME => Command::GetSet(GetSet::parse(&mut args)?),
+            Copy::NAME => Command::Copy(Copy::parse(&mut args)?),
+            Lcs::NAME => Command::Lcs(Lcs::parse(&mut args)?),
+            SetBit::NAME => Command::SetBit(SetBit::parse(&mut args)?),
+            GetBit::NAME => Command::GetBit(GetBit::parse(&mut args)?),
+            BitCount::NAME => Command::BitCount(BitCount::parse(&mut args)?),
+            BitPos::NAME => Command::BitPos(BitPos::parse(&mut args)?),
+            BitOp::NAME => Command::BitOp(BitOp::parse(&mut args)?),
+            BitField::NAME => Command::BitField(BitField::parse(&mut args)?),
+            BitFieldRo::NAME => Command::BitFieldRo(BitFieldRo::parse(&mut args)?),
+            PfAdd::NAME => Command::PfAdd(PfAdd::parse(&mut args)?),
+            PfCount::NAME => Command::PfCount(PfCount::parse(&mut args)?),
+            PfMerge::NAME => Command::PfMerge(PfMerge::parse(&mut args)?),
+            GeoAdd::NAME => Command::GeoAdd(GeoAdd::parse(&mut args)?),
+            GeoDist::NAME => Command::GeoDist(GeoDist::parse(&mut args)?),
+            GeoHash::NAME => Command::GeoHash(GeoHash::parse(&mut args)?),
+            GeoPos::NAME => Command::GeoPos(GeoPos::parse(&mut args)?),
+            GeoSearch::NAME => Command::GeoSearch(GeoSearch::parse(&mut args)?),
+            GeoSearchStore::NAME => Command::GeoSearchStore(GeoSearchStore::parse(&mut args)?),
             unknown => {
                 return Err(CmdErrors::UnknownCommand(unknown.to_owned()).into());
             }
         };
+        // every command consumes all arguments it knows about
+        args.finish()?;
 
         Ok(cmd)
     }
 
+    /// Commands that can wait for other clients, the connection handler watches
+    /// for disconnects while they run.
+    pub fn is_blocking(&self) -> bool {
+        matches!(
+            self,
+            Command::BLPop(_)
+                | Command::BRPop(_)
+                | Command::BLMove(_)
+                | Command::BLMPop(_)
+                | Command::BZPopMin(_)
+                | Command::BZPopMax(_)
+                | Command::BZMPop(_)
+        ) || match self {
+            Command::XRead(cmd) => cmd.blocks(),
+            Command::XReadGroup(cmd) => cmd.blocks(),
+            _ => false,
+        }
+    }
+
+    /// Executes commands that work with the storage only, commands that change
+    /// the connection itself (like `HELLO`) are executed by the connection handler.
+    pub async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        match self {
+            Command::Get(cmd) => cmd.run(storage).await?,
+            Command::Set(cmd) => cmd.run(storage).await?,
+            Command::Expire(cmd) => cmd.run(storage).await,
+            Command::PExpire(cmd) => cmd.run(storage).await,
+            Command::ExpireAt(cmd) => cmd.run(storage).await,
+            Command::PExpireAt(cmd) => cmd.run(storage).await,
+            Command::Ttl(cmd) => cmd.run(storage).await,
+            Command::PTtl(cmd) => cmd.run(storage).await,
+            Command::ExpireTime(cmd) => cmd.run(storage).await,
+            Command::PExpireTime(cmd) => cmd.run(storage).await,
+            Command::Persist(cmd) => cmd.run(storage).await,
+            Command::Info(cmd) => cmd.run(storage).await,
+            Command::Type(cmd) => cmd.run(storage).await,
+            Command::LPush(cmd) => cmd.run(storage).await?,
+            Command::RPush(cmd) => cmd.run(storage).await?,
+            Command::LPop(cmd) => cmd.run(storage).await?,
+            Command::RPop(cmd) => cmd.run(storage).await?,
+            Command::LLen(cmd) => cmd.run(storage).await?,
+            Command::LRange(cmd) => cmd.run(storage).await?,
+            Command::LIndex(cmd) => cmd.run(storage).await?,
+            Command::LSet(cmd) => cmd.run(storage).await?,
+            Command::LRem(cmd) => cmd.run(storage).await?,
+            Command::LTrim(cmd) => cmd.run(storage).await?,
+            Command::LInsert(cmd) => cmd.run(storage).await?,
+            Command::LPos(cmd) => cmd.run(storage).await?,
+            Command::LMove(cmd) => cmd.run(storage).await?,
+            Command::BLPop(cmd) => cmd.run(storage, client_id).await?,
+            Command::BRPop(cmd) => cmd.run(storage, client_id).await?,
+            Command::BLMove(cmd) => cmd.run(storage, client_id).await?,
+            Command::BLMPop(cmd) => cmd.run(storage, client_id).await?,
+            Command::HSet(cmd) => cmd.run(storage).await?,
+            Command::HSetNx(cmd) => cmd.run(storage).await?,
+            Command::HGet(cmd) => cmd.run(storage).await?,
+            Command::HMGet(cmd) => cmd.run(storage).await?,
+            Command::HDel(cmd) => cmd.run(storage).await?,
+            Command::HGetAll(cmd) => cmd.run(storage).await?,
+            Command::HKeys(cmd) => cmd.run(storage).await?,
+            Command::HVals(cmd) => cmd.run(storage).await?,
+            Command::HLen(cmd) => cmd.run(storage).await?,
+            Command::HExists(cmd) => cmd.run(storage).await?,
+            Command::HIncrBy(cmd) => cmd.run(storage).await?,
+            Command::HIncrByFloat(cmd) => cmd.run(storage).await?,
+            Command::HRandField(cmd) => cmd.run(storage).await?,
+            Command::HExpire(cmd) => cmd.run(storage).await?,
+            Command::HPExpire(cmd) => cmd.run(storage).await?,
+            Command::HExpireAt(cmd) => cmd.run(storage).await?,
+            Command::HPExpireAt(cmd) => cmd.run(storage).await?,
+            Command::HTtl(cmd) => cmd.run(storage).await?,
+            Command::HPTtl(cmd) => cmd.run(storage).await?,
+            Command::HExpireTime(cmd) => cmd.run(storage).await?,
+            Command::HPExpireTime(cmd) => cmd.run(storage).await?,
+            Command::HPersist(cmd) => cmd.run(storage).await?,
+            Command::SAdd(cmd) => cmd.run(storage).await?,
+            Command::SRem(cmd) => cmd.run(storage).await?,
+            Command::SMembers(cmd) => cmd.run(storage).await?,
+            Command::SIsMember(cmd) => cmd.run(storage).await?,
+            Command::SMIsMember(cmd) => cmd.run(storage).await?,
+            Command::SCard(cmd) => cmd.run(storage).await?,
+            Command::SPop(cmd) => cmd.run(storage).await?,
+            Command::SRandMember(cmd) => cmd.run(storage).await?,
+            Command::SInter(cmd) => cmd.run(storage).await?,
+            Command::SUnion(cmd) => cmd.run(storage).await?,
+            Command::SDiff(cmd) => cmd.run(storage).await?,
+            Command::SInterStore(cmd) => cmd.run(storage).await?,
+            Command::SUnionStore(cmd) => cmd.run(storage).await?,
+            Command::SDiffStore(cmd) => cmd.run(storage).await?,
+            Command::ZAdd(cmd) => cmd.run(storage).await?,
+            Command::ZRange(cmd) => cmd.run(storage).await?,
+            Command::ZRank(cmd) => cmd.run(storage).await?,
+            Command::ZScore(cmd) => cmd.run(storage).await?,
+            Command::ZIncrBy(cmd) => cmd.run(storage).await?,
+            Command::ZRem(cmd) => cmd.run(storage).await?,
+            Command::ZCard(cmd) => cmd.run(storage).await?,
+            Command::ZCount(cmd) => cmd.run(storage).await?,
+            Command::ZPopMin(cmd) => cmd.run(storage).await?,
+            Command::ZPopMax(cmd) => cmd.run(storage).await?,
+            Command::ZUnionStore(cmd) => cmd.run(storage).await?,
+            Command::ZInterStore(cmd) => cmd.run(storage).await?,
+            Command::BZPopMin(cmd) => cmd.run(storage, client_id).await?,
+            Command::BZPopMax(cmd) => cmd.run(storage, client_id).await?,
+            Command::BZMPop(cmd) => cmd.run(storage, client_id).await?,
+            Command::XAdd(cmd) => cmd.run(storage).await?,
+            Command::XRange(cmd) => cmd.run(storage).await?,
+            Command::XRevRange(cmd) => cmd.run(storage).await?,
+            Command::XLen(cmd) => cmd.run(storage).await?,
+            Command::XTrim(cmd) => cmd.run(storage).await?,
+            Command::XDel(cmd) => cmd.run(storage).await?,
+            Command::XRead(cmd) => cmd.run(storage, client_id).await?,
+            Command::XGroup(cmd) => cmd.run(storage).await?,
+            Command::XReadGroup(cmd) => cmd.run(storage, client_id).await?,
+            Command::XAck(cmd) => cmd.run(storage).await?,
+            Command::XPending(cmd) => cmd.run(storage).await?,
+            Command::XClaim(cmd) => cmd.run(storage).await?,
+            Command::XAutoClaim(cmd) => cmd.run(storage).await?,
+            Command::XInfo(cmd) => cmd.run(storage).await?,
+            Command::Incr(cmd) => cmd.run(storage).await?,
+            Command::Decr(cmd) => cmd.run(storage).await?,
+            Command::IncrBy(cmd) => cmd.run(storage).await?,
+            Command::DecrBy(cmd) => cmd.run(storage).await?,
+            Command::IncrByFloat(cmd) => cmd.run(storage).await?,
+            Command::Append(cmd) => cmd.run(storage).await?,
+            Command::StrLen(cmd) => cmd.run(storage).await?,
+            Command::GetRange(cmd) => cmd.run(storage).await?,
+            Command::SetRange(cmd) => cmd.run(storage).await?,
+            Command::MGet(cmd) => cmd.run(storage).await?,
+            Command::MSet(cmd) => cmd.run(storage).await?,
+            Command::MSetNx(cmd) => cmd.run(storage).await?,
+            Command::GetDel(cmd) => cmd.run(storage).await?,
+            Command::GetEx(cmd) => cmd.run(storage).await?,
+            Command::GetSet(cmd) => cmd.run(storage).await?,
+            Command::Copy(cmd) => cmd.run(storage).await?,
+            Command::Lcs(cmd) => cmd.run(storage).await?,
+            Command::SetBit(cmd) => cmd.run(storage).await?,
+            Command::GetBit(cmd) => cmd.run(storage).await?,
+            Command::BitCount(cmd) => cmd.run(storage).await?,
+            Command::BitPos(cmd) => cmd.run(storage).await?,
+            Command::BitOp(cmd) => cmd.run(storage).await?,
+            Command::BitField(cmd) => cmd.run(storage).await?,
+            Command::BitFieldRo(cmd) => cmd.run(storage).await?,
+            Command::PfAdd(cmd) => cmd.run(storage).await?,
+            Command::PfCount(cmd) => cmd.run(storage).await?,
+            Command::PfMerge(cmd) => cmd.run(storage).await?,
+            Command::GeoAdd(cmd) => cmd.run(storage).await?,
+            Command::GeoDist(cmd) => cmd.run(storage).await?,
+            Command::GeoHash(cmd) => cmd.run(storage).await?,
+            Command::GeoPos(cmd) => cmd.run(storage).await?,
+            Command::GeoSearch(cmd) => cmd.run(storage).await?,
+            Command::GeoSearchStore(cmd) => cmd.run(storage).await?,
+            Command::Ping(_) | Command::Echo(_) | Command::Hello(_) => {}
+        }
+        Ok(())
+    }
+
     pub fn as_response_frame(&self) -> Frame {
         match self {
             Command::Ping(ping) => ping.to_response(),
             Command::Echo(echo) => echo.to_response(),
             Command::Set(set) => set.to_response(),
             Command::Get(get) => get.to_response(),
+            Command::Hello(hello) => hello.to_response(),
+            Command::Expire(cmd) => cmd.to_response(),
+            Command::PExpire(cmd) => cmd.to_response(),
+            Command::ExpireAt(cmd) => cmd.to_response(),
+            Command::PExpireAt(cmd) => cmd.to_response(),
+            Command::Ttl(cmd) => cmd.to_response(),
+            Command::PTtl(cmd) => cmd.to_response(),
+            Command::ExpireTime(cmd) => cmd.to_response(),
+            Command::PExpireTime(cmd) => cmd.to_response(),
+            Command::Persist(cmd) => cmd.to_response(),
+            Command::Info(cmd) => cmd.to_response(),
+            Command::Type(cmd) => cmd.to_response(),
+            Command::LPush(cmd) => cmd.to_response(),
+            Command::RPush(cmd) => cmd.to_response(),
+            Command::LPop(cmd) => cmd.to_response(),
+            Command::RPop(cmd) => cmd.to_response(),
+            Command::LLen(cmd) => cmd.to_response(),
+            Command::LRange(cmd) => cmd.to_response(),
+            Command::LIndex(cmd) => cmd.to_response(),
+            Command::LSet(cmd) => cmd.to_response(),
+            Command::LRem(cmd) => cmd.to_response(),
+            Command::LTrim(cmd) => cmd.to_response(),
+            Command::LInsert(cmd) => cmd.to_response(),
+            Command::LPos(cmd) => cmd.to_response(),
+            Command::LMove(cmd) => cmd.to_response(),
+            Command::BLPop(cmd) => cmd.to_response(),
+            Command::BRPop(cmd) => cmd.to_response(),
+            Command::BLMove(cmd) => cmd.to_response(),
+            Command::BLMPop(cmd) => cmd.to_response(),
+            Command::HSet(cmd) => cmd.to_response(),
+            Command::HSetNx(cmd) => cmd.to_response(),
+            Command::HGet(cmd) => cmd.to_response(),
+            Command::HMGet(cmd) => cmd.to_response(),
+            Command::HDel(cmd) => cmd.to_response(),
+            Command::HGetAll(cmd) => cmd.to_response(),
+            Command::HKeys(cmd) => cmd.to_response(),
+            Command::HVals(cmd) => cmd.to_response(),
+            Command::HLen(cmd) => cmd.to_response(),
+            Command::HExists(cmd) => cmd.to_response(),
+            Command::HIncrBy(cmd) => cmd.to_response(),
+            Command::HIncrByFloat(cmd) => cmd.to_response(),
+            Command::HRandField(cmd) => cmd.to_response(),
+            Command::HExpire(cmd) => cmd.to_response(),
+            Command::HPExpire(cmd) => cmd.to_response(),
+            Command::HExpireAt(cmd) => cmd.to_response(),
+            Command::HPExpireAt(cmd) => cmd.to_response(),
+            Command::HTtl(cmd) => cmd.to_response(),
+            Command::HPTtl(cmd) => cmd.to_response(),
+            Command::HExpireTime(cmd) => cmd.to_response(),
+            Command::HPExpireTime(cmd) => cmd.to_response(),
+            Command::HPersist(cmd) => cmd.to_response(),
+            Command::SAdd(cmd) => cmd.to_response(),
+            Command::SRem(cmd) => cmd.to_response(),
+            Command::SMembers(cmd) => cmd.to_response(),
+            Command::SIsMember(cmd) => cmd.to_response(),
+            Command::SMIsMember(cmd) => cmd.to_response(),
+            Command::SCard(cmd) => cmd.to_response(),
+            Command::SPop(cmd) => cmd.to_response(),
+            Command::SRandMember(cmd) => cmd.to_response(),
+            Command::SInter(cmd) => cmd.to_response(),
+            Command::SUnion(cmd) => cmd.to_response(),
+            Command::SDiff(cmd) => cmd.to_response(),
+            Command::SInterStore(cmd) => cmd.to_response(),
+            Command::SUnionStore(cmd) => cmd.to_response(),
+            Command::SDiffStore(cmd) => cmd.to_response(),
+            Command::ZAdd(cmd) => cmd.to_response(),
+            Command::ZRange(cmd) => cmd.to_response(),
+            Command::ZRank(cmd) => cmd.to_response(),
+            Command::ZScore(cmd) => cmd.to_response(),
+            Command::ZIncrBy(cmd) => cmd.to_response(),
+            Command::ZRem(cmd) => cmd.to_response(),
+            Command::ZCard(cmd) => cmd.to_response(),
+            Command::ZCount(cmd) => cmd.to_response(),
+            Command::ZPopMin(cmd) => cmd.to_response(),
+            Command::ZPopMax(cmd) => cmd.to_response(),
+            Command::ZUnionStore(cmd) => cmd.to_response(),
+            Command::ZInterStore(cmd) => cmd.to_response(),
+            Command::BZPopMin(cmd) => cmd.to_response(),
+            Command::BZPopMax(cmd) => cmd.to_response(),
+            Command::BZMPop(cmd) => cmd.to_response(),
+            Command::XAdd(cmd) => cmd.to_response(),
+            Command::XRange(cmd) => cmd.to_response(),
+            Command::XRevRange(cmd) => cmd.to_response(),
+            Command::XLen(cmd) => cmd.to_response(),
+            Command::XTrim(cmd) => cmd.to_response(),
+            Command::XDel(cmd) => cmd.to_response(),
+            Command::XRead(cmd) => cmd.to_response(),
+            Command::XGroup(cmd) => cmd.to_response(),
+            Command::XReadGroup(cmd) => cmd.to_response(),
+            Command::XAck(cmd) => cmd.to_response(),
+            Command::XPending(cmd) => cmd.to_response(),
+            Command::XClaim(cmd) => cmd.to_response(),
+            Command::XAutoClaim(cmd) => cmd.to_response(),
+            Command::XInfo(cmd) => cmd.to_response(),
+            Command::Incr(cmd) => cmd.to_response(),
+            Command::Decr(cmd) => cmd.to_response(),
+            Command::IncrBy(cmd) => cmd.to_response(),
+            Command::DecrBy(cmd) => cmd.to_response(),
+            Command::IncrByFloat(cmd) => cmd.to_response(),
+            Command::Append(cmd) => cmd.to_response(),
+            Command::StrLen(cmd) => cmd.to_response(),
+            Command::GetRange(cmd) => cmd.to_response(),
+            Command::SetRange(cmd) => cmd.to_response(),
+            Command::MGet(cmd) => cmd.to_response(),
+            Command::MSet(cmd) => cmd.to_response(),
+            Command::MSetNx(cmd) => cmd.to_response(),
+            Command::GetDel(cmd) => cmd.to_response(),
+            Command::GetEx(cmd) => cmd.to_response(),
+            Command::GetSet(cmd) => cmd.to_response(),
+            Command::Copy(cmd) => cmd.to_response(),
+            Command::Lcs(cmd) => cmd.to_response(),
+            Command::SetBit(cmd) => cmd.to_response(),
+            Command::GetBit(cmd) => cmd.to_response(),
+            Command::BitCount(cmd) => cmd.to_response(),
+            Command::BitPos(cmd) => cmd.to_response(),
+            Command::BitOp(cmd) => cmd.to_response(),
+            Command::BitField(cmd) => cmd.to_response(),
+            Command::BitFieldRo(cmd) => cmd.to_response(),
+            Command::PfAdd(cmd) => cmd.to_response(),
+            Command::PfCount(cmd) => cmd.to_response(),
+            Command::PfMerge(cmd) => cmd.to_response(),
+            Command::GeoAdd(cmd) => cmd.to_response(),
+            Command::GeoDist(cmd) => cmd.to_response(),
+            Command::GeoHash(cmd) => cmd.to_response(),
+            Command::GeoPos(cmd) => cmd.to_response(),
+            Command::GeoSearch(cmd) => cmd.to_response(),
+            Command::GeoSearchStore(cmd) => cmd.to_response(),
         }
     }
 
     fn validate(frame: &Frame) -> Result<&Vec<Frame>, CmdErrors> {
         match frame {
-            Frame::Array(frames) if frames.len() > 0 => Ok(frames),
+            Frame::Array(frames) if !frames.is_empty() => Ok(frames),
             _ => Err(CmdErrors::InvalidArrayFrame),
         }
     }
@@ -107,4 +943,43 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_parse_float() {
+        assert_eq!(parse_float(b"1.5"), Some(1.5));
+        assert_eq!(parse_float(b"-3"), Some(-3.0));
+        assert_eq!(parse_float(b"5e3"), Some(5000.0));
+        assert_eq!(parse_float(b"inf"), Some(f64::INFINITY));
+
+        assert_eq!(parse_float(b""), None);
+        assert_eq!(parse_float(b" 1"), None);
+        assert_eq!(parse_float(b"nan"), None);
+        assert_eq!(parse_float(b"1.5x"), None);
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout(b"0"), Ok(None));
+        assert_eq!(parse_timeout(b"1.5"), Ok(Some(Duration::from_millis(1500))));
+        assert_eq!(parse_timeout(b"-1"), Err(CmdErrors::NegativeTimeout));
+        assert_eq!(parse_timeout(b"inf"), Err(CmdErrors::InvalidTimeout));
+        assert_eq!(parse_timeout(b"soon"), Err(CmdErrors::InvalidTimeout));
+    }
+
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!(parse_integer(b"0"), Some(0));
+        assert_eq!(parse_integer(b"-15"), Some(-15));
+        assert_eq!(parse_integer(b"9223372036854775807"), Some(i64::MAX));
+        assert_eq!(parse_integer(b"-9223372036854775808"), Some(i64::MIN));
+
+        assert_eq!(parse_integer(b""), None);
+        assert_eq!(parse_integer(b"-"), None);
+        assert_eq!(parse_integer(b"+1"), None);
+        assert_eq!(parse_integer(b"01"), None);
+        assert_eq!(parse_integer(b"-0"), None);
+        assert_eq!(parse_integer(b" 1"), None);
+        assert_eq!(parse_integer(b"1.0"), None);
+        assert_eq!(parse_integer(b"9223372036854775808"), None);
+    }
 }
@@ -0,0 +1,73 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+fn parse_pairs(args: &mut CommandArgs, command_name: &str) -> Result<Vec<(Bytes, Bytes)>> {
+    let mut pairs = vec![];
+    while let Some(key) = args.next_optional_bytes()? {
+        pairs.push((key, args.next_bytes()?));
+    }
+    if pairs.is_empty() {
+        return Err(CmdErrors::MissingCommandArg {
+            command_name: command_name.to_string(),
+        }
+        .into());
+    }
+    Ok(pairs)
+}
+
+#[derive(Debug)]
+pub(crate) struct MSet {
+    pairs: Vec<(Bytes, Bytes)>,
+}
+
+impl MSet {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        storage.string_set_many(&self.pairs, false).await;
+        Ok(())
+    }
+}
+
+impl RESPCommand for MSet {
+    const NAME: &'static str = "mset";
+
+    fn parse(args: &mut CommandArgs) -> Result<MSet> {
+        Ok(MSet {
+            pairs: parse_pairs(args, Self::NAME)?,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::SimpleString(Bytes::from_static(b"OK"))
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct MSetNx {
+    pairs: Vec<(Bytes, Bytes)>,
+    result: bool,
+}
+
+impl MSetNx {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.string_set_many(&self.pairs, true).await;
+        Ok(())
+    }
+}
+
+impl RESPCommand for MSetNx {
+    const NAME: &'static str = "msetnx";
+
+    fn parse(args: &mut CommandArgs) -> Result<MSetNx> {
+        Ok(MSetNx {
+            pairs: parse_pairs(args, Self::NAME)?,
+            result: false,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct Persist {
+    key: Bytes,
+    result: bool,
+}
+
+impl Persist {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.result = storage.persist(&self.key).await;
+    }
+}
+
+impl RESPCommand for Persist {
+    const NAME: &'static str = "persist";
+
+    fn parse(args: &mut CommandArgs) -> Result<Persist> {
+        let key = args.next_bytes()?;
+        Ok(Persist { key, result: false })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(i64::from(self.result))
+    }
+}
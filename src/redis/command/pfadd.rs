@@ -0,0 +1,40 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct PfAdd {
+    key: Bytes,
+    elements: Vec<Bytes>,
+    result: bool,
+}
+
+impl PfAdd {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.hll_add(&self.key, &self.elements).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for PfAdd {
+    const NAME: &'static str = "pfadd";
+
+    fn parse(args: &mut CommandArgs) -> Result<PfAdd> {
+        let key = args.next_bytes()?;
+        let mut elements = vec![];
+        while let Some(element) = args.next_optional_bytes()? {
+            elements.push(element);
+        }
+        Ok(PfAdd {
+            key,
+            elements,
+            result: false,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(i64::from(self.result))
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct PfCount {
+    keys: Vec<Bytes>,
+    result: u64,
+}
+
+impl PfCount {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.hll_count(&self.keys).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for PfCount {
+    const NAME: &'static str = "pfcount";
+
+    fn parse(args: &mut CommandArgs) -> Result<PfCount> {
+        let mut keys = vec![args.next_bytes()?];
+        while let Some(key) = args.next_optional_bytes()? {
+            keys.push(key);
+        }
+        Ok(PfCount { keys, result: 0 })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
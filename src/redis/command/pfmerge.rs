@@ -0,0 +1,35 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct PfMerge {
+    destination: Bytes,
+    keys: Vec<Bytes>,
+}
+
+impl PfMerge {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        storage.hll_merge(&self.destination, &self.keys).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for PfMerge {
+    const NAME: &'static str = "pfmerge";
+
+    fn parse(args: &mut CommandArgs) -> Result<PfMerge> {
+        let destination = args.next_bytes()?;
+        let mut keys = vec![];
+        while let Some(key) = args.next_optional_bytes()? {
+            keys.push(key);
+        }
+        Ok(PfMerge { destination, keys })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::SimpleString(Bytes::from_static(b"OK"))
+    }
+}
@@ -5,16 +5,22 @@ use super::{CommandArgs, RESPCommand};
 use crate::redis::Frame;
 
 #[derive(Debug)]
-pub(crate) struct Ping {}
+pub(crate) struct Ping {
+    message: Option<Bytes>,
+}
 
 impl RESPCommand for Ping {
     const NAME: &'static str = "ping";
 
-    fn parse(_: &mut CommandArgs) -> Result<Ping> {
-        Ok(Ping {})
+    fn parse(args: &mut CommandArgs) -> Result<Ping> {
+        let message = args.next_optional_bytes()?;
+        Ok(Ping { message })
     }
 
     fn to_response(&self) -> Frame {
-        Frame::SimpleString(Bytes::from_static(b"PONG"))
+        match &self.message {
+            Some(message) => Frame::BulkString(message.clone()),
+            None => Frame::SimpleString(Bytes::from_static(b"PONG")),
+        }
     }
 }
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct SAdd {
+    key: Bytes,
+    members: Vec<Bytes>,
+    result: usize,
+}
+
+impl SAdd {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.set_add(&self.key, &self.members).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for SAdd {
+    const NAME: &'static str = "sadd";
+
+    fn parse(args: &mut CommandArgs) -> Result<SAdd> {
+        let key = args.next_bytes()?;
+        let mut members = vec![args.next_bytes()?];
+        while let Some(member) = args.next_optional_bytes()? {
+            members.push(member);
+        }
+
+        Ok(SAdd {
+            key,
+            members,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
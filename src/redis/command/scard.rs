@@ -0,0 +1,31 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct SCard {
+    key: Bytes,
+    result: usize,
+}
+
+impl SCard {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.set_len(&self.key).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for SCard {
+    const NAME: &'static str = "scard";
+
+    fn parse(args: &mut CommandArgs) -> Result<SCard> {
+        let key = args.next_bytes()?;
+        Ok(SCard { key, result: 0 })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -1,18 +1,35 @@
 use anyhow::Result;
 use bytes::Bytes;
 
+use super::expire::{deadline, TimeUnit};
 use super::{CommandArgs, RESPCommand};
-use crate::redis::{Frame, Storage};
+use crate::redis::storage::{SetCondition, SetExpiry};
+use crate::redis::{CmdErrors, Frame, Storage};
 
 #[derive(Debug)]
 pub(crate) struct Set {
-    key: String,
+    key: Bytes,
     value: Bytes,
+    expiry: SetExpiry,
+    condition: SetCondition,
+    // `GET` option, reply with the old value instead of OK
+    get: bool,
+    written: bool,
+    old_value: Option<Bytes>,
 }
 
 impl Set {
-    pub(crate) async fn run(&self, storage: &Storage) {
-        storage.set(&self.key, &self.value).await;
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        (self.written, self.old_value) = storage
+            .set(
+                &self.key,
+                &self.value,
+                self.expiry,
+                &self.condition,
+                self.get,
+            )
+            .await?;
+        Ok(())
     }
 }
 
@@ -23,13 +40,75 @@ impl RESPCommand for Set {
         let key = args.next_bytes()?;
         let value = args.next_bytes()?;
 
+        let mut expiry = None;
+        let mut condition = None;
+        let mut get = false;
+        while let Some(option) = args.next_optional_bytes()? {
+            let option_lowercase = option.to_ascii_lowercase();
+            let time_option = match &option_lowercase[..] {
+                b"ex" => Some((TimeUnit::Seconds, false)),
+                b"px" => Some((TimeUnit::Milliseconds, false)),
+                b"exat" => Some((TimeUnit::Seconds, true)),
+                b"pxat" => Some((TimeUnit::Milliseconds, true)),
+                b"keepttl" => None,
+                // only one of NX, XX and IFEQ
+                b"nx" | b"xx" | b"ifeq" if condition.is_some() => {
+                    return Err(args.syntax_error(&option))
+                }
+                b"nx" => {
+                    condition = Some(SetCondition::NotExists);
+                    continue;
+                }
+                b"xx" => {
+                    condition = Some(SetCondition::Exists);
+                    continue;
+                }
+                b"ifeq" => {
+                    condition = Some(SetCondition::Equals(args.next_bytes()?));
+                    continue;
+                }
+                b"get" => {
+                    get = true;
+                    continue;
+                }
+                _ => return Err(args.syntax_error(&option)),
+            };
+            // only one of EX, PX, EXAT, PXAT and KEEPTTL
+            if expiry.is_some() {
+                return Err(args.syntax_error(&option));
+            }
+
+            expiry = Some(match time_option {
+                None => SetExpiry::Keep,
+                Some((unit, absolute)) => {
+                    let invalid_expire_time = || CmdErrors::InvalidExpireTime {
+                        command_name: Self::NAME.to_string(),
+                    };
+                    let amount = args.next_integer()?;
+                    if amount <= 0 {
+                        return Err(invalid_expire_time().into());
+                    }
+                    SetExpiry::At(deadline(amount, unit, absolute).ok_or_else(invalid_expire_time)?)
+                }
+            });
+        }
+
         Ok(Set {
-            key: String::from_utf8(key.to_vec())?,
+            key,
             value,
+            expiry: expiry.unwrap_or(SetExpiry::Discard),
+            condition: condition.unwrap_or(SetCondition::Always),
+            get,
+            written: false,
+            old_value: None,
         })
     }
 
     fn to_response(&self) -> Frame {
-        Frame::SimpleString(Bytes::from_static(b"OK"))
+        match (self.get, self.written, &self.old_value) {
+            (true, _, Some(old_value)) => Frame::BulkString(old_value.clone()),
+            (true, _, None) | (false, false, _) => Frame::Null,
+            (false, true, _) => Frame::SimpleString(Bytes::from_static(b"OK")),
+        }
     }
 }
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{parse_integer, CommandArgs, RESPCommand};
+use crate::redis::storage::BITMAP_MAX_BITS;
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// Offset of a bit in a string, below the size a string can grow to.
+pub(super) fn parse_bit_offset(value: &[u8]) -> Result<usize, CmdErrors> {
+    parse_integer(value)
+        .and_then(|offset| usize::try_from(offset).ok())
+        .filter(|offset| *offset < BITMAP_MAX_BITS)
+        .ok_or(CmdErrors::BitOffsetOutOfRange)
+}
+
+#[derive(Debug)]
+pub(crate) struct SetBit {
+    key: Bytes,
+    offset: usize,
+    bit: bool,
+    result: bool,
+}
+
+impl SetBit {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.bit_set(&self.key, self.offset, self.bit).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for SetBit {
+    const NAME: &'static str = "setbit";
+
+    fn parse(args: &mut CommandArgs) -> Result<SetBit> {
+        let key = args.next_bytes()?;
+        let offset = parse_bit_offset(&args.next_bytes()?)?;
+        let bit = match &args.next_bytes()?[..] {
+            b"0" => false,
+            b"1" => true,
+            _ => return Err(CmdErrors::NotABit.into()),
+        };
+        Ok(SetBit {
+            key,
+            offset,
+            bit,
+            result: false,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(i64::from(self.result))
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct SetRange {
+    key: Bytes,
+    offset: usize,
+    value: Bytes,
+    result: usize,
+}
+
+impl SetRange {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .string_set_range(&self.key, self.offset, &self.value)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for SetRange {
+    const NAME: &'static str = "setrange";
+
+    fn parse(args: &mut CommandArgs) -> Result<SetRange> {
+        let key = args.next_bytes()?;
+        let offset =
+            usize::try_from(args.next_integer()?).map_err(|_| CmdErrors::OffsetOutOfRange)?;
+        let value = args.next_bytes()?;
+        Ok(SetRange {
+            key,
+            offset,
+            value,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
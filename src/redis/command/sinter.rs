@@ -0,0 +1,101 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::SetOp;
+use crate::redis::{Frame, Storage};
+
+/// Shared by `SINTER`, `SUNION` and `SDIFF`, they differ only in the operation.
+#[derive(Debug)]
+struct SetOpArgs {
+    keys: Vec<Bytes>,
+    op: SetOp,
+    result: Vec<Bytes>,
+}
+
+impl SetOpArgs {
+    fn parse(args: &mut CommandArgs, op: SetOp) -> Result<SetOpArgs> {
+        let mut keys = vec![args.next_bytes()?];
+        while let Some(key) = args.next_optional_bytes()? {
+            keys.push(key);
+        }
+
+        Ok(SetOpArgs {
+            keys,
+            op,
+            result: vec![],
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.set_combine(&self.keys, self.op).await?;
+        Ok(())
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Set(self.result.iter().cloned().map(Frame::BulkString).collect())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SInter(SetOpArgs);
+
+impl SInter {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for SInter {
+    const NAME: &'static str = "sinter";
+
+    fn parse(args: &mut CommandArgs) -> Result<SInter> {
+        Ok(SInter(SetOpArgs::parse(args, SetOp::Intersection)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SUnion(SetOpArgs);
+
+impl SUnion {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for SUnion {
+    const NAME: &'static str = "sunion";
+
+    fn parse(args: &mut CommandArgs) -> Result<SUnion> {
+        Ok(SUnion(SetOpArgs::parse(args, SetOp::Union)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SDiff(SetOpArgs);
+
+impl SDiff {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for SDiff {
+    const NAME: &'static str = "sdiff";
+
+    fn parse(args: &mut CommandArgs) -> Result<SDiff> {
+        Ok(SDiff(SetOpArgs::parse(args, SetOp::Difference)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::SetOp;
+use crate::redis::{Frame, Storage};
+
+/// Shared by `SINTERSTORE`, `SUNIONSTORE` and `SDIFFSTORE`, they differ only in the operation.
+#[derive(Debug)]
+struct SetOpStoreArgs {
+    destination: Bytes,
+    keys: Vec<Bytes>,
+    op: SetOp,
+    result: usize,
+}
+
+impl SetOpStoreArgs {
+    fn parse(args: &mut CommandArgs, op: SetOp) -> Result<SetOpStoreArgs> {
+        let destination = args.next_bytes()?;
+        let mut keys = vec![args.next_bytes()?];
+        while let Some(key) = args.next_optional_bytes()? {
+            keys.push(key);
+        }
+
+        Ok(SetOpStoreArgs {
+            destination,
+            keys,
+            op,
+            result: 0,
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .set_combine_store(&self.destination, &self.keys, self.op)
+            .await?;
+        Ok(())
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SInterStore(SetOpStoreArgs);
+
+impl SInterStore {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for SInterStore {
+    const NAME: &'static str = "sinterstore";
+
+    fn parse(args: &mut CommandArgs) -> Result<SInterStore> {
+        Ok(SInterStore(SetOpStoreArgs::parse(
+            args,
+            SetOp::Intersection,
+        )?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SUnionStore(SetOpStoreArgs);
+
+impl SUnionStore {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for SUnionStore {
+    const NAME: &'static str = "sunionstore";
+
+    fn parse(args: &mut CommandArgs) -> Result<SUnionStore> {
+        Ok(SUnionStore(SetOpStoreArgs::parse(args, SetOp::Union)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SDiffStore(SetOpStoreArgs);
+
+impl SDiffStore {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for SDiffStore {
+    const NAME: &'static str = "sdiffstore";
+
+    fn parse(args: &mut CommandArgs) -> Result<SDiffStore> {
+        Ok(SDiffStore(SetOpStoreArgs::parse(args, SetOp::Difference)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
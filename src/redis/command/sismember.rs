@@ -0,0 +1,81 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct SIsMember {
+    key: Bytes,
+    member: Bytes,
+    result: bool,
+}
+
+impl SIsMember {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let result = storage
+            .set_contains(&self.key, std::slice::from_ref(&self.member))
+            .await?;
+        self.result = result.first().copied().unwrap_or(false);
+        Ok(())
+    }
+}
+
+impl RESPCommand for SIsMember {
+    const NAME: &'static str = "sismember";
+
+    fn parse(args: &mut CommandArgs) -> Result<SIsMember> {
+        let key = args.next_bytes()?;
+        let member = args.next_bytes()?;
+        Ok(SIsMember {
+            key,
+            member,
+            result: false,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SMIsMember {
+    key: Bytes,
+    members: Vec<Bytes>,
+    result: Vec<bool>,
+}
+
+impl SMIsMember {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.set_contains(&self.key, &self.members).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for SMIsMember {
+    const NAME: &'static str = "smismember";
+
+    fn parse(args: &mut CommandArgs) -> Result<SMIsMember> {
+        let key = args.next_bytes()?;
+        let mut members = vec![args.next_bytes()?];
+        while let Some(member) = args.next_optional_bytes()? {
+            members.push(member);
+        }
+
+        Ok(SMIsMember {
+            key,
+            members,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Array(
+            self.result
+                .iter()
+                .map(|found| Frame::Integer(*found as i64))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,35 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct SMembers {
+    key: Bytes,
+    result: Vec<Bytes>,
+}
+
+impl SMembers {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.set_members(&self.key).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for SMembers {
+    const NAME: &'static str = "smembers";
+
+    fn parse(args: &mut CommandArgs) -> Result<SMembers> {
+        let key = args.next_bytes()?;
+        Ok(SMembers {
+            key,
+            result: vec![],
+        })
+    }
+
+    // a set for RESP3 clients, RESP2 ones get an array
+    fn to_response(&self) -> Frame {
+        Frame::Set(self.result.iter().cloned().map(Frame::BulkString).collect())
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct SPop {
+    key: Bytes,
+    // without a count the reply is a single member instead of a set
+    count: Option<usize>,
+    result: Vec<Bytes>,
+}
+
+impl SPop {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.set_pop(&self.key, self.count.unwrap_or(1)).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for SPop {
+    const NAME: &'static str = "spop";
+
+    fn parse(args: &mut CommandArgs) -> Result<SPop> {
+        let key = args.next_bytes()?;
+        let count = args.next_optional_count()?;
+
+        Ok(SPop {
+            key,
+            count,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match self.count {
+            None => self
+                .result
+                .first()
+                .map_or(Frame::Null, |member| Frame::BulkString(member.clone())),
+            Some(_) => Frame::Set(self.result.iter().cloned().map(Frame::BulkString).collect()),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct SRandMember {
+    key: Bytes,
+    // without a count the reply is a single member instead of an array
+    count: Option<i64>,
+    result: Vec<Bytes>,
+}
+
+impl SRandMember {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .set_random_members(&self.key, self.count.unwrap_or(1))
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for SRandMember {
+    const NAME: &'static str = "srandmember";
+
+    fn parse(args: &mut CommandArgs) -> Result<SRandMember> {
+        let key = args.next_bytes()?;
+        let count = args.next_optional_integer()?;
+        // the same range as `HRANDFIELD`
+        if count.is_some_and(|count| count < -(i64::MAX / 2)) {
+            return Err(CmdErrors::ValueOutOfRange.into());
+        }
+
+        Ok(SRandMember {
+            key,
+            count,
+            result: vec![],
+        })
+    }
+
+    // an array even for RESP3 clients, members repeat with a negative count
+    fn to_response(&self) -> Frame {
+        match self.count {
+            None => self
+                .result
+                .first()
+                .map_or(Frame::Null, |member| Frame::BulkString(member.clone())),
+            Some(_) => Frame::Array(self.result.iter().cloned().map(Frame::BulkString).collect()),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct StrLen {
+    key: Bytes,
+    result: usize,
+}
+
+impl StrLen {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.string_len(&self.key).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for StrLen {
+    const NAME: &'static str = "strlen";
+
+    fn parse(args: &mut CommandArgs) -> Result<StrLen> {
+        let key = args.next_bytes()?;
+        Ok(StrLen { key, result: 0 })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::expire::TimeUnit;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::KeyExpiry;
+use crate::redis::{Frame, Storage};
+
+/// Shared by `TTL`, `PTTL`, `EXPIRETIME` and `PEXPIRETIME`: the first two reply with
+/// the time left, the last two with the unix time of the deadline.
+#[derive(Debug)]
+struct TtlArgs {
+    key: Bytes,
+    result: KeyExpiry,
+}
+
+impl TtlArgs {
+    fn parse(args: &mut CommandArgs) -> Result<TtlArgs> {
+        let key = args.next_bytes()?;
+        Ok(TtlArgs {
+            key,
+            result: KeyExpiry::Missing,
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) {
+        self.result = storage.expiry(&self.key).await;
+    }
+
+    fn to_response(&self, unit: TimeUnit, absolute: bool) -> Frame {
+        expiry_frame(self.result, unit, absolute)
+    }
+}
+
+/// Reply of the TTL family: time left or unix time of the deadline, `-2` when
+/// there is nothing and `-1` when there is no deadline.
+pub(super) fn expiry_frame(expiry: KeyExpiry, unit: TimeUnit, absolute: bool) -> Frame {
+    let at = match expiry {
+        KeyExpiry::Missing => return Frame::Integer(-2),
+        KeyExpiry::Persistent => return Frame::Integer(-1),
+        KeyExpiry::At(at) => at,
+    };
+
+    let duration = match absolute {
+        true => at.duration_since(UNIX_EPOCH),
+        false => at.duration_since(SystemTime::now()),
+    }
+    .unwrap_or(Duration::ZERO);
+
+    let value = match (unit, absolute) {
+        // time left is rounded to the closest second, like in Redis
+        (TimeUnit::Seconds, false) => (duration.as_millis() + 500) / 1000,
+        (TimeUnit::Seconds, true) => duration.as_secs() as u128,
+        (TimeUnit::Milliseconds, _) => duration.as_millis(),
+    };
+    Frame::Integer(value as i64)
+}
+
+#[derive(Debug)]
+pub(crate) struct Ttl(TtlArgs);
+
+impl Ttl {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.0.run(storage).await;
+    }
+}
+
+impl RESPCommand for Ttl {
+    const NAME: &'static str = "ttl";
+
+    fn parse(args: &mut CommandArgs) -> Result<Ttl> {
+        Ok(Ttl(TtlArgs::parse(args)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response(TimeUnit::Seconds, false)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct PTtl(TtlArgs);
+
+impl PTtl {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.0.run(storage).await;
+    }
+}
+
+impl RESPCommand for PTtl {
+    const NAME: &'static str = "pttl";
+
+    fn parse(args: &mut CommandArgs) -> Result<PTtl> {
+        Ok(PTtl(TtlArgs::parse(args)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response(TimeUnit::Milliseconds, false)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ExpireTime(TtlArgs);
+
+impl ExpireTime {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.0.run(storage).await;
+    }
+}
+
+impl RESPCommand for ExpireTime {
+    const NAME: &'static str = "expiretime";
+
+    fn parse(args: &mut CommandArgs) -> Result<ExpireTime> {
+        Ok(ExpireTime(TtlArgs::parse(args)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response(TimeUnit::Seconds, true)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct PExpireTime(TtlArgs);
+
+impl PExpireTime {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.0.run(storage).await;
+    }
+}
+
+impl RESPCommand for PExpireTime {
+    const NAME: &'static str = "pexpiretime";
+
+    fn parse(args: &mut CommandArgs) -> Result<PExpireTime> {
+        Ok(PExpireTime(TtlArgs::parse(args)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response(TimeUnit::Milliseconds, true)
+    }
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct Type {
+    key: Bytes,
+    result: Option<&'static str>,
+}
+
+impl Type {
+    pub(crate) async fn run(&mut self, storage: &Storage) {
+        self.result = storage.key_type(&self.key).await;
+    }
+}
+
+impl RESPCommand for Type {
+    const NAME: &'static str = "type";
+
+    fn parse(args: &mut CommandArgs) -> Result<Type> {
+        let key = args.next_bytes()?;
+        Ok(Type { key, result: None })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::SimpleString(Bytes::from_static(self.result.unwrap_or("none").as_bytes()))
+    }
+}
@@ -0,0 +1,47 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::xrange::parse_stream_id;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::StreamId;
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct XAck {
+    key: Bytes,
+    group: Bytes,
+    ids: Vec<StreamId>,
+    result: usize,
+}
+
+impl XAck {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .stream_ack(&self.key, &self.group, &self.ids)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for XAck {
+    const NAME: &'static str = "xack";
+
+    fn parse(args: &mut CommandArgs) -> Result<XAck> {
+        let key = args.next_bytes()?;
+        let group = args.next_bytes()?;
+        let mut ids = vec![parse_stream_id(&args.next_bytes()?, 0)?];
+        while let Some(id) = args.next_optional_bytes()? {
+            ids.push(parse_stream_id(&id, 0)?);
+        }
+        Ok(XAck {
+            key,
+            group,
+            ids,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::xrange::parse_stream_id;
+use super::{parse_integer, CommandArgs, RESPCommand};
+use crate::redis::storage::{NewStreamId, StreamId, StreamTrim, TrimStrategy};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// `MAXLEN | MINID [= | ~] threshold [LIMIT count]` of `XADD` and `XTRIM`,
+/// `None` when the next argument is neither.
+pub(super) fn parse_trim(args: &mut CommandArgs) -> Result<Option<StreamTrim>> {
+    let max_len = if args.next_is(b"maxlen") {
+        true
+    } else if args.next_is(b"minid") {
+        false
+    } else {
+        return Ok(None);
+    };
+    args.next_bytes()?;
+
+    let approximate = args.next_is(b"~");
+    if approximate || args.next_is(b"=") {
+        args.next_bytes()?;
+    }
+
+    let threshold = args.next_bytes()?;
+    let strategy = match max_len {
+        true => {
+            let max_len = parse_integer(&threshold).ok_or(CmdErrors::NotAnInteger)?;
+            let max_len =
+                usize::try_from(max_len).map_err(|_| CmdErrors::NegativeArgument("MAXLEN"))?;
+            TrimStrategy::MaxLen(max_len)
+        }
+        false => TrimStrategy::MinId(parse_stream_id(&threshold, 0)?),
+    };
+
+    let mut limit = None;
+    if args.next_is(b"limit") {
+        args.next_bytes()?;
+        let count = args.next_integer()?;
+        let count = usize::try_from(count).map_err(|_| CmdErrors::NegativeArgument("LIMIT"))?;
+        if !approximate {
+            return Err(CmdErrors::LimitWithoutApproximate.into());
+        }
+        limit = Some(count);
+    }
+
+    Ok(Some(StreamTrim {
+        strategy,
+        approximate,
+        limit,
+    }))
+}
+
+/// `*`, `ms-*` or `ms-seq`, `ms` alone is `ms-0`.
+fn parse_new_id(value: &[u8]) -> Result<NewStreamId, CmdErrors> {
+    if value == b"*" {
+        return Ok(NewStreamId::Auto);
+    }
+    if let Some(ms) = value.strip_suffix(b"-*") {
+        // `ms` itself must not have a sequence number
+        if ms.contains(&b'-') {
+            return Err(CmdErrors::InvalidStreamId);
+        }
+        return Ok(NewStreamId::AutoSeq(parse_stream_id(ms, 0)?.ms));
+    }
+    parse_stream_id(value, 0).map(NewStreamId::Explicit)
+}
+
+#[derive(Debug)]
+pub(crate) struct XAdd {
+    key: Bytes,
+    // `NOMKSTREAM`: don't create the stream when it doesn't exist
+    no_make_stream: bool,
+    trim: Option<StreamTrim>,
+    id: NewStreamId,
+    fields: Vec<(Bytes, Bytes)>,
+    result: Option<StreamId>,
+}
+
+impl XAdd {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        self.result = storage
+            .stream_add(&self.key, self.id, fields, self.no_make_stream, self.trim)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for XAdd {
+    const NAME: &'static str = "xadd";
+
+    fn parse(args: &mut CommandArgs) -> Result<XAdd> {
+        let key = args.next_bytes()?;
+
+        let mut no_make_stream = false;
+        let mut trim = None;
+        loop {
+            if args.next_is(b"nomkstream") {
+                args.next_bytes()?;
+                no_make_stream = true;
+            } else if let Some(parsed) = parse_trim(args)? {
+                trim = Some(parsed);
+            } else {
+                break;
+            }
+        }
+
+        let id = parse_new_id(&args.next_bytes()?)?;
+        let mut fields = vec![];
+        while let Some(field) = args.next_optional_bytes()? {
+            let Some(value) = args.next_optional_bytes()? else {
+                return Err(args.syntax_error(&field));
+            };
+            fields.push((field, value));
+        }
+        if fields.is_empty() {
+            return Err(CmdErrors::MissingCommandArg {
+                command_name: Self::NAME.to_string(),
+            }
+            .into());
+        }
+
+        Ok(XAdd {
+            key,
+            no_make_stream,
+            trim,
+            id,
+            fields,
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match self.result {
+            Some(id) => Frame::BulkString(Bytes::from(id.to_string())),
+            None => Frame::Null,
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::xclaim::{claimed_frame, parse_millis};
+use super::xrange::parse_range_bound;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::{AutoClaimed, StreamId};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+// like in Redis
+const XAUTOCLAIM_DEFAULT_COUNT: usize = 100;
+
+#[derive(Debug)]
+pub(crate) struct XAutoClaim {
+    key: Bytes,
+    group: Bytes,
+    consumer: Bytes,
+    min_idle: u64,
+    start: StreamId,
+    count: usize,
+    just_id: bool,
+    result: AutoClaimed,
+}
+
+impl XAutoClaim {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .stream_auto_claim(
+                &self.key,
+                &self.group,
+                &self.consumer,
+                self.min_idle,
+                self.start,
+                self.count,
+                self.just_id,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for XAutoClaim {
+    const NAME: &'static str = "xautoclaim";
+
+    fn parse(args: &mut CommandArgs) -> Result<XAutoClaim> {
+        let key = args.next_bytes()?;
+        let group = args.next_bytes()?;
+        let consumer = args.next_bytes()?;
+        let min_idle = parse_millis(args, "min-idle-time")?;
+        let start = parse_range_bound(&args.next_bytes()?, true)?;
+
+        let (mut count, mut just_id) = (XAUTOCLAIM_DEFAULT_COUNT, false);
+        while let Some(option) = args.next_optional_bytes()? {
+            match &option.to_ascii_lowercase()[..] {
+                b"count" => {
+                    count = usize::try_from(args.next_integer()?)
+                        .ok()
+                        .filter(|count| *count > 0)
+                        .ok_or(CmdErrors::NotGreaterThanZero("COUNT"))?;
+                }
+                b"justid" => just_id = true,
+                _ => return Err(args.syntax_error(&option)),
+            }
+        }
+
+        Ok(XAutoClaim {
+            key,
+            group,
+            consumer,
+            min_idle,
+            start,
+            count,
+            just_id,
+            result: AutoClaimed {
+                next: StreamId::MIN,
+                claimed: vec![],
+                deleted: vec![],
+            },
+        })
+    }
+
+    // where to continue, the claimed entries and the deleted ones
+    fn to_response(&self) -> Frame {
+        let id_frame = |id: &StreamId| Frame::BulkString(Bytes::from(id.to_string()));
+        Frame::Array(vec![
+            id_frame(&self.result.next),
+            claimed_frame(&self.result.claimed, self.just_id),
+            Frame::Array(self.result.deleted.iter().map(id_frame).collect()),
+        ])
+    }
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::xrange::{entries_frame, parse_stream_id};
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::{ClaimOptions, StreamEntry, StreamId};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// Milliseconds argument that can't be negative.
+pub(super) fn parse_millis(args: &mut CommandArgs, name: &'static str) -> Result<u64> {
+    let millis = args.next_integer()?;
+    Ok(u64::try_from(millis).map_err(|_| CmdErrors::NegativeArgument(name))?)
+}
+
+/// Only IDs of the entries, or the entries themselves.
+pub(super) fn claimed_frame(entries: &[StreamEntry], just_id: bool) -> Frame {
+    match just_id {
+        true => Frame::Array(
+            entries
+                .iter()
+                .map(|entry| Frame::BulkString(Bytes::from(entry.id.to_string())))
+                .collect(),
+        ),
+        false => entries_frame(entries),
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct XClaim {
+    key: Bytes,
+    group: Bytes,
+    consumer: Bytes,
+    min_idle: u64,
+    ids: Vec<StreamId>,
+    options: ClaimOptions,
+    result: Vec<StreamEntry>,
+}
+
+impl XClaim {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .stream_claim(
+                &self.key,
+                &self.group,
+                &self.consumer,
+                self.min_idle,
+                &self.ids,
+                self.options,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for XClaim {
+    const NAME: &'static str = "xclaim";
+
+    fn parse(args: &mut CommandArgs) -> Result<XClaim> {
+        let key = args.next_bytes()?;
+        let group = args.next_bytes()?;
+        let consumer = args.next_bytes()?;
+        let min_idle = parse_millis(args, "min-idle-time")?;
+
+        let mut ids = vec![parse_stream_id(&args.next_bytes()?, 0)?];
+        let mut options = ClaimOptions::default();
+        let mut in_options = false;
+        while let Some(arg) = args.next_optional_bytes()? {
+            // IDs go on until the first option
+            if !in_options {
+                if let Ok(id) = parse_stream_id(&arg, 0) {
+                    ids.push(id);
+                    continue;
+                }
+                in_options = true;
+            }
+            match &arg.to_ascii_lowercase()[..] {
+                b"idle" => options.idle = Some(parse_millis(args, "IDLE")?),
+                b"time" => options.time = Some(parse_millis(args, "TIME")?),
+                b"retrycount" => options.retry_count = Some(parse_millis(args, "RETRYCOUNT")?),
+                b"force" => options.force = true,
+                b"justid" => options.just_id = true,
+                b"lastid" => options.last_id = Some(parse_stream_id(&args.next_bytes()?, 0)?),
+                _ => return Err(args.syntax_error(&arg)),
+            }
+        }
+
+        Ok(XClaim {
+            key,
+            group,
+            consumer,
+            min_idle,
+            ids,
+            options,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        claimed_frame(&self.result, self.options.just_id)
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::xrange::parse_stream_id;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::StreamId;
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct XDel {
+    key: Bytes,
+    ids: Vec<StreamId>,
+    result: usize,
+}
+
+impl XDel {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.stream_delete(&self.key, &self.ids).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for XDel {
+    const NAME: &'static str = "xdel";
+
+    fn parse(args: &mut CommandArgs) -> Result<XDel> {
+        let key = args.next_bytes()?;
+        let mut ids = vec![parse_stream_id(&args.next_bytes()?, 0)?];
+        while let Some(id) = args.next_optional_bytes()? {
+            ids.push(parse_stream_id(&id, 0)?);
+        }
+        Ok(XDel {
+            key,
+            ids,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::xrange::parse_stream_id;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::ReadFrom;
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// `$` or an ID the group has read up to.
+fn parse_group_start(value: &[u8]) -> Result<ReadFrom, CmdErrors> {
+    match value {
+        b"$" => Ok(ReadFrom::End),
+        id => parse_stream_id(id, 0).map(ReadFrom::After),
+    }
+}
+
+/// `ENTRIESREAD` of `CREATE` and `SETID`.
+fn parse_entries_read(args: &mut CommandArgs) -> Result<Option<u64>> {
+    if !args.next_is(b"entriesread") {
+        return Ok(None);
+    }
+    args.next_bytes()?;
+    let entries_read = args.next_integer()?;
+    let entries_read =
+        u64::try_from(entries_read).map_err(|_| CmdErrors::NegativeArgument("ENTRIESREAD"))?;
+    Ok(Some(entries_read))
+}
+
+#[derive(Debug)]
+enum Subcommand {
+    Create {
+        from: ReadFrom,
+        // `MKSTREAM`: create an empty stream when there is none
+        make_stream: bool,
+        entries_read: Option<u64>,
+    },
+    SetId {
+        from: ReadFrom,
+        entries_read: Option<u64>,
+    },
+    Destroy,
+    CreateConsumer(Bytes),
+    DelConsumer(Bytes),
+}
+
+#[derive(Debug)]
+pub(crate) struct XGroup {
+    key: Bytes,
+    group: Bytes,
+    subcommand: Subcommand,
+    result: i64,
+}
+
+impl XGroup {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let (key, group) = (&self.key, &self.group);
+        self.result = match &self.subcommand {
+            Subcommand::Create {
+                from,
+                make_stream,
+                entries_read,
+            } => {
+                storage
+                    .stream_group_create(key, group, *from, *make_stream, *entries_read)
+                    .await?;
+                0
+            }
+            Subcommand::SetId { from, entries_read } => {
+                storage
+                    .stream_group_set_id(key, group, *from, *entries_read)
+                    .await?;
+                0
+            }
+            Subcommand::Destroy => storage.stream_group_destroy(key, group).await? as i64,
+            Subcommand::CreateConsumer(consumer) => {
+                storage
+                    .stream_group_create_consumer(key, group, consumer)
+                    .await? as i64
+            }
+            Subcommand::DelConsumer(consumer) => {
+                storage
+                    .stream_group_delete_consumer(key, group, consumer)
+                    .await? as i64
+            }
+        };
+        Ok(())
+    }
+}
+
+impl RESPCommand for XGroup {
+    const NAME: &'static str = "xgroup";
+
+    fn parse(args: &mut CommandArgs) -> Result<XGroup> {
+        let subcommand = String::from_utf8_lossy(&args.next_bytes()?).into_owned();
+        let key = args.next_bytes()?;
+        let group = args.next_bytes()?;
+
+        let subcommand = match subcommand.to_ascii_lowercase().as_str() {
+            "create" => {
+                let from = parse_group_start(&args.next_bytes()?)?;
+                let mut make_stream = false;
+                if args.next_is(b"mkstream") {
+                    args.next_bytes()?;
+                    make_stream = true;
+                }
+                Subcommand::Create {
+                    from,
+                    make_stream,
+                    entries_read: parse_entries_read(args)?,
+                }
+            }
+            "setid" => Subcommand::SetId {
+                from: parse_group_start(&args.next_bytes()?)?,
+                entries_read: parse_entries_read(args)?,
+            },
+            "destroy" => Subcommand::Destroy,
+            "createconsumer" => Subcommand::CreateConsumer(args.next_bytes()?),
+            "delconsumer" => Subcommand::DelConsumer(args.next_bytes()?),
+            _ => {
+                return Err(CmdErrors::UnknownSubcommand {
+                    command: "XGROUP",
+                    subcommand,
+                }
+                .into())
+            }
+        };
+
+        Ok(XGroup {
+            key,
+            group,
+            subcommand,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match self.subcommand {
+            Subcommand::Create { .. } | Subcommand::SetId { .. } => {
+                Frame::SimpleString(Bytes::from_static(b"OK"))
+            }
+            _ => Frame::Integer(self.result),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::xrange::entry_frame;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::{ConsumerInfo, GroupInfo, StreamEntry, StreamId, StreamInfo};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+fn field(name: &'static str, value: Frame) -> (Frame, Frame) {
+    (
+        Frame::BulkString(Bytes::from_static(name.as_bytes())),
+        value,
+    )
+}
+
+fn id_frame(id: StreamId) -> Frame {
+    Frame::BulkString(Bytes::from(id.to_string()))
+}
+
+fn optional_integer(value: Option<u64>) -> Frame {
+    value.map_or(Frame::Null, |value| Frame::Integer(value as i64))
+}
+
+fn optional_entry(entry: &Option<StreamEntry>) -> Frame {
+    entry.as_ref().map_or(Frame::Null, entry_frame)
+}
+
+#[derive(Debug)]
+enum Subcommand {
+    Stream,
+    Groups,
+    Consumers(Bytes),
+}
+
+#[derive(Debug)]
+enum Info {
+    None,
+    Stream(StreamInfo),
+    Groups(Vec<GroupInfo>),
+    Consumers(Vec<ConsumerInfo>),
+}
+
+#[derive(Debug)]
+pub(crate) struct XInfo {
+    key: Bytes,
+    subcommand: Subcommand,
+    result: Info,
+}
+
+impl XInfo {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = match &self.subcommand {
+            Subcommand::Stream => Info::Stream(storage.stream_info(&self.key).await?),
+            Subcommand::Groups => Info::Groups(storage.stream_groups_info(&self.key).await?),
+            Subcommand::Consumers(group) => {
+                Info::Consumers(storage.stream_consumers_info(&self.key, group).await?)
+            }
+        };
+        Ok(())
+    }
+}
+
+impl RESPCommand for XInfo {
+    const NAME: &'static str = "xinfo";
+
+    fn parse(args: &mut CommandArgs) -> Result<XInfo> {
+        let subcommand = String::from_utf8_lossy(&args.next_bytes()?).into_owned();
+        let key = args.next_bytes()?;
+        let subcommand = match subcommand.to_ascii_lowercase().as_str() {
+            "stream" => Subcommand::Stream,
+            "groups" => Subcommand::Groups,
+            "consumers" => Subcommand::Consumers(args.next_bytes()?),
+            _ => {
+                return Err(CmdErrors::UnknownSubcommand {
+                    command: "XINFO",
+                    subcommand,
+                }
+                .into())
+            }
+        };
+        Ok(XInfo {
+            key,
+            subcommand,
+            result: Info::None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            Info::None => Frame::Null,
+            Info::Stream(info) => Frame::Map(vec![
+                field("length", Frame::Integer(info.len as i64)),
+                field("last-generated-id", id_frame(info.last_generated_id)),
+                field("max-deleted-entry-id", id_frame(info.max_deleted_id)),
+                field("entries-added", Frame::Integer(info.entries_added as i64)),
+                field("recorded-first-entry-id", id_frame(info.first_id)),
+                field("groups", Frame::Integer(info.groups as i64)),
+                field("first-entry", optional_entry(&info.first)),
+                field("last-entry", optional_entry(&info.last)),
+            ]),
+            Info::Groups(groups) => Frame::Array(
+                groups
+                    .iter()
+                    .map(|group| {
+                        Frame::Map(vec![
+                            field("name", Frame::BulkString(group.name.clone())),
+                            field("consumers", Frame::Integer(group.consumers as i64)),
+                            field("pending", Frame::Integer(group.pending as i64)),
+                            field("last-delivered-id", id_frame(group.last_delivered)),
+                            field("entries-read", optional_integer(group.entries_read)),
+                            field("lag", optional_integer(group.lag)),
+                        ])
+                    })
+                    .collect(),
+            ),
+            Info::Consumers(consumers) => Frame::Array(
+                consumers
+                    .iter()
+                    .map(|consumer| {
+                        Frame::Map(vec![
+                            field("name", Frame::BulkString(consumer.name.clone())),
+                            field("pending", Frame::Integer(consumer.pending as i64)),
+                            field("idle", Frame::Integer(consumer.idle as i64)),
+                            // -1 when the consumer never got entries
+                            field(
+                                "inactive",
+                                Frame::Integer(consumer.inactive.map_or(-1, |ms| ms as i64)),
+                            ),
+                        ])
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct XLen {
+    key: Bytes,
+    result: usize,
+}
+
+impl XLen {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.stream_len(&self.key).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for XLen {
+    const NAME: &'static str = "xlen";
+
+    fn parse(args: &mut CommandArgs) -> Result<XLen> {
+        let key = args.next_bytes()?;
+        Ok(XLen { key, result: 0 })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
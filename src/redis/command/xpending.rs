@@ -0,0 +1,125 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::xrange::parse_range_bound;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::{PendingFilter, PendingInfo, PendingSummary, StreamId};
+use crate::redis::{Frame, Storage};
+
+fn id_frame(id: Option<StreamId>) -> Frame {
+    match id {
+        Some(id) => Frame::BulkString(Bytes::from(id.to_string())),
+        None => Frame::Null,
+    }
+}
+
+#[derive(Debug)]
+enum Pending {
+    Summary(PendingSummary),
+    Entries(Vec<PendingInfo>),
+}
+
+#[derive(Debug)]
+pub(crate) struct XPending {
+    key: Bytes,
+    group: Bytes,
+    // the extended form, without it only a summary is given
+    filter: Option<PendingFilter>,
+    result: Pending,
+}
+
+impl XPending {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = match &self.filter {
+            Some(filter) => Pending::Entries(
+                storage
+                    .stream_pending(&self.key, &self.group, filter)
+                    .await?,
+            ),
+            None => Pending::Summary(
+                storage
+                    .stream_pending_summary(&self.key, &self.group)
+                    .await?,
+            ),
+        };
+        Ok(())
+    }
+}
+
+impl RESPCommand for XPending {
+    const NAME: &'static str = "xpending";
+
+    fn parse(args: &mut CommandArgs) -> Result<XPending> {
+        let key = args.next_bytes()?;
+        let group = args.next_bytes()?;
+
+        let mut filter = None;
+        if let Some(mut start) = args.next_optional_bytes()? {
+            let mut min_idle = None;
+            if start.eq_ignore_ascii_case(b"idle") {
+                let idle = args.next_integer()?;
+                min_idle = Some(u64::try_from(idle).unwrap_or(0));
+                start = args.next_bytes()?;
+            }
+            let end = args.next_bytes()?;
+            let count = args.next_integer()?;
+            filter = Some(PendingFilter {
+                min_idle,
+                start: parse_range_bound(&start, true)?,
+                end: parse_range_bound(&end, false)?,
+                // a negative count is no count at all
+                count: usize::try_from(count).unwrap_or(0),
+                consumer: args.next_optional_bytes()?,
+            });
+        }
+        if let Some(option) = args.next_optional_bytes()? {
+            return Err(args.syntax_error(&option));
+        }
+
+        Ok(XPending {
+            key,
+            group,
+            filter,
+            result: Pending::Summary(PendingSummary::default()),
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match &self.result {
+            // count, the smallest and the biggest IDs, then pending entries per consumer
+            Pending::Summary(summary) => Frame::Array(vec![
+                Frame::Integer(summary.count as i64),
+                id_frame(summary.first),
+                id_frame(summary.last),
+                match summary.consumers.is_empty() {
+                    true => Frame::NullArray,
+                    false => Frame::Array(
+                        summary
+                            .consumers
+                            .iter()
+                            .map(|(consumer, count)| {
+                                Frame::Array(vec![
+                                    Frame::BulkString(consumer.clone()),
+                                    Frame::BulkString(Bytes::from(count.to_string())),
+                                ])
+                            })
+                            .collect(),
+                    ),
+                },
+            ]),
+            Pending::Entries(entries) => Frame::Array(
+                entries
+                    .iter()
+                    .map(|entry| {
+                        Frame::Array(vec![
+                            id_frame(Some(entry.id)),
+                            Frame::BulkString(entry.consumer.clone()),
+                            Frame::Integer(entry.idle as i64),
+                            Frame::Integer(entry.delivery_count as i64),
+                        ])
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
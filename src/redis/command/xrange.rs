@@ -0,0 +1,156 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::{StreamEntry, StreamId};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// `ms-seq` or `ms` alone, in which case the sequence number is `missing_seq`.
+pub(super) fn parse_stream_id(value: &[u8], missing_seq: u64) -> Result<StreamId, CmdErrors> {
+    let number = |value: &[u8]| -> Result<u64, CmdErrors> {
+        std::str::from_utf8(value)
+            .ok()
+            .filter(|value| value.bytes().all(|byte| byte.is_ascii_digit()))
+            .and_then(|value| value.parse().ok())
+            .ok_or(CmdErrors::InvalidStreamId)
+    };
+    match value.iter().position(|byte| *byte == b'-') {
+        Some(dash) => Ok(StreamId {
+            ms: number(&value[..dash])?,
+            seq: number(&value[dash + 1..])?,
+        }),
+        None => Ok(StreamId {
+            ms: number(value)?,
+            seq: missing_seq,
+        }),
+    }
+}
+
+/// Range item: `-`, `+`, an ID, exclusive when it starts with `(`. A start
+/// without a sequence number is the first ID of the millisecond, an end the last.
+pub(super) fn parse_range_bound(value: &[u8], is_start: bool) -> Result<StreamId, CmdErrors> {
+    match value {
+        b"-" => return Ok(StreamId::MIN),
+        b"+" => return Ok(StreamId::MAX),
+        _ => {}
+    }
+    let missing_seq = if is_start { 0 } else { u64::MAX };
+    match value.strip_prefix(b"(") {
+        Some(value) => {
+            let id = parse_stream_id(value, missing_seq)?;
+            let bound = if is_start { id.next() } else { id.prev() };
+            bound.ok_or(CmdErrors::InvalidStreamId)
+        }
+        None => parse_stream_id(value, missing_seq),
+    }
+}
+
+/// The ID and a flat array of fields and values, null for a deleted entry.
+pub(super) fn entry_frame(entry: &StreamEntry) -> Frame {
+    let id = Frame::BulkString(Bytes::from(entry.id.to_string()));
+    if entry.fields.is_empty() {
+        return Frame::Array(vec![id, Frame::Null]);
+    }
+    let mut fields = vec![];
+    for (field, value) in &entry.fields {
+        fields.push(Frame::BulkString(field.clone()));
+        fields.push(Frame::BulkString(value.clone()));
+    }
+    Frame::Array(vec![id, Frame::Array(fields)])
+}
+
+pub(super) fn entries_frame(entries: &[StreamEntry]) -> Frame {
+    Frame::Array(entries.iter().map(entry_frame).collect())
+}
+
+/// Shared by `XRANGE` and `XREVRANGE`, the reversed one takes the end first.
+#[derive(Debug)]
+struct RangeArgs {
+    key: Bytes,
+    start: StreamId,
+    end: StreamId,
+    rev: bool,
+    count: Option<usize>,
+    result: Vec<StreamEntry>,
+}
+
+impl RangeArgs {
+    fn parse(args: &mut CommandArgs, rev: bool) -> Result<RangeArgs> {
+        let key = args.next_bytes()?;
+        let (first, second) = (args.next_bytes()?, args.next_bytes()?);
+        let (start, end) = match rev {
+            true => (second, first),
+            false => (first, second),
+        };
+
+        let mut count = None;
+        if let Some(option) = args.next_optional_bytes()? {
+            if !option.eq_ignore_ascii_case(b"count") {
+                return Err(args.syntax_error(&option));
+            }
+            // a negative count is no count at all
+            count = Some(usize::try_from(args.next_integer()?).unwrap_or(0));
+        }
+        if let Some(option) = args.next_optional_bytes()? {
+            return Err(args.syntax_error(&option));
+        }
+
+        Ok(RangeArgs {
+            key,
+            start: parse_range_bound(&start, true)?,
+            end: parse_range_bound(&end, false)?,
+            rev,
+            count,
+            result: vec![],
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .stream_range(&self.key, self.start, self.end, self.rev, self.count)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct XRange(RangeArgs);
+
+impl XRange {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for XRange {
+    const NAME: &'static str = "xrange";
+
+    fn parse(args: &mut CommandArgs) -> Result<XRange> {
+        Ok(XRange(RangeArgs::parse(args, false)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        entries_frame(&self.0.result)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct XRevRange(RangeArgs);
+
+impl XRevRange {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for XRevRange {
+    const NAME: &'static str = "xrevrange";
+
+    fn parse(args: &mut CommandArgs) -> Result<XRevRange> {
+        Ok(XRevRange(RangeArgs::parse(args, true)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        entries_frame(&self.0.result)
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::time::Duration;
+
+use super::xrange::{entries_frame, parse_stream_id};
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::{BlockingOp, Popped, PoppedItems, ReadFrom, StreamEntry};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// Keys and IDs after `STREAMS`, there have to be as many IDs as keys.
+pub(super) fn parse_streams(
+    args: &mut CommandArgs,
+    command: &'static str,
+) -> Result<(Vec<Bytes>, Vec<Bytes>)> {
+    let mut keys = vec![];
+    while let Some(arg) = args.next_optional_bytes()? {
+        keys.push(arg);
+    }
+    if keys.is_empty() || keys.len() % 2 != 0 {
+        return Err(CmdErrors::UnbalancedStreams(command).into());
+    }
+    let ids = keys.split_off(keys.len() / 2);
+    Ok((keys, ids))
+}
+
+/// `BLOCK` milliseconds, zero is waiting forever.
+pub(super) fn parse_block(args: &mut CommandArgs) -> Result<Option<Duration>> {
+    let ms = args.next_integer()?;
+    let ms = u64::try_from(ms).map_err(|_| CmdErrors::NegativeTimeout)?;
+    Ok((ms > 0).then(|| Duration::from_millis(ms)))
+}
+
+/// An array of key and entries pairs, a null array when there are none.
+pub(super) fn streams_frame(streams: &[(Bytes, Vec<StreamEntry>)]) -> Frame {
+    if streams.is_empty() {
+        return Frame::NullArray;
+    }
+    Frame::Array(
+        streams
+            .iter()
+            .map(|(key, entries)| {
+                Frame::Array(vec![Frame::BulkString(key.clone()), entries_frame(entries)])
+            })
+            .collect(),
+    )
+}
+
+#[derive(Debug)]
+pub(crate) struct XRead {
+    keys: Vec<Bytes>,
+    from: Vec<ReadFrom>,
+    count: Option<usize>,
+    // `BLOCK`: wait for new entries when there are none yet
+    block: bool,
+    // no timeout means waiting forever
+    timeout: Option<Duration>,
+    result: Vec<(Bytes, Vec<StreamEntry>)>,
+}
+
+impl XRead {
+    pub(crate) fn blocks(&self) -> bool {
+        self.block
+    }
+
+    pub(crate) async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        let (result, after) = storage
+            .stream_read(&self.keys, &self.from, self.count)
+            .await?;
+        if !result.is_empty() || !self.block {
+            self.result = result;
+            return Ok(());
+        }
+
+        // `$` is resolved by now, entries added while blocking are after these IDs
+        let op = BlockingOp::StreamRead {
+            after,
+            count: self.count,
+        };
+        let popped = storage
+            .blocking_pop(client_id, &self.keys, op, self.timeout)
+            .await?;
+        if let Some(Popped {
+            key,
+            items: PoppedItems::Entries(entries),
+        }) = popped
+        {
+            self.result = vec![(key, entries)];
+        }
+        Ok(())
+    }
+}
+
+impl RESPCommand for XRead {
+    const NAME: &'static str = "xread";
+
+    fn parse(args: &mut CommandArgs) -> Result<XRead> {
+        let (mut count, mut block, mut timeout) = (None, false, None);
+        loop {
+            let option = args.next_bytes()?;
+            match &option.to_ascii_lowercase()[..] {
+                b"count" => count = Some(usize::try_from(args.next_integer()?).unwrap_or(0)),
+                b"block" => {
+                    block = true;
+                    timeout = parse_block(args)?;
+                }
+                b"streams" => break,
+                _ => return Err(args.syntax_error(&option)),
+            }
+        }
+        // like in Redis, zero count is no count at all
+        let count = count.filter(|count| *count > 0);
+
+        let (keys, ids) = parse_streams(args, Self::NAME)?;
+        let from = ids
+            .iter()
+            .map(|id| match &id[..] {
+                b"$" => Ok(ReadFrom::End),
+                id => parse_stream_id(id, 0).map(ReadFrom::After),
+            })
+            .collect::<Result<Vec<ReadFrom>, CmdErrors>>()?;
+
+        Ok(XRead {
+            keys,
+            from,
+            count,
+            block,
+            timeout,
+            result: vec![],
+        })
+    }
+
+    // streams without new entries are left out
+    fn to_response(&self) -> Frame {
+        streams_frame(&self.result)
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::time::Duration;
+
+use super::xrange::parse_stream_id;
+use super::xread::{parse_block, parse_streams, streams_frame};
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::{BlockingOp, GroupReadFrom, Popped, PoppedItems, StreamEntry};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct XReadGroup {
+    group: Bytes,
+    consumer: Bytes,
+    keys: Vec<Bytes>,
+    from: Vec<GroupReadFrom>,
+    count: Option<usize>,
+    block: bool,
+    // no timeout means waiting forever
+    timeout: Option<Duration>,
+    // `NOACK`: delivered entries don't become pending
+    no_ack: bool,
+    result: Vec<(Bytes, Vec<StreamEntry>)>,
+}
+
+impl XReadGroup {
+    pub(crate) fn blocks(&self) -> bool {
+        self.block
+    }
+
+    pub(crate) async fn run(&mut self, storage: &Storage, client_id: u64) -> Result<()> {
+        self.result = storage
+            .stream_group_read(
+                &self.keys,
+                &self.from,
+                &self.group,
+                &self.consumer,
+                self.count,
+                self.no_ack,
+            )
+            .await?;
+        // only reads of new entries wait, pending entries are there or not
+        let only_new = self.from.iter().all(|from| *from == GroupReadFrom::New);
+        if !self.result.is_empty() || !self.block || !only_new {
+            return Ok(());
+        }
+
+        let op = BlockingOp::GroupRead {
+            group: self.group.clone(),
+            consumer: self.consumer.clone(),
+            count: self.count,
+            no_ack: self.no_ack,
+        };
+        let popped = storage
+            .blocking_pop(client_id, &self.keys, op, self.timeout)
+            .await?;
+        if let Some(Popped {
+            key,
+            items: PoppedItems::Entries(entries),
+        }) = popped
+        {
+            self.result = vec![(key, entries)];
+        }
+        Ok(())
+    }
+}
+
+impl RESPCommand for XReadGroup {
+    const NAME: &'static str = "xreadgroup";
+
+    fn parse(args: &mut CommandArgs) -> Result<XReadGroup> {
+        let option = args.next_bytes()?;
+        if !option.eq_ignore_ascii_case(b"group") {
+            return Err(args.syntax_error(&option));
+        }
+        let group = args.next_bytes()?;
+        let consumer = args.next_bytes()?;
+
+        let (mut count, mut block, mut timeout, mut no_ack) = (None, false, None, false);
+        loop {
+            let option = args.next_bytes()?;
+            match &option.to_ascii_lowercase()[..] {
+                b"count" => count = Some(usize::try_from(args.next_integer()?).unwrap_or(0)),
+                b"block" => {
+                    block = true;
+                    timeout = parse_block(args)?;
+                }
+                b"noack" => no_ack = true,
+                b"streams" => break,
+                _ => return Err(args.syntax_error(&option)),
+            }
+        }
+        let count = count.filter(|count| *count > 0);
+
+        let (keys, ids) = parse_streams(args, Self::NAME)?;
+        let from = ids
+            .iter()
+            .map(|id| match &id[..] {
+                b">" => Ok(GroupReadFrom::New),
+                id => parse_stream_id(id, 0).map(GroupReadFrom::Pending),
+            })
+            .collect::<Result<Vec<GroupReadFrom>, CmdErrors>>()?;
+
+        Ok(XReadGroup {
+            group,
+            consumer,
+            keys,
+            from,
+            count,
+            block,
+            timeout,
+            no_ack,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        streams_frame(&self.result)
+    }
+}
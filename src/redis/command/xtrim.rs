@@ -0,0 +1,42 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::xadd::parse_trim;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::StreamTrim;
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct XTrim {
+    key: Bytes,
+    trim: StreamTrim,
+    result: usize,
+}
+
+impl XTrim {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.stream_trim(&self.key, self.trim).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for XTrim {
+    const NAME: &'static str = "xtrim";
+
+    fn parse(args: &mut CommandArgs) -> Result<XTrim> {
+        let key = args.next_bytes()?;
+        let Some(trim) = parse_trim(args)? else {
+            let option = args.next_bytes()?;
+            return Err(args.syntax_error(&option));
+        };
+        Ok(XTrim {
+            key,
+            trim,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
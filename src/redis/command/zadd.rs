@@ -0,0 +1,101 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{parse_float, CommandArgs, RESPCommand};
+use crate::redis::storage::{ZAddOptions, ZAdded};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct ZAdd {
+    key: Bytes,
+    pairs: Vec<(f64, Bytes)>,
+    options: ZAddOptions,
+    // `CH`: reply with the number of added and updated members
+    changed: bool,
+    result: ZAdded,
+}
+
+impl ZAdd {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .zset_add(&self.key, &self.pairs, self.options)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for ZAdd {
+    const NAME: &'static str = "zadd";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZAdd> {
+        let key = args.next_bytes()?;
+
+        let mut options = ZAddOptions::default();
+        let mut changed = false;
+        // options come before the first score, a score can't look like an option
+        loop {
+            let flag = if args.next_is(b"nx") {
+                &mut options.only_new
+            } else if args.next_is(b"xx") {
+                &mut options.only_existing
+            } else if args.next_is(b"gt") {
+                &mut options.greater
+            } else if args.next_is(b"lt") {
+                &mut options.less
+            } else if args.next_is(b"incr") {
+                &mut options.increment
+            } else if args.next_is(b"ch") {
+                &mut changed
+            } else {
+                break;
+            };
+            *flag = true;
+            args.next_bytes()?;
+        }
+
+        let mut pairs = vec![];
+        while let Some(score) = args.next_optional_bytes()? {
+            let Some(member) = args.next_optional_bytes()? else {
+                return Err(args.syntax_error(&score));
+            };
+            let score = parse_float(&score).ok_or(CmdErrors::NotAFloat)?;
+            pairs.push((score, member));
+        }
+        if pairs.is_empty() {
+            return Err(CmdErrors::MissingCommandArg {
+                command_name: Self::NAME.to_string(),
+            }
+            .into());
+        }
+
+        if options.only_new && options.only_existing {
+            return Err(CmdErrors::NxAndXx.into());
+        }
+        if (options.greater && options.less)
+            || (options.only_new && (options.greater || options.less))
+        {
+            return Err(CmdErrors::GtLtNx.into());
+        }
+        if options.increment && pairs.len() > 1 {
+            return Err(CmdErrors::IncrWithManyPairs.into());
+        }
+
+        Ok(ZAdd {
+            key,
+            pairs,
+            options,
+            changed,
+            result: ZAdded::default(),
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        if self.options.increment {
+            return self.result.score.map_or(Frame::Null, Frame::Double);
+        }
+        match self.changed {
+            true => Frame::Integer((self.result.added + self.result.updated) as i64),
+            false => Frame::Integer(self.result.added as i64),
+        }
+    }
+}
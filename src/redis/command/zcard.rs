@@ -0,0 +1,31 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct ZCard {
+    key: Bytes,
+    result: usize,
+}
+
+impl ZCard {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.zset_len(&self.key).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for ZCard {
+    const NAME: &'static str = "zcard";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZCard> {
+        let key = args.next_bytes()?;
+        Ok(ZCard { key, result: 0 })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
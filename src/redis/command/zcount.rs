@@ -0,0 +1,43 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::zrange::parse_score_bound;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::ScoreBound;
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct ZCount {
+    key: Bytes,
+    min: ScoreBound,
+    max: ScoreBound,
+    result: usize,
+}
+
+impl ZCount {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.zset_count(&self.key, self.min, self.max).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for ZCount {
+    const NAME: &'static str = "zcount";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZCount> {
+        let key = args.next_bytes()?;
+        let min = parse_score_bound(&args.next_bytes()?)?;
+        let max = parse_score_bound(&args.next_bytes()?)?;
+
+        Ok(ZCount {
+            key,
+            min,
+            max,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
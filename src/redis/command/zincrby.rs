@@ -0,0 +1,49 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{parse_float, CommandArgs, RESPCommand};
+use crate::redis::storage::ZAddOptions;
+use crate::redis::{CmdErrors, Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct ZIncrBy {
+    key: Bytes,
+    increment: f64,
+    member: Bytes,
+    result: f64,
+}
+
+impl ZIncrBy {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        let options = ZAddOptions {
+            increment: true,
+            ..ZAddOptions::default()
+        };
+        let pairs = [(self.increment, self.member.clone())];
+        let added = storage.zset_add(&self.key, &pairs, options).await?;
+        // without conditions the score always changes
+        self.result = added.score.unwrap_or(self.increment);
+        Ok(())
+    }
+}
+
+impl RESPCommand for ZIncrBy {
+    const NAME: &'static str = "zincrby";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZIncrBy> {
+        let key = args.next_bytes()?;
+        let increment = parse_float(&args.next_bytes()?).ok_or(CmdErrors::NotAFloat)?;
+        let member = args.next_bytes()?;
+
+        Ok(ZIncrBy {
+            key,
+            increment,
+            member,
+            result: 0.0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Double(self.result)
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::zrange::scored_frame;
+use super::{CommandArgs, RESPCommand};
+use crate::redis::storage::ZSetEnd;
+use crate::redis::{Frame, Storage};
+
+/// Shared by `ZPOPMIN` and `ZPOPMAX`, they differ only in the end they pop from.
+#[derive(Debug)]
+struct ZPopArgs {
+    key: Bytes,
+    end: ZSetEnd,
+    count: usize,
+    result: Vec<(Bytes, f64)>,
+}
+
+impl ZPopArgs {
+    fn parse(args: &mut CommandArgs, end: ZSetEnd) -> Result<ZPopArgs> {
+        let key = args.next_bytes()?;
+        let count = args.next_optional_count()?.unwrap_or(1);
+
+        Ok(ZPopArgs {
+            key,
+            end,
+            count,
+            result: vec![],
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.zset_pop(&self.key, self.end, self.count).await?;
+        Ok(())
+    }
+
+    fn to_response(&self) -> Frame {
+        scored_frame(&self.result, true)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ZPopMin(ZPopArgs);
+
+impl ZPopMin {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for ZPopMin {
+    const NAME: &'static str = "zpopmin";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZPopMin> {
+        Ok(ZPopMin(ZPopArgs::parse(args, ZSetEnd::Min)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ZPopMax(ZPopArgs);
+
+impl ZPopMax {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for ZPopMax {
+    const NAME: &'static str = "zpopmax";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZPopMax> {
+        Ok(ZPopMax(ZPopArgs::parse(args, ZSetEnd::Max)?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{parse_float, parse_integer, CommandArgs, RESPCommand};
+use crate::redis::storage::{LexBound, ScoreBound, ZRange as Range};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// Score range item: a float, `-inf`, `+inf`, exclusive when it starts with `(`.
+pub(super) fn parse_score_bound(value: &[u8]) -> Result<ScoreBound, CmdErrors> {
+    let (value, exclusive) = match value.strip_prefix(b"(") {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let value = parse_float(value).ok_or(CmdErrors::MinOrMaxNotAFloat)?;
+    Ok(ScoreBound { value, exclusive })
+}
+
+/// Lexicographical range item: `-`, `+`, `[member` or `(member`.
+pub(super) fn parse_lex_bound(value: &Bytes) -> Result<LexBound, CmdErrors> {
+    match value.first() {
+        Some(b'-') if value.len() == 1 => Ok(LexBound::NegativeInfinity),
+        Some(b'+') if value.len() == 1 => Ok(LexBound::PositiveInfinity),
+        Some(b'[') => Ok(LexBound::Inclusive(value.slice(1..))),
+        Some(b'(') => Ok(LexBound::Exclusive(value.slice(1..))),
+        _ => Err(CmdErrors::MinOrMaxNotAStringRange),
+    }
+}
+
+/// Members with their scores right after them, or members alone.
+pub(super) fn scored_frame(pairs: &[(Bytes, f64)], with_scores: bool) -> Frame {
+    let mut items = vec![];
+    for (member, score) in pairs {
+        items.push(Frame::BulkString(member.clone()));
+        if with_scores {
+            items.push(Frame::Double(*score));
+        }
+    }
+    Frame::Array(items)
+}
+
+#[derive(Debug)]
+pub(crate) struct ZRange {
+    key: Bytes,
+    range: Range,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+    with_scores: bool,
+    result: Vec<(Bytes, f64)>,
+}
+
+impl ZRange {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .zset_range(&self.key, &self.range, self.rev, self.limit)
+            .await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for ZRange {
+    const NAME: &'static str = "zrange";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZRange> {
+        let key = args.next_bytes()?;
+        let start = args.next_bytes()?;
+        let stop = args.next_bytes()?;
+
+        let (mut by_score, mut by_lex, mut rev, mut with_scores) = (false, false, false, false);
+        let mut limit = None;
+        while let Some(option) = args.next_optional_bytes()? {
+            match &option.to_ascii_lowercase()[..] {
+                b"byscore" if !by_lex => by_score = true,
+                b"bylex" if !by_score => by_lex = true,
+                b"rev" => rev = true,
+                b"withscores" => with_scores = true,
+                b"limit" => limit = Some((args.next_integer()?, args.next_integer()?)),
+                _ => return Err(args.syntax_error(&option)),
+            }
+        }
+        if limit.is_some() && !by_score && !by_lex {
+            return Err(CmdErrors::LimitWithoutBy.into());
+        }
+        if with_scores && by_lex {
+            return Err(CmdErrors::WithScoresByLex.into());
+        }
+
+        // reversed score and lexicographical ranges go from max to min
+        let (min, max) = match rev {
+            true => (&stop, &start),
+            false => (&start, &stop),
+        };
+        let range = match (by_score, by_lex) {
+            (true, _) => Range::Score {
+                min: parse_score_bound(min)?,
+                max: parse_score_bound(max)?,
+            },
+            (_, true) => Range::Lex {
+                min: parse_lex_bound(min)?,
+                max: parse_lex_bound(max)?,
+            },
+            _ => Range::Rank {
+                start: parse_integer(&start).ok_or(CmdErrors::NotAnInteger)?,
+                stop: parse_integer(&stop).ok_or(CmdErrors::NotAnInteger)?,
+            },
+        };
+
+        Ok(ZRange {
+            key,
+            range,
+            rev,
+            limit,
+            with_scores,
+            result: vec![],
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        scored_frame(&self.result, self.with_scores)
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct ZRank {
+    key: Bytes,
+    member: Bytes,
+    with_score: bool,
+    result: Option<(usize, f64)>,
+}
+
+impl ZRank {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.zset_rank(&self.key, &self.member).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for ZRank {
+    const NAME: &'static str = "zrank";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZRank> {
+        let key = args.next_bytes()?;
+        let member = args.next_bytes()?;
+        let with_score = match args.next_optional_bytes()? {
+            None => false,
+            Some(option) if option.eq_ignore_ascii_case(b"withscore") => true,
+            Some(option) => return Err(args.syntax_error(&option)),
+        };
+
+        Ok(ZRank {
+            key,
+            member,
+            with_score,
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        match (self.result, self.with_score) {
+            (None, false) => Frame::Null,
+            (None, true) => Frame::NullArray,
+            (Some((rank, _)), false) => Frame::Integer(rank as i64),
+            (Some((rank, score)), true) => {
+                Frame::Array(vec![Frame::Integer(rank as i64), Frame::Double(score)])
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct ZRem {
+    key: Bytes,
+    members: Vec<Bytes>,
+    result: usize,
+}
+
+impl ZRem {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.zset_remove(&self.key, &self.members).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for ZRem {
+    const NAME: &'static str = "zrem";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZRem> {
+        let key = args.next_bytes()?;
+        let mut members = vec![args.next_bytes()?];
+        while let Some(member) = args.next_optional_bytes()? {
+            members.push(member);
+        }
+
+        Ok(ZRem {
+            key,
+            members,
+            result: 0,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{CommandArgs, RESPCommand};
+use crate::redis::{Frame, Storage};
+
+#[derive(Debug)]
+pub(crate) struct ZScore {
+    key: Bytes,
+    member: Bytes,
+    result: Option<f64>,
+}
+
+impl ZScore {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage.zset_score(&self.key, &self.member).await?;
+        Ok(())
+    }
+}
+
+impl RESPCommand for ZScore {
+    const NAME: &'static str = "zscore";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZScore> {
+        let key = args.next_bytes()?;
+        let member = args.next_bytes()?;
+        Ok(ZScore {
+            key,
+            member,
+            result: None,
+        })
+    }
+
+    fn to_response(&self) -> Frame {
+        self.result.map_or(Frame::Null, Frame::Double)
+    }
+}
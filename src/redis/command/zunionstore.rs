@@ -0,0 +1,132 @@
+use anyhow::Result;
+use bytes::Bytes;
+
+use super::{parse_float, CommandArgs, RESPCommand};
+use crate::redis::storage::{Aggregate, SetOp};
+use crate::redis::{CmdErrors, Frame, Storage};
+
+/// Shared by `ZUNIONSTORE` and `ZINTERSTORE`, they differ only in the operation.
+#[derive(Debug)]
+struct ZStoreArgs {
+    destination: Bytes,
+    keys: Vec<Bytes>,
+    weights: Vec<f64>,
+    aggregate: Aggregate,
+    op: SetOp,
+    result: usize,
+}
+
+impl ZStoreArgs {
+    fn parse(args: &mut CommandArgs, command_name: &'static str, op: SetOp) -> Result<ZStoreArgs> {
+        let destination = args.next_bytes()?;
+        let num_keys = match args.next_integer()? {
+            num_keys if num_keys > 0 => num_keys,
+            _ => return Err(CmdErrors::NoInputKeys(command_name).into()),
+        };
+        let keys = (0..num_keys)
+            .map(|_| args.next_bytes())
+            .collect::<Result<Vec<Bytes>>>()?;
+
+        let mut weights = vec![];
+        let mut aggregate = Aggregate::Sum;
+        while let Some(option) = args.next_optional_bytes()? {
+            match &option.to_ascii_lowercase()[..] {
+                b"weights" => {
+                    weights = keys
+                        .iter()
+                        .map(|_| {
+                            let weight = args.next_bytes()?;
+                            parse_float(&weight).ok_or(CmdErrors::WeightNotAFloat.into())
+                        })
+                        .collect::<Result<Vec<f64>>>()?;
+                }
+                b"aggregate" => {
+                    let value = args.next_bytes()?;
+                    aggregate = match &value.to_ascii_lowercase()[..] {
+                        b"sum" => Aggregate::Sum,
+                        b"min" => Aggregate::Min,
+                        b"max" => Aggregate::Max,
+                        _ => return Err(args.syntax_error(&value)),
+                    };
+                }
+                _ => return Err(args.syntax_error(&option)),
+            }
+        }
+
+        Ok(ZStoreArgs {
+            destination,
+            keys,
+            weights,
+            aggregate,
+            op,
+            result: 0,
+        })
+    }
+
+    async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.result = storage
+            .zset_combine_store(
+                &self.destination,
+                &self.keys,
+                &self.weights,
+                self.aggregate,
+                self.op,
+            )
+            .await?;
+        Ok(())
+    }
+
+    fn to_response(&self) -> Frame {
+        Frame::Integer(self.result as i64)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ZUnionStore(ZStoreArgs);
+
+impl ZUnionStore {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for ZUnionStore {
+    const NAME: &'static str = "zunionstore";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZUnionStore> {
+        Ok(ZUnionStore(ZStoreArgs::parse(
+            args,
+            Self::NAME,
+            SetOp::Union,
+        )?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ZInterStore(ZStoreArgs);
+
+impl ZInterStore {
+    pub(crate) async fn run(&mut self, storage: &Storage) -> Result<()> {
+        self.0.run(storage).await
+    }
+}
+
+impl RESPCommand for ZInterStore {
+    const NAME: &'static str = "zinterstore";
+
+    fn parse(args: &mut CommandArgs) -> Result<ZInterStore> {
+        Ok(ZInterStore(ZStoreArgs::parse(
+            args,
+            Self::NAME,
+            SetOp::Intersection,
+        )?))
+    }
+
+    fn to_response(&self) -> Frame {
+        self.0.to_response()
+    }
+}
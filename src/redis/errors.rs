@@ -1,40 +1,297 @@
+use bytes::Bytes;
 use thiserror::Error;
 
+use crate::redis::Frame;
+
 #[derive(Debug, Error, PartialEq)]
 pub(crate) enum FrameErrors {
+    #[error("frame is not complete yet")]
+    Incomplete,
+
     #[error("unexpected first byte: 0x{0:02X}")]
     IncorrectFirstByte(u8),
 
     #[error("missing CRLF terminator")]
     MissingCRLF,
 
-    #[error("Incorrect bulk string length")]
+    #[error("invalid bulk string length")]
     IncorrectBulkStringLength,
 
-    #[error("Array items must be bulk strings")]
-    WrongArrayItemFormat,
+    #[error("invalid integer")]
+    IncorrectInteger,
+
+    #[error("invalid array length")]
+    IncorrectArrayLength,
+
+    #[error("invalid null")]
+    IncorrectNull,
+
+    #[error("invalid double")]
+    IncorrectDouble,
+
+    #[error("invalid boolean")]
+    IncorrectBoolean,
+
+    #[error("invalid big number")]
+    IncorrectBigNumber,
+
+    #[error("invalid verbatim string")]
+    IncorrectVerbatimString,
+
+    #[error("unbalanced quotes in request")]
+    UnbalancedQuotes,
+
+    #[error("too big inline request")]
+    InlineTooBig,
+
+    #[error("too many nested aggregates")]
+    NestingTooDeep,
 
-    #[error("")]
+    #[error("string is not valid UTF-8")]
     StringInterpretationError,
 }
 
+impl FrameErrors {
+    /// A malformed frame leaves the rest of the input unreadable,
+    /// so the client gets this reply right before the connection is closed.
+    pub(crate) fn as_error_frame(&self) -> Frame {
+        Frame::Error(Bytes::from(format!("ERR Protocol error: {}", self)))
+    }
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub(crate) enum CmdErrors {
-    #[error("Frame is not an array frame or empty")]
+    #[error("invalid command frame, expected a non-empty array")]
     InvalidArrayFrame,
 
-    #[error("Wrong arg - {arg:}, for {command_name:}")]
-    IncorrectCommandArg {
-        command_name: &'static str,
-        arg: String,
+    #[error("syntax error, unexpected `{arg:}` for '{command_name:}' command")]
+    IncorrectCommandArg { command_name: String, arg: String },
+
+    #[error("wrong number of arguments for '{command_name:}' command")]
+    MissingCommandArg { command_name: String },
+
+    #[error("unknown command '{0}'")]
+    UnknownCommand(String),
+
+    #[error("unknown subcommand '{subcommand}'. Try {command} HELP.")]
+    UnknownSubcommand {
+        command: &'static str,
+        subcommand: String,
     },
 
-    #[error("Wrong or missing args for {command_name:}, args - {arg_name:}")]
-    MissingCommandArg {
-        command_name: &'static str,
-        arg_name: &'static str,
+    #[error("unsupported protocol version")]
+    UnsupportedProtocol,
+
+    #[error("value is not an integer or out of range")]
+    NotAnInteger,
+
+    #[error("invalid expire time in '{command_name:}' command")]
+    InvalidExpireTime { command_name: String },
+
+    #[error("Operation against a key holding the wrong kind of value")]
+    WrongType,
+
+    #[error("value is not a valid float")]
+    NotAFloat,
+
+    #[error("increment or decrement would overflow")]
+    Overflow,
+
+    #[error("decrement would overflow")]
+    DecrementOverflow,
+
+    #[error("offset is out of range")]
+    OffsetOutOfRange,
+
+    #[error("string exceeds maximum allowed size (proto-max-bulk-len)")]
+    StringTooLong,
+
+    #[error("source and destination objects are the same")]
+    SameObject,
+
+    #[error("DB index is out of range")]
+    DbIndexOutOfRange,
+
+    #[error("If you want both the length and indexes, please just use IDX.")]
+    LcsLenAndIdx,
+
+    #[error("Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len")]
+    LcsTooBig,
+
+    #[error("bit offset is not an integer or out of range")]
+    BitOffsetOutOfRange,
+
+    #[error("bit is not an integer or out of range")]
+    NotABit,
+
+    #[error("The bit argument must be 1 or 0.")]
+    BitArgument,
+
+    #[error("BITOP NOT must be called with a single source key.")]
+    BitOpNotSingleKey,
+
+    #[error("Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.")]
+    InvalidBitfieldType,
+
+    #[error("Invalid OVERFLOW type specified")]
+    InvalidOverflowType,
+
+    #[error("BITFIELD_RO only supports the GET subcommand")]
+    BitfieldReadOnly,
+
+    #[error("Key is not a valid HyperLogLog string value.")]
+    NotAHyperLogLog,
+
+    #[error("Corrupted HLL object detected")]
+    CorruptedHyperLogLog,
+
+    #[error("invalid longitude,latitude pair {longitude:.6},{latitude:.6}")]
+    InvalidLonLat { longitude: f64, latitude: f64 },
+
+    #[error("unsupported unit provided. please use M, KM, FT, MI")]
+    UnsupportedUnit,
+
+    #[error("could not decode requested zset member")]
+    GeoMemberNotFound,
+
+    #[error("exactly one of {options} can be specified for {command}")]
+    GeoExactlyOne {
+        options: &'static str,
+        command: &'static str,
     },
 
-    #[error("`{0}`")]
-    UnknownCommand(String),
+    #[error("radius cannot be negative")]
+    NegativeRadius,
+
+    #[error("height or width cannot be negative")]
+    NegativeBox,
+
+    #[error("{0} is not compatible with WITHDIST, WITHHASH and WITHCOORD options")]
+    GeoStoreWithReplyOptions(&'static str),
+
+    #[error("increment would produce NaN or Infinity")]
+    NaNOrInfinity,
+
+    #[error("hash value is not an integer")]
+    HashValueNotAnInteger,
+
+    #[error("hash value is not a float")]
+    HashValueNotAFloat,
+
+    #[error("Mandatory argument FIELDS is missing or not at the right position")]
+    MissingFields,
+
+    #[error("The `numfields` parameter must match the number of arguments")]
+    NumFieldsMismatch,
+
+    #[error("resulting score is not a number (NaN)")]
+    ScoreIsNaN,
+
+    #[error("min or max is not a float")]
+    MinOrMaxNotAFloat,
+
+    #[error("min or max not valid string range item")]
+    MinOrMaxNotAStringRange,
+
+    #[error("weight value is not a float")]
+    WeightNotAFloat,
+
+    #[error("at least 1 input key is needed for '{0}' command")]
+    NoInputKeys(&'static str),
+
+    #[error("XX and NX options at the same time are not compatible")]
+    NxAndXx,
+
+    #[error("GT, LT, and/or NX options at the same time are not compatible")]
+    GtLtNx,
+
+    #[error("NX and XX, GT or LT options at the same time are not compatible")]
+    ExpireNxWithOthers,
+
+    #[error("GT and LT options at the same time are not compatible")]
+    ExpireGtAndLt,
+
+    #[error("INCR option supports a single increment-element pair")]
+    IncrWithManyPairs,
+
+    #[error("syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX")]
+    LimitWithoutBy,
+
+    #[error("syntax error, WITHSCORES not supported in combination with BYLEX")]
+    WithScoresByLex,
+
+    #[error("Invalid stream ID specified as stream command argument")]
+    InvalidStreamId,
+
+    #[error("The ID specified in XADD is equal or smaller than the target stream top item")]
+    StreamIdTooSmall,
+
+    #[error("The ID specified in XADD must be greater than 0-0")]
+    StreamIdZero,
+
+    #[error("The stream has exhausted the last possible ID, unable to add more items")]
+    StreamExhausted,
+
+    #[error("syntax error, LIMIT cannot be used without the special ~ option")]
+    LimitWithoutApproximate,
+
+    #[error(
+        "Unbalanced '{0}' list of streams: for each stream key an ID or '$' must be specified."
+    )]
+    UnbalancedStreams(&'static str),
+
+    #[error("Consumer Group name already exists")]
+    BusyGroup,
+
+    #[error("No such key '{key}' or consumer group '{group}'")]
+    NoGroup { key: String, group: String },
+
+    #[error("The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically.")]
+    XGroupKeyMissing,
+
+    #[error("no such key")]
+    NoSuchKey,
+
+    #[error("index out of range")]
+    IndexOutOfRange,
+
+    #[error("value is out of range")]
+    ValueOutOfRange,
+
+    #[error("value is out of range, must be positive")]
+    NotPositive,
+
+    #[error("{0} can't be negative")]
+    NegativeArgument(&'static str),
+
+    #[error("{0} should be greater than 0")]
+    NotGreaterThanZero(&'static str),
+
+    #[error("timeout is not a float or out of range")]
+    InvalidTimeout,
+
+    #[error("timeout is negative")]
+    NegativeTimeout,
+
+    #[error("RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list")]
+    ZeroRank,
+}
+
+impl CmdErrors {
+    /// First word of the error reply, clients use it to tell errors apart.
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            CmdErrors::IncorrectCommandArg { .. } => "SYNTAX",
+            CmdErrors::UnsupportedProtocol => "NOPROTO",
+            CmdErrors::WrongType | CmdErrors::NotAHyperLogLog => "WRONGTYPE",
+            CmdErrors::CorruptedHyperLogLog => "INVALIDOBJ",
+            CmdErrors::BusyGroup => "BUSYGROUP",
+            CmdErrors::NoGroup { .. } => "NOGROUP",
+            _ => "ERR",
+        }
+    }
+
+    pub(crate) fn as_error_frame(&self) -> Frame {
+        Frame::Error(Bytes::from(format!("{} {}", self.code(), self)))
+    }
 }
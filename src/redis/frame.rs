@@ -1,9 +1,16 @@
 // RESP encoding/decoding modul
-use bytes::Bytes;
-use std::{fmt::Display, unimplemented};
+use bytes::{BufMut, Bytes, BytesMut};
+use std::fmt::{Display, Write};
 
 use crate::redis::FrameErrors;
 
+const CRLF: &[u8] = b"\r\n";
+// same as `proto-max-bulk-len` default in Redis - 512MB
+const MAX_BULK_STRING_LENGTH: i64 = 512 * 1024 * 1024;
+const MAX_NESTING_DEPTH: usize = 128;
+// same as the inline request limit in Redis - 64KB
+const MAX_INLINE_LENGTH: usize = 64 * 1024;
+
 #[repr(u8)]
 enum SpecialBytes {
     CR = b'\r',
@@ -12,10 +19,21 @@ enum SpecialBytes {
 
 #[repr(u8)]
 enum FirstByte {
-    Plus = b'+',   // simple string
-    Colon = b':',  // integer
-    Dollar = b'$', // bulk string
-    Star = b'*',   // array
+    Plus = b'+',        // simple string
+    Minus = b'-',       // simple error
+    Colon = b':',       // integer
+    Dollar = b'$',      // bulk string
+    Star = b'*',        // array
+    Underscore = b'_',  // null, RESP3
+    Comma = b',',       // double, RESP3
+    Hash = b'#',        // boolean, RESP3
+    LeftParen = b'(',   // big number, RESP3
+    Bang = b'!',        // blob error, RESP3
+    Equal = b'=',       // verbatim string, RESP3
+    Percent = b'%',     // map, RESP3
+    Tilde = b'~',       // set, RESP3
+    GreaterThan = b'>', // push, RESP3
+    Pipe = b'|',        // attribute, RESP3
 }
 
 impl TryFrom<u8> for FirstByte {
@@ -24,244 +42,863 @@ impl TryFrom<u8> for FirstByte {
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             value if value == FirstByte::Plus as u8 => Ok(FirstByte::Plus),
+            value if value == FirstByte::Minus as u8 => Ok(FirstByte::Minus),
             value if value == FirstByte::Star as u8 => Ok(FirstByte::Star),
             value if value == FirstByte::Dollar as u8 => Ok(FirstByte::Dollar),
             value if value == FirstByte::Colon as u8 => Ok(FirstByte::Colon),
+            value if value == FirstByte::Underscore as u8 => Ok(FirstByte::Underscore),
+            value if value == FirstByte::Comma as u8 => Ok(FirstByte::Comma),
+            value if value == FirstByte::Hash as u8 => Ok(FirstByte::Hash),
+            value if value == FirstByte::LeftParen as u8 => Ok(FirstByte::LeftParen),
+            value if value == FirstByte::Bang as u8 => Ok(FirstByte::Bang),
+            value if value == FirstByte::Equal as u8 => Ok(FirstByte::Equal),
+            value if value == FirstByte::Percent as u8 => Ok(FirstByte::Percent),
+            value if value == FirstByte::Tilde as u8 => Ok(FirstByte::Tilde),
+            value if value == FirstByte::GreaterThan as u8 => Ok(FirstByte::GreaterThan),
+            value if value == FirstByte::Pipe as u8 => Ok(FirstByte::Pipe),
             other => Err(FrameErrors::IncorrectFirstByte(other)),
         }
     }
 }
 
+/// Protocol version negotiated with `HELLO`, every connection starts with RESP2.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Protocol {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum Frame {
     Array(Vec<Frame>),
     SimpleString(Bytes),
+    // simple error, the message starts with an error code like `ERR` or `WRONGTYPE`
+    Error(Bytes),
     BulkString(Bytes),
     Integer(i64),
     Null,
+    // `*-1`, RESP2 has separate null values for strings and arrays
+    NullArray,
+    // RESP3 types, they are downgraded to the closest RESP2 type for RESP2 clients
+    Map(Vec<(Frame, Frame)>),
+    Set(Vec<Frame>),
+    Double(f64),
+    Boolean(bool),
+    BigNumber(Bytes),
+    VerbatimString { format: [u8; 3], data: Bytes },
+    BlobError(Bytes),
+    Push(Vec<Frame>),
+    // attribute is decoded as a separate frame, the reply it describes is the next frame
+    Attribute(Vec<(Frame, Frame)>),
 }
 
 impl Frame {
-    //// RESP serialized
-    pub fn as_resp_bytes(&self) -> Vec<u8> {
+    /// Appends RESP serialized frame to `buffer`, nested frames are written in place,
+    /// so a reply of any size is encoded without intermediate allocations.
+    pub fn encode(&self, buffer: &mut BytesMut, protocol: Protocol) {
         match self {
-            Frame::SimpleString(val) => encode_simple_string(val),
-            Frame::BulkString(val) => encode_bulk_string(val),
-            Frame::Null => encode_null(),
-            _ => unimplemented!(),
+            Frame::SimpleString(val) => encode_simple_string(val, buffer),
+            Frame::Error(val) => encode_error(val, buffer),
+            Frame::BulkString(val) => encode_bulk_string(val, buffer),
+            Frame::Integer(val) => encode_integer(*val, buffer),
+            Frame::Null => encode_null(buffer, protocol),
+            Frame::NullArray => encode_null_array(buffer, protocol),
+            Frame::Array(items) => encode_aggregate(FirstByte::Star, items, buffer, protocol),
+            Frame::Map(pairs) => encode_map(FirstByte::Percent, pairs, buffer, protocol),
+            Frame::Set(items) => encode_aggregate(FirstByte::Tilde, items, buffer, protocol),
+            Frame::Double(val) => encode_double(*val, buffer, protocol),
+            Frame::Boolean(val) => encode_boolean(*val, buffer, protocol),
+            Frame::BigNumber(val) => encode_big_number(val, buffer, protocol),
+            Frame::VerbatimString { format, data } => {
+                encode_verbatim_string(format, data, buffer, protocol)
+            }
+            Frame::BlobError(val) => encode_blob_error(val, buffer, protocol),
+            Frame::Push(items) => encode_aggregate(FirstByte::GreaterThan, items, buffer, protocol),
+            // RESP2 has no way to send out-of-band data, so the attribute is dropped
+            Frame::Attribute(pairs) => match protocol {
+                Protocol::Resp2 => {}
+                Protocol::Resp3 => encode_map(FirstByte::Pipe, pairs, buffer, protocol),
+            },
         }
     }
 
-    pub fn from_bytes(buffer: &[u8]) -> Result<Frame, FrameErrors> {
-        Frame::check(buffer)?;
-        let frame = match FirstByte::try_from(buffer[0])? {
-            FirstByte::Plus => Frame::SimpleString(decode_simple_string(&buffer[1..])?),
-            FirstByte::Colon => Frame::Integer(decode_integer(&buffer[1..])?),
-            FirstByte::Star => Frame::Array(decode_array(&buffer[1..])?),
-            FirstByte::Dollar => Frame::BulkString(decode_bulk_string(&buffer[1..])?),
+    /// Tries to parse one frame from the start of `buffer`.
+    ///
+    /// Returns `Ok(None)` when the buffer holds only a part of a frame, so the caller
+    /// should read more bytes and try again. Otherwise returns the frame together with
+    /// the number of bytes it occupied, anything after that belongs to the next frame.
+    #[cfg(test)]
+    pub fn parse(buffer: &[u8]) -> Result<Option<(Frame, usize)>, FrameErrors> {
+        let Some(length) = FrameScanner::default().scan(buffer)? else {
+            return Ok(None);
         };
+        let frame = Frame::decode(Bytes::copy_from_slice(&buffer[..length]))?;
+        Ok(Some((frame, length)))
+    }
 
-        Ok(frame)
+    /// Builds the whole frame a `FrameScanner` found. Strings are slices of `frame`,
+    /// so bulk payloads are not copied.
+    pub fn decode(frame: Bytes) -> Result<Frame, FrameErrors> {
+        decode(&frame, &frame, 0).map(|(frame, _)| frame)
     }
 
     pub fn as_string(&self) -> Result<String, FrameErrors> {
         match self {
-            Frame::SimpleString(val) | Frame::BulkString(val) => Ok(std::str::from_utf8(&val)
+            Frame::SimpleString(val) | Frame::BulkString(val) => Ok(std::str::from_utf8(val)
                 .map_err(|_| FrameErrors::StringInterpretationError)?
                 .to_string()),
             _ => Err(FrameErrors::StringInterpretationError),
         }
     }
-
-    fn check(buffer: &[u8]) -> Result<(), FrameErrors> {
-        let lenght = buffer.len();
-        // command must end with `\r\n`
-        if buffer[lenght - 2] != SpecialBytes::CR as u8
-            || buffer[lenght - 1] != SpecialBytes::LF as u8
-        {
-            return Err(FrameErrors::MissingCRLF);
-        }
-
-        Ok(())
-    }
 }
 
 impl Display for Frame {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let repr = match self {
             Frame::SimpleString(val) => format!("Simple string - {:?}", val),
+            Frame::Error(val) => format!("Error - {:?}", val),
             Frame::BulkString(val) => format!("Bulk string - {:?}", val),
             Frame::Integer(val) => format!("Integer - {}", val),
             Frame::Array(val) => format!("Array - [{:?}]", val),
             Frame::Null => "Null".to_string(),
+            Frame::NullArray => "Null array".to_string(),
+            Frame::Map(val) => format!("Map - [{:?}]", val),
+            Frame::Set(val) => format!("Set - [{:?}]", val),
+            Frame::Double(val) => format!("Double - {}", val),
+            Frame::Boolean(val) => format!("Boolean - {}", val),
+            Frame::BigNumber(val) => format!("Big number - {:?}", val),
+            Frame::VerbatimString { format, data } => {
+                format!(
+                    "Verbatim string - {:?} {:?}",
+                    Bytes::copy_from_slice(format),
+                    data
+                )
+            }
+            Frame::BlobError(val) => format!("Blob error - {:?}", val),
+            Frame::Push(val) => format!("Push - [{:?}]", val),
+            Frame::Attribute(val) => format!("Attribute - [{:?}]", val),
         };
 
         write!(f, "{}", repr)
     }
 }
 
-fn decode_simple_string(buffer: &[u8]) -> Result<Bytes, FrameErrors> {
-    let length = get_input_length(buffer)?;
-    Ok(Bytes::copy_from_slice(&buffer[0..length]))
+/// Formats a double the way it is sent to clients: the shortest representation that
+/// parses back to the same value, `inf`/`-inf`/`nan` for special values.
+pub fn format_double(val: f64) -> String {
+    match val {
+        val if val.is_nan() => "nan".to_string(),
+        val if val.is_infinite() && val > 0.0 => "inf".to_string(),
+        val if val.is_infinite() => "-inf".to_string(),
+        // avoid hundreds of zeros for very big and very small numbers
+        val if val != 0.0 && (val.abs() >= 1e17 || val.abs() < 1e-5) => format!("{:e}", val),
+        val => format!("{}", val),
+    }
 }
 
-fn decode_bulk_string(buffer: &[u8]) -> Result<Bytes, FrameErrors> {
-    let data_len = decode_integer(buffer)? as usize;
-    let start = 1 + get_position(buffer, SpecialBytes::LF as u8).ok_or(FrameErrors::MissingCRLF)?;
+/// Formats a double the way Redis stores `INCRBYFLOAT` results (`LD_STR_HUMAN`): fixed
+/// point with at most 17 decimals, trailing zeros removed. Redis adds in long double,
+/// an f64 only has its 15 significant digits right, so `0.1 + 0.2` is still `0.3`.
+pub fn format_human_double(val: f64) -> String {
+    let magnitude = match val {
+        0.0 => 0,
+        val => val.abs().log10().floor() as i32,
+    };
+    let decimals = (f64::DIGITS as i32 - 1 - magnitude).clamp(0, 17) as usize;
+    let mut formatted = format!("{:.*}", decimals, val);
+    if formatted.contains('.') {
+        formatted.truncate(formatted.trim_end_matches('0').trim_end_matches('.').len());
+    }
+    match formatted.as_str() {
+        "-0" => "0".to_string(),
+        _ => formatted,
+    }
+}
+
+/// Finds where the next frame ends without building it. What was scanned is kept
+/// across calls, so a big frame that arrives in many reads is scanned once and not
+/// from its first byte after every read.
+#[derive(Debug, Default)]
+pub struct FrameScanner {
+    // start of the next element to scan
+    position: usize,
+    // items still missing from every aggregate that is not complete, the innermost last
+    open: Vec<usize>,
+}
+
+impl FrameScanner {
+    /// Length of the frame at the start of `buffer`, `None` while a part of it is still
+    /// missing. `buffer` may only grow at its end until the frame is found.
+    pub fn scan(&mut self, buffer: &[u8]) -> Result<Option<usize>, FrameErrors> {
+        loop {
+            let scanned = scan_element(&buffer[self.position..], self.open.len());
+            let (length, items) = match scanned {
+                Ok(scanned) => scanned,
+                Err(FrameErrors::Incomplete) => return Ok(None),
+                Err(err) => {
+                    *self = FrameScanner::default();
+                    return Err(err);
+                }
+            };
+            self.position += length;
+            if items > 0 {
+                self.open.push(items);
+                continue;
+            }
+
+            // the element is complete, so is every aggregate it was the last item of
+            loop {
+                match self.open.last_mut() {
+                    None => {
+                        let length = self.position;
+                        *self = FrameScanner::default();
+                        return Ok(Some(length));
+                    }
+                    Some(missing) if *missing > 1 => {
+                        *missing -= 1;
+                        break;
+                    }
+                    Some(_) => {
+                        self.open.pop();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Length of the element at the start of `buffer` without its items, and how many
+/// items follow it. The checks are the ones of `decode`, so errors show up as soon as
+/// the bytes are there.
+fn scan_element(buffer: &[u8], depth: usize) -> Result<(usize, usize), FrameErrors> {
+    if depth > MAX_NESTING_DEPTH {
+        return Err(FrameErrors::NestingTooDeep);
+    }
+
+    let first_byte = match FirstByte::try_from(*buffer.first().ok_or(FrameErrors::Incomplete)?) {
+        Ok(first_byte) => first_byte,
+        Err(_) if depth == 0 => return Ok((decode_inline(buffer)?.1, 0)),
+        Err(err) => return Err(err),
+    };
+    let buffer = &buffer[1..];
+    let (length, items) = match first_byte {
+        FirstByte::Plus | FirstByte::Minus => (decode_line(buffer)?.1, 0),
+        FirstByte::Colon => (decode_integer(buffer)?.1, 0),
+        FirstByte::Dollar | FirstByte::Bang => (decode_bulk_string(buffer)?.1, 0),
+        FirstByte::Underscore => (decode_null(buffer)?, 0),
+        FirstByte::Comma => (decode_double(buffer)?.1, 0),
+        FirstByte::Hash => (decode_boolean(buffer)?.1, 0),
+        FirstByte::LeftParen => (decode_big_number(buffer)?.1, 0),
+        FirstByte::Equal => (decode_verbatim_string(buffer)?.1, 0),
+        FirstByte::Star => match decode_integer(buffer)? {
+            // `*-1\r\n` is a null array
+            (-1, length) => (length, 0),
+            (items_count, length) => (length, checked_items_count(items_count)?),
+        },
+        FirstByte::Tilde | FirstByte::GreaterThan => {
+            let (items_count, length) = decode_integer(buffer)?;
+            (length, checked_items_count(items_count)?)
+        }
+        FirstByte::Percent | FirstByte::Pipe => {
+            let (pairs_count, length) = decode_integer(buffer)?;
+            let items_count = pairs_count
+                .checked_mul(2)
+                .ok_or(FrameErrors::IncorrectArrayLength)?;
+            (length, checked_items_count(items_count)?)
+        }
+    };
+
+    // +1 for the first byte
+    Ok((length + 1, items))
+}
+
+fn checked_items_count(items_count: i64) -> Result<usize, FrameErrors> {
+    usize::try_from(items_count).map_err(|_| FrameErrors::IncorrectArrayLength)
+}
+
+// Every decoder gets the buffer right after the first byte and returns the decoded value
+// together with the number of bytes it consumed (trailing CRLF included).
+// `FrameErrors::Incomplete` means that the frame is not fully in the buffer yet.
+// `source` is the whole frame `buffer` is a part of, strings are sliced from it.
+
+fn decode(buffer: &[u8], source: &Bytes, depth: usize) -> Result<(Frame, usize), FrameErrors> {
+    // every nesting level is a recursive call, don't let a client blow up the stack
+    if depth > MAX_NESTING_DEPTH {
+        return Err(FrameErrors::NestingTooDeep);
+    }
+
+    let first_byte = match FirstByte::try_from(*buffer.first().ok_or(FrameErrors::Incomplete)?) {
+        Ok(first_byte) => first_byte,
+        // anything that is not a RESP type at the top level is an inline command,
+        // what people type in telnet or netcat
+        Err(_) if depth == 0 => return decode_inline(buffer),
+        Err(err) => return Err(err),
+    };
+    let buffer = &buffer[1..];
+    let (frame, length) = match first_byte {
+        FirstByte::Plus => {
+            let (val, length) = decode_line(buffer)?;
+            (Frame::SimpleString(source.slice_ref(val)), length)
+        }
+        FirstByte::Minus => {
+            let (val, length) = decode_line(buffer)?;
+            (Frame::Error(source.slice_ref(val)), length)
+        }
+        FirstByte::Colon => {
+            let (val, length) = decode_integer(buffer)?;
+            (Frame::Integer(val), length)
+        }
+        FirstByte::Dollar => {
+            let (val, length) = decode_bulk_string(buffer)?;
+            let val = val.map(|val| source.slice_ref(val));
+            (val.map_or(Frame::Null, Frame::BulkString), length)
+        }
+        FirstByte::Star => {
+            let (val, length) = decode_array(buffer, source, depth)?;
+            (val.map_or(Frame::NullArray, Frame::Array), length)
+        }
+        FirstByte::Underscore => (Frame::Null, decode_null(buffer)?),
+        FirstByte::Comma => {
+            let (val, length) = decode_double(buffer)?;
+            (Frame::Double(val), length)
+        }
+        FirstByte::Hash => {
+            let (val, length) = decode_boolean(buffer)?;
+            (Frame::Boolean(val), length)
+        }
+        FirstByte::LeftParen => {
+            let (val, length) = decode_big_number(buffer)?;
+            (Frame::BigNumber(source.slice_ref(val)), length)
+        }
+        FirstByte::Bang => {
+            let (val, length) = decode_bulk_string(buffer)?;
+            let val = val.map(|val| source.slice_ref(val));
+            (Frame::BlobError(val.unwrap_or_default()), length)
+        }
+        FirstByte::Equal => {
+            let ((format, data), length) = decode_verbatim_string(buffer)?;
+            let data = source.slice_ref(data);
+            (Frame::VerbatimString { format, data }, length)
+        }
+        FirstByte::Percent => {
+            let (pairs, length) = decode_map(buffer, source, depth)?;
+            (Frame::Map(pairs), length)
+        }
+        FirstByte::Tilde => {
+            let (items, length) = decode_aggregate(buffer, source, depth)?;
+            (Frame::Set(items), length)
+        }
+        FirstByte::GreaterThan => {
+            let (items, length) = decode_aggregate(buffer, source, depth)?;
+            (Frame::Push(items), length)
+        }
+        FirstByte::Pipe => {
+            let (pairs, length) = decode_map(buffer, source, depth)?;
+            (Frame::Attribute(pairs), length)
+        }
+    };
 
-    if buffer[start..start + data_len]
-        .iter()
-        .any(|&c| is_special_byte(&c))
-    {
+    // +1 for the first byte
+    Ok((frame, length + 1))
+}
+
+fn decode_bulk_string(buffer: &[u8]) -> Result<(Option<&[u8]>, usize), FrameErrors> {
+    let (data_len, start) = decode_integer(buffer)?;
+    // `$-1\r\n` is a null bulk string
+    if data_len == -1 {
+        return Ok((None, start));
+    }
+    if !(0..=MAX_BULK_STRING_LENGTH).contains(&data_len) {
         return Err(FrameErrors::IncorrectBulkStringLength);
     }
 
-    if buffer[start + data_len] != SpecialBytes::CR as u8 {
+    let end = start + data_len as usize;
+    // data has to be followed by CRLF, if the bytes we already have there are something
+    // else - the declared length is wrong, no matter how many bytes are still to come
+    let terminator = &buffer[end.min(buffer.len())..buffer.len().min(end + 2)];
+    if !CRLF.starts_with(terminator) {
         return Err(FrameErrors::IncorrectBulkStringLength);
     }
+    if terminator.len() < CRLF.len() {
+        return Err(FrameErrors::Incomplete);
+    }
 
-    Ok(Bytes::copy_from_slice(&buffer[start..start + data_len]))
+    Ok((Some(&buffer[start..end]), end + 2))
 }
 
-fn decode_integer(buffer: &[u8]) -> Result<i64, FrameErrors> {
-    let offset: usize;
-    let sign_multiplier: i64;
+fn decode_integer(buffer: &[u8]) -> Result<(i64, usize), FrameErrors> {
+    let (line, length) = decode_line(buffer)?;
 
     // +/- before integer is optional in RESP
-    match buffer[0] {
-        b'+' => {
-            offset = 1;
-            sign_multiplier = 1
-        }
-        b'-' => {
-            offset = 1;
-            sign_multiplier = -1
+    let (digits, sign_multiplier) = match line.first() {
+        Some(b'+') => (&line[1..], 1),
+        Some(b'-') => (&line[1..], -1),
+        _ => (line, 1),
+    };
+
+    if digits.is_empty() {
+        return Err(FrameErrors::IncorrectInteger);
+    }
+
+    let mut number: i64 = 0;
+    for &digit in digits {
+        if !digit.is_ascii_digit() {
+            return Err(FrameErrors::IncorrectInteger);
         }
-        _ => {
-            offset = 0;
-            sign_multiplier = 1
+        number = number
+            .checked_mul(10)
+            .and_then(|number| number.checked_add(sign_multiplier * i64::from(digit - b'0')))
+            .ok_or(FrameErrors::IncorrectInteger)?;
+    }
+
+    Ok((number, length))
+}
+
+fn decode_array(
+    buffer: &[u8],
+    source: &Bytes,
+    depth: usize,
+) -> Result<(Option<Vec<Frame>>, usize), FrameErrors> {
+    let (items_count, length) = decode_integer(buffer)?;
+    // `*-1\r\n` is a null array
+    if items_count == -1 {
+        return Ok((None, length));
+    }
+
+    let (items, items_length) = decode_items(&buffer[length..], source, items_count, depth)?;
+    Ok((Some(items), length + items_length))
+}
+
+fn decode_null(buffer: &[u8]) -> Result<usize, FrameErrors> {
+    match decode_line(buffer)? {
+        (b"", length) => Ok(length),
+        _ => Err(FrameErrors::IncorrectNull),
+    }
+}
+
+fn decode_double(buffer: &[u8]) -> Result<(f64, usize), FrameErrors> {
+    let (line, length) = decode_line(buffer)?;
+    let val = std::str::from_utf8(line)
+        .ok()
+        .and_then(|line| line.parse::<f64>().ok())
+        .ok_or(FrameErrors::IncorrectDouble)?;
+
+    Ok((val, length))
+}
+
+fn decode_boolean(buffer: &[u8]) -> Result<(bool, usize), FrameErrors> {
+    match decode_line(buffer)? {
+        (b"t", length) => Ok((true, length)),
+        (b"f", length) => Ok((false, length)),
+        _ => Err(FrameErrors::IncorrectBoolean),
+    }
+}
+
+fn decode_big_number(buffer: &[u8]) -> Result<(&[u8], usize), FrameErrors> {
+    let (line, length) = decode_line(buffer)?;
+    let digits = match line.first() {
+        Some(b'+') | Some(b'-') => &line[1..],
+        _ => line,
+    };
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(FrameErrors::IncorrectBigNumber);
+    }
+
+    Ok((line, length))
+}
+
+// format and data of a verbatim string
+type Verbatim<'a> = ([u8; 3], &'a [u8]);
+
+fn decode_verbatim_string(buffer: &[u8]) -> Result<(Verbatim<'_>, usize), FrameErrors> {
+    let (data, length) = decode_bulk_string(buffer)?;
+    // payload is `<3 bytes of format>:<data>`
+    match data {
+        Some(data) if data.len() >= 4 && data[3] == b':' => {
+            let format = [data[0], data[1], data[2]];
+            Ok(((format, &data[4..]), length))
         }
+        _ => Err(FrameErrors::IncorrectVerbatimString),
     }
+}
 
-    let length = get_input_length(buffer)?;
-    let mut number: i64 = 0;
+fn decode_aggregate(
+    buffer: &[u8],
+    source: &Bytes,
+    depth: usize,
+) -> Result<(Vec<Frame>, usize), FrameErrors> {
+    let (items_count, length) = decode_integer(buffer)?;
+    let (items, items_length) = decode_items(&buffer[length..], source, items_count, depth)?;
+    Ok((items, length + items_length))
+}
 
-    for i in offset..length {
-        number = number * 10 + i64::from(buffer[i] - b'0')
+fn decode_map(
+    buffer: &[u8],
+    source: &Bytes,
+    depth: usize,
+) -> Result<(Vec<(Frame, Frame)>, usize), FrameErrors> {
+    let (pairs_count, length) = decode_integer(buffer)?;
+    let items_count = pairs_count
+        .checked_mul(2)
+        .ok_or(FrameErrors::IncorrectArrayLength)?;
+    let (items, items_length) = decode_items(&buffer[length..], source, items_count, depth)?;
+
+    let mut items = items.into_iter();
+    let mut pairs = Vec::with_capacity(items.len() / 2);
+    while let (Some(key), Some(value)) = (items.next(), items.next()) {
+        pairs.push((key, value));
     }
 
-    return Ok(number * sign_multiplier);
+    Ok((pairs, length + items_length))
 }
 
-fn decode_array(buffer: &[u8]) -> Result<Vec<Frame>, FrameErrors> {
-    let items_count = decode_integer(buffer)?;
-    let mut items: Vec<Frame> = Vec::new();
+/// Decodes `items_count` consecutive frames of any type, used by all aggregate types.
+fn decode_items(
+    buffer: &[u8],
+    source: &Bytes,
+    items_count: i64,
+    depth: usize,
+) -> Result<(Vec<Frame>, usize), FrameErrors> {
+    if items_count < 0 {
+        return Err(FrameErrors::IncorrectArrayLength);
+    }
 
-    // start reading array items by skipping `*<number-of-elements>\r\n` part
-    let mut item_start = get_input_length(buffer)? + 2;
+    // don't trust the declared count when reserving memory, the items are not there yet
+    let mut items: Vec<Frame> = Vec::with_capacity((items_count as usize).min(1024));
+    let mut length = 0;
     for _ in 0..items_count {
-        let slice = &buffer[item_start..];
+        // items can be of any type, nested aggregates included
+        let (item, item_length) = decode(&buffer[length..], source, depth + 1)?;
+        length += item_length;
+        items.push(item);
+    }
 
-        let first_lf =
-            get_position(slice, SpecialBytes::LF as u8).ok_or(FrameErrors::WrongArrayItemFormat)?;
+    Ok((items, length))
+}
 
-        let second_lf = get_position(&slice[first_lf + 1..], SpecialBytes::LF as u8)
-            .ok_or(FrameErrors::WrongArrayItemFormat)?;
+/// Inline command is a single line of space separated arguments, turned into the same
+/// array of bulk strings that a RESP client would send. Unlike the rest of the frames
+/// it doesn't have a first byte, and a bare LF is enough to end it.
+fn decode_inline(buffer: &[u8]) -> Result<(Frame, usize), FrameErrors> {
+    let lf = match get_position(buffer, SpecialBytes::LF as u8) {
+        Some(lf) => lf,
+        None if buffer.len() > MAX_INLINE_LENGTH => return Err(FrameErrors::InlineTooBig),
+        None => return Err(FrameErrors::Incomplete),
+    };
+    let line = match buffer[..lf].last() {
+        Some(&c) if c == SpecialBytes::CR as u8 => &buffer[..lf - 1],
+        _ => &buffer[..lf],
+    };
+
+    let args = split_inline_args(line)?
+        .into_iter()
+        .map(Frame::BulkString)
+        .collect();
+
+    Ok((Frame::Array(args), lf + 1))
+}
 
-        // +1 for each LF
-        let item_length = first_lf + 1 + second_lf + 1;
+/// Splits an inline command line into arguments the same way `redis-cli` and Redis do:
+/// arguments are separated by whitespace, can be wrapped in double quotes
+/// (with `\n`, `\x41`-like escapes) or in single quotes (only `\'` is escaped).
+fn split_inline_args(line: &[u8]) -> Result<Vec<Bytes>, FrameErrors> {
+    let mut args = Vec::new();
+    let mut i = 0;
 
-        match Frame::from_bytes(&slice[..item_length])? {
-            Frame::BulkString(val) => {
-                item_start += item_length;
-                items.push(Frame::BulkString(val));
-            }
-            _ => {
-                return Err(FrameErrors::WrongArrayItemFormat);
+    loop {
+        while i < line.len() && line[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i == line.len() {
+            return Ok(args);
+        }
+
+        let mut arg = Vec::new();
+        let mut in_double_quotes = false;
+        let mut in_single_quotes = false;
+        loop {
+            let c = line.get(i).copied();
+            if in_double_quotes {
+                match c {
+                    None => return Err(FrameErrors::UnbalancedQuotes),
+                    Some(b'\\')
+                        if i + 3 < line.len()
+                            && line[i + 1] == b'x'
+                            && line[i + 2].is_ascii_hexdigit()
+                            && line[i + 3].is_ascii_hexdigit() =>
+                    {
+                        arg.push(hex_value(line[i + 2]) << 4 | hex_value(line[i + 3]));
+                        i += 3;
+                    }
+                    Some(b'\\') if i + 1 < line.len() => {
+                        i += 1;
+                        arg.push(match line[i] {
+                            b'n' => b'\n',
+                            b'r' => b'\r',
+                            b't' => b'\t',
+                            b'b' => 0x08,
+                            b'a' => 0x07,
+                            other => other,
+                        });
+                    }
+                    Some(b'"') => {
+                        // closing quote must be followed by a space or nothing
+                        if line.get(i + 1).is_some_and(|c| !c.is_ascii_whitespace()) {
+                            return Err(FrameErrors::UnbalancedQuotes);
+                        }
+                        i += 1;
+                        break;
+                    }
+                    Some(c) => arg.push(c),
+                }
+            } else if in_single_quotes {
+                match c {
+                    None => return Err(FrameErrors::UnbalancedQuotes),
+                    Some(b'\\') if line.get(i + 1) == Some(&b'\'') => {
+                        i += 1;
+                        arg.push(b'\'');
+                    }
+                    Some(b'\'') => {
+                        if line.get(i + 1).is_some_and(|c| !c.is_ascii_whitespace()) {
+                            return Err(FrameErrors::UnbalancedQuotes);
+                        }
+                        i += 1;
+                        break;
+                    }
+                    Some(c) => arg.push(c),
+                }
+            } else {
+                match c {
+                    None => break,
+                    Some(c) if c.is_ascii_whitespace() => break,
+                    Some(b'"') => in_double_quotes = true,
+                    Some(b'\'') => in_single_quotes = true,
+                    Some(c) => arg.push(c),
+                }
             }
+            i += 1;
         }
+
+        args.push(Bytes::from(arg));
     }
+}
 
-    Ok(items)
+fn hex_value(c: u8) -> u8 {
+    (c as char).to_digit(16).unwrap_or_default() as u8
 }
 
-fn encode_simple_string(val: &Bytes) -> Vec<u8> {
-    let mut buffer = Vec::with_capacity(3 + val.len());
+/// Returns the bytes before the first CRLF and the number of bytes consumed with CRLF.
+fn decode_line(buffer: &[u8]) -> Result<(&[u8], usize), FrameErrors> {
+    let lf = get_position(buffer, SpecialBytes::LF as u8).ok_or(FrameErrors::Incomplete)?;
+    if lf == 0 || buffer[lf - 1] != SpecialBytes::CR as u8 {
+        return Err(FrameErrors::MissingCRLF);
+    }
 
-    buffer.push(FirstByte::Plus as u8);
-    buffer.extend_from_slice(val);
-    buffer.push(SpecialBytes::CR as u8);
-    buffer.push(SpecialBytes::LF as u8);
+    Ok((&buffer[..lf - 1], lf + 1))
+}
 
-    buffer
+fn encode_simple_string(val: &Bytes, buffer: &mut BytesMut) {
+    encode_line(FirstByte::Plus, val, buffer);
 }
 
-fn encode_bulk_string(val: &Bytes) -> Vec<u8> {
-    let len_str = val.len().to_string();
-    let mut buffer = Vec::with_capacity(5 + len_str.len() + val.len());
+fn encode_error(val: &Bytes, buffer: &mut BytesMut) {
+    encode_line(FirstByte::Minus, val, buffer);
+}
 
-    buffer.push(FirstByte::Dollar as u8);
-    buffer.extend_from_slice(len_str.as_bytes());
-    buffer.push(SpecialBytes::CR as u8);
-    buffer.push(SpecialBytes::LF as u8);
-    buffer.extend_from_slice(val);
-    buffer.push(SpecialBytes::CR as u8);
-    buffer.push(SpecialBytes::LF as u8);
+fn encode_bulk_string(val: &Bytes, buffer: &mut BytesMut) {
+    encode_length_prefixed(FirstByte::Dollar, &[val], buffer);
+}
 
-    buffer
+fn encode_integer(val: i64, buffer: &mut BytesMut) {
+    encode_number_line(FirstByte::Colon, val, buffer);
 }
 
-fn encode_null() -> Vec<u8> {
-    b"$-1\r\n".to_vec()
+fn encode_null(buffer: &mut BytesMut, protocol: Protocol) {
+    match protocol {
+        Protocol::Resp2 => buffer.extend_from_slice(b"$-1\r\n"),
+        Protocol::Resp3 => buffer.extend_from_slice(b"_\r\n"),
+    }
 }
 
-fn get_input_length(buffer: &[u8]) -> Result<usize, FrameErrors> {
-    // number of bytes before first `\r`
-    get_position(buffer, SpecialBytes::CR as u8).ok_or(FrameErrors::MissingCRLF)
+fn encode_null_array(buffer: &mut BytesMut, protocol: Protocol) {
+    match protocol {
+        Protocol::Resp2 => buffer.extend_from_slice(b"*-1\r\n"),
+        Protocol::Resp3 => buffer.extend_from_slice(b"_\r\n"),
+    }
 }
 
-fn get_position(slice: &[u8], char_we_look_for: u8) -> Option<usize> {
-    slice.iter().position(|&c| c == char_we_look_for)
+fn encode_double(val: f64, buffer: &mut BytesMut, protocol: Protocol) {
+    let repr = format_double(val);
+    match protocol {
+        Protocol::Resp2 => encode_length_prefixed(FirstByte::Dollar, &[repr.as_bytes()], buffer),
+        Protocol::Resp3 => encode_line(FirstByte::Comma, repr.as_bytes(), buffer),
+    }
 }
 
-fn is_special_byte(b: &u8) -> bool {
-    return *b == SpecialBytes::CR as u8 || *b == SpecialBytes::LF as u8;
+fn encode_boolean(val: bool, buffer: &mut BytesMut, protocol: Protocol) {
+    match protocol {
+        Protocol::Resp2 => encode_integer(i64::from(val), buffer),
+        Protocol::Resp3 => encode_line(FirstByte::Hash, if val { b"t" } else { b"f" }, buffer),
+    }
+}
+
+fn encode_big_number(val: &Bytes, buffer: &mut BytesMut, protocol: Protocol) {
+    match protocol {
+        Protocol::Resp2 => encode_bulk_string(val, buffer),
+        Protocol::Resp3 => encode_line(FirstByte::LeftParen, val, buffer),
+    }
+}
+
+fn encode_verbatim_string(
+    format: &[u8; 3],
+    data: &Bytes,
+    buffer: &mut BytesMut,
+    protocol: Protocol,
+) {
+    match protocol {
+        Protocol::Resp2 => encode_bulk_string(data, buffer),
+        Protocol::Resp3 => {
+            encode_length_prefixed(FirstByte::Equal, &[format, b":", data], buffer);
+        }
+    }
+}
+
+fn encode_blob_error(val: &Bytes, buffer: &mut BytesMut, protocol: Protocol) {
+    match protocol {
+        // RESP2 only has single line errors
+        Protocol::Resp2 => {
+            buffer.put_u8(FirstByte::Minus as u8);
+            buffer.extend(val.iter().map(|&c| match c {
+                b'\r' | b'\n' => b' ',
+                c => c,
+            }));
+            buffer.extend_from_slice(CRLF);
+        }
+        Protocol::Resp3 => encode_length_prefixed(FirstByte::Bang, &[val], buffer),
+    }
+}
+
+fn encode_aggregate(
+    first_byte: FirstByte,
+    items: &[Frame],
+    buffer: &mut BytesMut,
+    protocol: Protocol,
+) {
+    // all aggregates are plain arrays in RESP2
+    let first_byte = match protocol {
+        Protocol::Resp2 => FirstByte::Star,
+        Protocol::Resp3 => first_byte,
+    };
+    encode_number_line(first_byte, items.len() as i64, buffer);
+    for item in items {
+        item.encode(buffer, protocol);
+    }
+}
+
+fn encode_map(
+    first_byte: FirstByte,
+    pairs: &[(Frame, Frame)],
+    buffer: &mut BytesMut,
+    protocol: Protocol,
+) {
+    // RESP2 gets a flat array of key, value, key, value...
+    let (first_byte, length) = match protocol {
+        Protocol::Resp2 => (FirstByte::Star, pairs.len() * 2),
+        Protocol::Resp3 => (first_byte, pairs.len()),
+    };
+    encode_number_line(first_byte, length as i64, buffer);
+    for (key, value) in pairs {
+        key.encode(buffer, protocol);
+        value.encode(buffer, protocol);
+    }
+}
+
+/// `<first byte><line>\r\n`
+fn encode_line(first_byte: FirstByte, line: &[u8], buffer: &mut BytesMut) {
+    buffer.reserve(3 + line.len());
+    buffer.put_u8(first_byte as u8);
+    buffer.extend_from_slice(line);
+    buffer.extend_from_slice(CRLF);
+}
+
+/// `<first byte><number>\r\n`, number is formatted right into the buffer
+fn encode_number_line(first_byte: FirstByte, number: i64, buffer: &mut BytesMut) {
+    buffer.put_u8(first_byte as u8);
+    // writing into BytesMut can't fail
+    let _ = write!(buffer, "{}", number);
+    buffer.extend_from_slice(CRLF);
+}
+
+/// `<first byte><length>\r\n<data>\r\n`, data is passed in parts to avoid copying them first
+fn encode_length_prefixed(first_byte: FirstByte, parts: &[&[u8]], buffer: &mut BytesMut) {
+    let data_len: usize = parts.iter().map(|part| part.len()).sum();
+
+    buffer.reserve(24 + data_len);
+    encode_number_line(first_byte, data_len as i64, buffer);
+    for part in parts {
+        buffer.extend_from_slice(part);
+    }
+    buffer.extend_from_slice(CRLF);
+}
+
+fn get_position(slice: &[u8], char_we_look_for: u8) -> Option<usize> {
+    slice.iter().position(|&c| c == char_we_look_for)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bytes::BytesMut;
+
+    fn encoded(frame: &Frame, protocol: Protocol) -> Vec<u8> {
+        let mut buffer = BytesMut::new();
+        frame.encode(&mut buffer, protocol);
+        buffer.to_vec()
+    }
+
+    // parses a buffer that is expected to hold exactly one frame
+    fn parse_single(buffer: &[u8]) -> Result<Frame, FrameErrors> {
+        let (frame, length) = Frame::parse(buffer)?.expect("incomplete frame");
+        assert_eq!(length, buffer.len());
+        Ok(frame)
+    }
 
     #[test]
     fn test_parse_simple_string() {
         let mut buffer = BytesMut::with_capacity(64);
         buffer.extend_from_slice(b"+OK\r\n");
 
-        let frame = Frame::from_bytes(&buffer).unwrap();
+        let frame = parse_single(&buffer).unwrap();
         let expected = Frame::SimpleString(Bytes::from_static(b"OK"));
 
         assert_eq!(expected, frame);
     }
 
+    #[test]
+    fn test_parse_error() {
+        let frame = parse_single(b"-ERR unknown command 'foo'\r\n").unwrap();
+        let expected = Frame::Error(Bytes::from_static(b"ERR unknown command 'foo'"));
+        assert_eq!(expected, frame);
+    }
+
     #[test]
     fn test_parse_integer() {
         let mut buffer = BytesMut::with_capacity(64);
 
         buffer.extend_from_slice(b":22\r\n");
-        let frame = Frame::from_bytes(&buffer).unwrap();
+        let frame = parse_single(&buffer).unwrap();
         let expected = Frame::Integer(22);
         assert_eq!(expected, frame);
 
         buffer.clear();
         buffer.extend_from_slice(b":+423232341231233\r\n");
-        let frame = Frame::from_bytes(&buffer).unwrap();
+        let frame = parse_single(&buffer).unwrap();
         let expected = Frame::Integer(423232341231233);
         assert_eq!(expected, frame);
 
         buffer.clear();
         buffer.extend_from_slice(b":-22\r\n");
-        let frame = Frame::from_bytes(&buffer).unwrap();
+        let frame = parse_single(&buffer).unwrap();
         let expected = Frame::Integer(-22);
         assert_eq!(expected, frame);
     }
@@ -271,26 +908,40 @@ mod tests {
         let mut buffer = BytesMut::with_capacity(64);
         buffer.extend_from_slice(b"$5\r\nhello\r\n");
 
-        let frame = Frame::from_bytes(&buffer).unwrap();
+        let frame = parse_single(&buffer).unwrap();
         let expected = Frame::BulkString(Bytes::from_static(b"hello"));
 
         assert_eq!(expected, frame);
     }
 
+    #[test]
+    fn test_parse_binary_bulk_string() {
+        // payload is read by its declared length only, CR/LF and non UTF-8 bytes are data
+        let buffer = b"$8\r\n\r\n\x00\xff\r\n\r\r\r\n";
+        let expected = Frame::BulkString(Bytes::from_static(b"\r\n\x00\xff\r\n\r\r"));
+        assert_eq!(parse_single(buffer).unwrap(), expected);
+
+        let buffer = b"$0\r\n\r\n";
+        assert_eq!(
+            parse_single(buffer).unwrap(),
+            Frame::BulkString(Bytes::new())
+        );
+    }
+
     #[test]
     fn test_parse_bulk_string_with_incorrect_length() {
         let mut buffer = BytesMut::with_capacity(64);
 
         buffer.extend_from_slice(b"$4\r\nhello\r\n");
         assert_eq!(
-            Frame::from_bytes(&buffer).unwrap_err(),
+            parse_single(&buffer).unwrap_err(),
             FrameErrors::IncorrectBulkStringLength
         );
 
         buffer.clear();
         buffer.extend_from_slice(b"$6\r\nhello\r\n");
         assert_eq!(
-            Frame::from_bytes(&buffer).unwrap_err(),
+            parse_single(&buffer).unwrap_err(),
             FrameErrors::IncorrectBulkStringLength
         );
     }
@@ -299,57 +950,503 @@ mod tests {
     fn test_parse_array() {
         let mut buffer = BytesMut::with_capacity(64);
         buffer.extend_from_slice(b"*2\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
-        let frame = Frame::from_bytes(&buffer).unwrap();
+        let frame = parse_single(&buffer).unwrap();
         let expected = Frame::Array(vec![
             Frame::BulkString(Bytes::from_static(b"hello")),
             Frame::BulkString(Bytes::from_static(b"world")),
         ]);
 
         assert_eq!(expected, frame);
+
+        assert_eq!(parse_single(b"*-1\r\n").unwrap(), Frame::NullArray);
+        assert_eq!(parse_single(b"*0\r\n").unwrap(), Frame::Array(vec![]));
     }
 
     #[test]
     fn test_parse_incorrect_command() {
         let mut buffer = BytesMut::with_capacity(64);
 
-        buffer.extend_from_slice(b":-");
+        buffer.extend_from_slice(b"+OK\n");
+        assert_eq!(Frame::parse(&buffer).unwrap_err(), FrameErrors::MissingCRLF);
+
+        buffer.clear();
+        buffer.extend_from_slice(b":12a\r\n");
         assert_eq!(
-            Frame::from_bytes(&buffer).unwrap_err(),
-            FrameErrors::MissingCRLF
+            Frame::parse(&buffer).unwrap_err(),
+            FrameErrors::IncorrectInteger
         );
 
         buffer.clear();
-        buffer.extend_from_slice(b"+OK\r");
+        buffer.extend_from_slice(b":9223372036854775808\r\n"); // i64::MAX + 1
         assert_eq!(
-            Frame::from_bytes(&buffer).unwrap_err(),
-            FrameErrors::MissingCRLF
+            Frame::parse(&buffer).unwrap_err(),
+            FrameErrors::IncorrectInteger
         );
 
         buffer.clear();
         buffer.extend_from_slice(b"*2\r\n$5\r\nhell\r\n$5\r\nworld\r\n"); // wrong first item len
         assert_eq!(
-            Frame::from_bytes(&buffer).unwrap_err(),
+            parse_single(&buffer).unwrap_err(),
             FrameErrors::IncorrectBulkStringLength
         );
     }
 
+    #[test]
+    fn test_parse_incomplete_frame() {
+        let full = b"*2\r\n$5\r\nhello\r\n$5\r\nworld\r\n";
+        // every strict prefix of a valid frame needs more bytes
+        for end in 0..full.len() {
+            assert_eq!(Frame::parse(&full[..end]).unwrap(), None);
+        }
+
+        assert_eq!(Frame::parse(b":-").unwrap(), None);
+        assert_eq!(Frame::parse(b"+OK\r").unwrap(), None);
+        assert_eq!(Frame::parse(b"$5\r\nhel").unwrap(), None);
+    }
+
+    #[test]
+    fn test_scan_across_reads() {
+        let full = b"*3\r\n$3\r\nSET\r\n*1\r\n%1\r\n+a\r\n:1\r\n$5\r\nvalue\r\n+PING\r\n";
+        let frame_len = full.len() - b"+PING\r\n".len();
+        let mut scanner = FrameScanner::default();
+        for end in 0..frame_len {
+            assert_eq!(scanner.scan(&full[..end]), Ok(None));
+        }
+        // what was scanned of the previous reads is not scanned again
+        assert_eq!(scanner.position, frame_len - b"$5\r\nvalue\r\n".len());
+        assert_eq!(scanner.scan(full), Ok(Some(frame_len)));
+        assert_eq!(scanner.scan(&full[frame_len..]), Ok(Some(7)));
+    }
+
+    #[test]
+    fn test_decode_slices_bulk_strings() {
+        let source = Bytes::from_static(b"*1\r\n$5\r\nhello\r\n");
+        let Frame::Array(items) = Frame::decode(source.clone()).unwrap() else {
+            panic!("not an array");
+        };
+        let Frame::BulkString(hello) = &items[0] else {
+            panic!("not a bulk string");
+        };
+        assert_eq!(hello, "hello");
+        assert_eq!(hello.as_ptr(), source[8..].as_ptr());
+    }
+
+    #[test]
+    fn test_parse_pipelined_frames() {
+        let buffer = b"+OK\r\n:7\r\n$3\r\nhey\r\n$-1\r\n*1\r\n$2\r\nab\r\n+PAR";
+        let mut frames = Vec::new();
+        let mut start = 0;
+        while let Some((frame, length)) = Frame::parse(&buffer[start..]).unwrap() {
+            frames.push(frame);
+            start += length;
+        }
+
+        assert_eq!(
+            frames,
+            vec![
+                Frame::SimpleString(Bytes::from_static(b"OK")),
+                Frame::Integer(7),
+                Frame::BulkString(Bytes::from_static(b"hey")),
+                Frame::Null,
+                Frame::Array(vec![Frame::BulkString(Bytes::from_static(b"ab"))]),
+            ]
+        );
+        // partial tail is left for the next read
+        assert_eq!(&buffer[start..], b"+PAR");
+    }
+
     #[test]
     fn test_encode_simple_string() {
         let input = Bytes::from_static(b"Simple");
         let expected = b"+Simple\r\n";
-        assert_eq!(encode_simple_string(&input), expected);
+        let mut buffer = BytesMut::new();
+        encode_simple_string(&input, &mut buffer);
+        assert_eq!(&buffer[..], expected);
+    }
+
+    #[test]
+    fn test_encode_error() {
+        let input = Bytes::from_static(b"WRONGTYPE Operation against a key");
+        let expected = b"-WRONGTYPE Operation against a key\r\n";
+        let mut buffer = BytesMut::new();
+        encode_error(&input, &mut buffer);
+        assert_eq!(&buffer[..], expected);
     }
 
     #[test]
     fn test_encode_null() {
+        let expected = b"$-1\r\n";
+        assert_eq!(encoded(&Frame::Null, Protocol::Resp2), &expected[..]);
+
         let expected = b"_\r\n";
-        assert_eq!(encode_null(), expected);
+        assert_eq!(encoded(&Frame::Null, Protocol::Resp3), &expected[..]);
+    }
+
+    #[test]
+    fn test_encode_integer() {
+        assert_eq!(encoded(&Frame::Integer(0), Protocol::Resp2), &b":0\r\n"[..]);
+        assert_eq!(
+            encoded(&Frame::Integer(i64::MIN), Protocol::Resp2),
+            &b":-9223372036854775808\r\n"[..]
+        );
+    }
+
+    #[test]
+    fn test_encode_array() {
+        let frame = Frame::Array(vec![
+            Frame::BulkString(Bytes::from_static(b"a")),
+            Frame::Integer(1),
+            Frame::Null,
+            Frame::Array(vec![Frame::SimpleString(Bytes::from_static(b"OK"))]),
+            Frame::Array(vec![]),
+            Frame::NullArray,
+        ]);
+        let expected = b"*6\r\n$1\r\na\r\n:1\r\n$-1\r\n*1\r\n+OK\r\n*0\r\n*-1\r\n";
+        assert_eq!(encoded(&frame, Protocol::Resp2), &expected[..]);
+
+        let expected = b"*6\r\n$1\r\na\r\n:1\r\n_\r\n*1\r\n+OK\r\n*0\r\n_\r\n";
+        assert_eq!(encoded(&frame, Protocol::Resp3), &expected[..]);
+    }
+
+    #[test]
+    fn test_encode_appends_to_buffer() {
+        let mut buffer = BytesMut::from(&b"+PONG\r\n"[..]);
+        Frame::Integer(5).encode(&mut buffer, Protocol::Resp2);
+        assert_eq!(&buffer[..], b"+PONG\r\n:5\r\n");
     }
 
     #[test]
     fn test_encode_bulk_string() {
         let input = Bytes::from_static(b"hello");
         let expected = b"$5\r\nhello\r\n";
-        assert_eq!(encode_bulk_string(&input), expected);
+        let mut buffer = BytesMut::new();
+        encode_bulk_string(&input, &mut buffer);
+        assert_eq!(&buffer[..], expected);
+    }
+
+    #[test]
+    fn test_parse_resp3_scalars() {
+        assert_eq!(parse_single(b"_\r\n").unwrap(), Frame::Null);
+        assert_eq!(parse_single(b",1.5\r\n").unwrap(), Frame::Double(1.5));
+        assert_eq!(
+            parse_single(b",-inf\r\n").unwrap(),
+            Frame::Double(f64::NEG_INFINITY)
+        );
+        assert_eq!(parse_single(b",1e3\r\n").unwrap(), Frame::Double(1000.0));
+        assert_eq!(parse_single(b"#t\r\n").unwrap(), Frame::Boolean(true));
+        assert_eq!(parse_single(b"#f\r\n").unwrap(), Frame::Boolean(false));
+        assert_eq!(
+            parse_single(b"(-3492890328409238509324850943850943825024385\r\n").unwrap(),
+            Frame::BigNumber(Bytes::from_static(
+                b"-3492890328409238509324850943850943825024385"
+            ))
+        );
+        assert_eq!(
+            parse_single(b"!21\r\nSYNTAX invalid syntax\r\n").unwrap(),
+            Frame::BlobError(Bytes::from_static(b"SYNTAX invalid syntax"))
+        );
+        assert_eq!(
+            parse_single(b"=15\r\ntxt:Some string\r\n").unwrap(),
+            Frame::VerbatimString {
+                format: *b"txt",
+                data: Bytes::from_static(b"Some string")
+            }
+        );
+
+        assert_eq!(
+            Frame::parse(b"_x\r\n").unwrap_err(),
+            FrameErrors::IncorrectNull
+        );
+        assert_eq!(
+            Frame::parse(b",one\r\n").unwrap_err(),
+            FrameErrors::IncorrectDouble
+        );
+        assert_eq!(
+            Frame::parse(b"#x\r\n").unwrap_err(),
+            FrameErrors::IncorrectBoolean
+        );
+        assert_eq!(
+            Frame::parse(b"(12a\r\n").unwrap_err(),
+            FrameErrors::IncorrectBigNumber
+        );
+        assert_eq!(
+            Frame::parse(b"=3\r\ntxt\r\n").unwrap_err(),
+            FrameErrors::IncorrectVerbatimString
+        );
+    }
+
+    #[test]
+    fn test_parse_resp3_aggregates() {
+        assert_eq!(
+            parse_single(b"%2\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n").unwrap(),
+            Frame::Map(vec![
+                (
+                    Frame::BulkString(Bytes::from_static(b"a")),
+                    Frame::BulkString(Bytes::from_static(b"1"))
+                ),
+                (
+                    Frame::BulkString(Bytes::from_static(b"b")),
+                    Frame::BulkString(Bytes::from_static(b"2"))
+                ),
+            ])
+        );
+        assert_eq!(
+            parse_single(b"~1\r\n$1\r\na\r\n").unwrap(),
+            Frame::Set(vec![Frame::BulkString(Bytes::from_static(b"a"))])
+        );
+        assert_eq!(
+            parse_single(b">2\r\n$7\r\nmessage\r\n$2\r\nhi\r\n").unwrap(),
+            Frame::Push(vec![
+                Frame::BulkString(Bytes::from_static(b"message")),
+                Frame::BulkString(Bytes::from_static(b"hi")),
+            ])
+        );
+        assert_eq!(
+            parse_single(b"|1\r\n$3\r\nttl\r\n$1\r\n3\r\n").unwrap(),
+            Frame::Attribute(vec![(
+                Frame::BulkString(Bytes::from_static(b"ttl")),
+                Frame::BulkString(Bytes::from_static(b"3"))
+            )])
+        );
+        assert_eq!(Frame::parse(b"%1\r\n$1\r\na\r\n").unwrap(), None);
+    }
+
+    #[test]
+    fn test_encode_resp3_types() {
+        let map = Frame::Map(vec![(
+            Frame::SimpleString(Bytes::from_static(b"proto")),
+            Frame::Integer(3),
+        )]);
+        assert_eq!(encoded(&map, Protocol::Resp3), b"%1\r\n+proto\r\n:3\r\n");
+        assert_eq!(encoded(&map, Protocol::Resp2), b"*2\r\n+proto\r\n:3\r\n");
+
+        let set = Frame::Set(vec![Frame::BulkString(Bytes::from_static(b"a"))]);
+        assert_eq!(encoded(&set, Protocol::Resp3), b"~1\r\n$1\r\na\r\n");
+        assert_eq!(encoded(&set, Protocol::Resp2), b"*1\r\n$1\r\na\r\n");
+
+        let push = Frame::Push(vec![Frame::BulkString(Bytes::from_static(b"a"))]);
+        assert_eq!(encoded(&push, Protocol::Resp3), b">1\r\n$1\r\na\r\n");
+        assert_eq!(encoded(&push, Protocol::Resp2), b"*1\r\n$1\r\na\r\n");
+
+        let double = Frame::Double(3.25);
+        assert_eq!(encoded(&double, Protocol::Resp3), b",3.25\r\n");
+        assert_eq!(encoded(&double, Protocol::Resp2), b"$4\r\n3.25\r\n");
+        assert_eq!(
+            encoded(&Frame::Double(f64::INFINITY), Protocol::Resp3),
+            b",inf\r\n"
+        );
+
+        let boolean = Frame::Boolean(true);
+        assert_eq!(encoded(&boolean, Protocol::Resp3), b"#t\r\n");
+        assert_eq!(encoded(&boolean, Protocol::Resp2), b":1\r\n");
+
+        let big = Frame::BigNumber(Bytes::from_static(b"12345678901234567890"));
+        assert_eq!(encoded(&big, Protocol::Resp3), b"(12345678901234567890\r\n");
+        assert_eq!(
+            encoded(&big, Protocol::Resp2),
+            b"$20\r\n12345678901234567890\r\n"
+        );
+
+        let verbatim = Frame::VerbatimString {
+            format: *b"txt",
+            data: Bytes::from_static(b"Some string"),
+        };
+        assert_eq!(
+            encoded(&verbatim, Protocol::Resp3),
+            b"=15\r\ntxt:Some string\r\n"
+        );
+        assert_eq!(
+            encoded(&verbatim, Protocol::Resp2),
+            b"$11\r\nSome string\r\n"
+        );
+
+        let error = Frame::BlobError(Bytes::from_static(b"ERR multi\r\nline"));
+        assert_eq!(
+            encoded(&error, Protocol::Resp3),
+            b"!15\r\nERR multi\r\nline\r\n"
+        );
+        assert_eq!(encoded(&error, Protocol::Resp2), b"-ERR multi  line\r\n");
+
+        let attribute = Frame::Attribute(vec![(
+            Frame::SimpleString(Bytes::from_static(b"ttl")),
+            Frame::Integer(3),
+        )]);
+        assert_eq!(
+            encoded(&attribute, Protocol::Resp3),
+            b"|1\r\n+ttl\r\n:3\r\n"
+        );
+        assert_eq!(encoded(&attribute, Protocol::Resp2), b"");
+    }
+
+    #[test]
+    fn test_format_double() {
+        assert_eq!(format_double(1.0), "1");
+        assert_eq!(format_double(-0.125), "-0.125");
+        assert_eq!(format_double(1e300), "1e300");
+        assert_eq!(format_double(f64::NAN), "nan");
+        assert_eq!(format_double(f64::NEG_INFINITY), "-inf");
+    }
+
+    #[test]
+    fn test_format_human_double() {
+        assert_eq!(format_human_double(0.1 + 0.2), "0.3");
+        assert_eq!(format_human_double(0.1 + 0.7), "0.8");
+        assert_eq!(format_human_double(10.5 + 0.1), "10.6");
+        assert_eq!(format_human_double(5200.0), "5200");
+        assert_eq!(format_human_double(-1.25), "-1.25");
+        assert_eq!(format_human_double(1e20), "100000000000000000000");
+        assert_eq!(format_human_double(1e-20), "0");
+        assert_eq!(format_human_double(-0.0), "0");
+    }
+
+    #[test]
+    fn test_parse_nested_array() {
+        let buffer = b"*3\r\n:1\r\n*2\r\n+a\r\n$-1\r\n$4\r\n\r\n\r\n\r\n";
+        let expected = Frame::Array(vec![
+            Frame::Integer(1),
+            Frame::Array(vec![
+                Frame::SimpleString(Bytes::from_static(b"a")),
+                Frame::Null,
+            ]),
+            Frame::BulkString(Bytes::from_static(b"\r\n\r\n")),
+        ]);
+        assert_eq!(parse_single(buffer).unwrap(), expected);
+
+        // incomplete nested item means incomplete array
+        assert_eq!(Frame::parse(b"*2\r\n:1\r\n*1\r\n").unwrap(), None);
+
+        let too_deep = b"*1\r\n".repeat(MAX_NESTING_DEPTH + 2);
+        assert_eq!(
+            Frame::parse(&too_deep).unwrap_err(),
+            FrameErrors::NestingTooDeep
+        );
+    }
+
+    #[test]
+    fn test_round_trip_resp2() {
+        let frames = vec![
+            Frame::SimpleString(Bytes::from_static(b"OK")),
+            Frame::Error(Bytes::from_static(b"ERR oops")),
+            Frame::BulkString(Bytes::from_static(b"\x00\xff\r\n")),
+            Frame::Integer(-42),
+            Frame::Null,
+            Frame::NullArray,
+            Frame::Array(vec![]),
+            Frame::Array(vec![
+                Frame::Integer(1),
+                Frame::Array(vec![
+                    Frame::BulkString(Bytes::from_static(b"deep")),
+                    Frame::Array(vec![Frame::NullArray, Frame::Null]),
+                ]),
+                Frame::Error(Bytes::from_static(b"WRONGTYPE nope")),
+            ]),
+        ];
+
+        for frame in frames {
+            let buffer = encoded(&frame, Protocol::Resp2);
+            assert_eq!(parse_single(&buffer).unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_resp3() {
+        let frames = vec![
+            Frame::Double(-1.5),
+            Frame::Double(1e300),
+            Frame::Double(f64::INFINITY),
+            Frame::Boolean(false),
+            Frame::BigNumber(Bytes::from_static(b"-123456789012345678901234567890")),
+            Frame::VerbatimString {
+                format: *b"mkd",
+                data: Bytes::from_static(b"# title\r\n"),
+            },
+            Frame::BlobError(Bytes::from_static(b"SYNTAX bad\r\nthing")),
+            Frame::Map(vec![
+                (
+                    Frame::BulkString(Bytes::from_static(b"scores")),
+                    Frame::Set(vec![Frame::Double(1.0), Frame::Integer(2)]),
+                ),
+                (
+                    Frame::Integer(1),
+                    Frame::Map(vec![(Frame::Boolean(true), Frame::Null)]),
+                ),
+            ]),
+            Frame::Push(vec![
+                Frame::BulkString(Bytes::from_static(b"message")),
+                Frame::Array(vec![Frame::Integer(1), Frame::Array(vec![])]),
+            ]),
+            Frame::Attribute(vec![(
+                Frame::SimpleString(Bytes::from_static(b"key-popularity")),
+                Frame::Map(vec![(
+                    Frame::BulkString(Bytes::from_static(b"a")),
+                    Frame::Double(0.1923),
+                )]),
+            )]),
+        ];
+
+        for frame in frames {
+            let buffer = encoded(&frame, Protocol::Resp3);
+            assert_eq!(parse_single(&buffer).unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn test_parse_inline_command() {
+        let expected = Frame::Array(vec![
+            Frame::BulkString(Bytes::from_static(b"SET")),
+            Frame::BulkString(Bytes::from_static(b"foo")),
+            Frame::BulkString(Bytes::from_static(b"bar")),
+        ]);
+        assert_eq!(parse_single(b"SET foo bar\r\n").unwrap(), expected);
+        assert_eq!(parse_single(b"  SET \t foo   bar  \n").unwrap(), expected);
+
+        assert_eq!(
+            parse_single(b"PING\r\n").unwrap(),
+            Frame::Array(vec![Frame::BulkString(Bytes::from_static(b"PING"))])
+        );
+        assert_eq!(parse_single(b"\r\n").unwrap(), Frame::Array(vec![]));
+        assert_eq!(Frame::parse(b"SET foo").unwrap(), None);
+
+        // pipelined with a RESP frame
+        let buffer = b"PING\r\n*1\r\n$4\r\nPING\r\n";
+        let (_, length) = Frame::parse(buffer).unwrap().unwrap();
+        assert_eq!(length, 6);
+
+        // only the top level frame can be inline
+        assert_eq!(
+            Frame::parse(b"*1\r\nPING\r\n").unwrap_err(),
+            FrameErrors::IncorrectFirstByte(b'P')
+        );
+
+        let too_big = vec![b'a'; MAX_INLINE_LENGTH + 1];
+        assert_eq!(
+            Frame::parse(&too_big).unwrap_err(),
+            FrameErrors::InlineTooBig
+        );
+    }
+
+    #[test]
+    fn test_split_inline_args_with_quotes() {
+        assert_eq!(
+            split_inline_args(br#"SET "hello world" 'it''s'"#).unwrap_err(),
+            FrameErrors::UnbalancedQuotes
+        );
+        assert_eq!(
+            split_inline_args(br#"SET "a\"b\n\x41" 'it\'s' """#).unwrap(),
+            vec![
+                Bytes::from_static(b"SET"),
+                Bytes::from_static(b"a\"b\nA"),
+                Bytes::from_static(b"it's"),
+                Bytes::from_static(b""),
+            ]
+        );
+        assert_eq!(
+            split_inline_args(br#"GET "foo"#).unwrap_err(),
+            FrameErrors::UnbalancedQuotes
+        );
+        assert_eq!(
+            split_inline_args(br#"GET "foo"bar"#).unwrap_err(),
+            FrameErrors::UnbalancedQuotes
+        );
     }
 }
@@ -1,36 +1,84 @@
 use anyhow::Result;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use crate::redis::{Command, Frame, Storage};
+use crate::redis::{CmdErrors, Command, Frame, FrameErrors, Protocol, Storage};
 use crate::Connection;
 
+// ids are unique for the lifetime of the process, like client ids in Redis
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
 pub(crate) struct ConnectionHandler {
+    id: u64,
     connection: Connection,
     storage: Storage,
+    protocol: Protocol,
 }
 
 impl ConnectionHandler {
     pub fn new(connection: Connection, storage: Storage) -> Self {
         ConnectionHandler {
-            connection: connection,
-            storage: storage,
+            id: NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed),
+            connection,
+            storage,
+            protocol: Protocol::default(),
         }
     }
 
     pub async fn run(&mut self) -> Result<()> {
         loop {
-            let buffer = self.connection.read().await?;
-            let frame = Frame::from_bytes(&buffer)?;
-            let mut cmd: Command = Command::from_frame(&frame)?;
-            match &mut cmd {
-                Command::Get(cmd) => cmd.run(&self.storage).await,
-                Command::Set(cmd) => cmd.run(&self.storage).await,
-                _ => {}
+            let frame = match self.connection.read_frame().await {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return Ok(()),
+                Err(err) => {
+                    // the client still gets a reason before the connection is dropped
+                    if let Some(frame_err) = err.downcast_ref::<FrameErrors>() {
+                        let response = frame_err.as_error_frame();
+                        self.connection
+                            .write_frame(&response, self.protocol)
+                            .await?;
+                    }
+                    return Err(err);
+                }
+            };
+
+            // empty command (like a blank inline line) gets no reply, same as in Redis
+            if matches!(&frame, Frame::Array(parts) if parts.is_empty()) {
+                continue;
+            }
+
+            // command errors are answered, the connection stays usable
+            let response_frame = match self.execute(&frame).await {
+                Ok(Some(response)) => response,
+                // the client disconnected while blocked
+                Ok(None) => return Ok(()),
+                Err(err) => match err.downcast_ref::<CmdErrors>() {
+                    Some(cmd_err) => cmd_err.as_error_frame(),
+                    None => return Err(err),
+                },
             };
 
-            let response_frame = cmd.as_response_frame();
             self.connection
-                .write(&response_frame.as_resp_bytes())
+                .write_frame(&response_frame, self.protocol)
                 .await?;
         }
     }
+
+    /// `None` means the client disconnected before the command finished.
+    async fn execute(&mut self, frame: &Frame) -> Result<Option<Frame>> {
+        let mut cmd: Command = Command::from_frame(frame)?;
+        match &mut cmd {
+            Command::Hello(cmd) => cmd.run(&mut self.protocol, self.id),
+            // nobody would wake up a client that is gone, so it is unblocked right away
+            cmd if cmd.is_blocking() => tokio::select! {
+                result = cmd.run(&self.storage, self.id) => result?,
+                _ = self.connection.closed() => {
+                    self.storage.unblock(self.id).await;
+                    return Ok(None);
+                }
+            },
+            cmd => cmd.run(&self.storage, self.id).await?,
+        };
+
+        Ok(Some(cmd.as_response_frame()))
+    }
 }
@@ -2,11 +2,11 @@ pub(crate) mod command;
 pub(crate) mod errors;
 pub(crate) mod frame;
 pub(crate) mod handler;
+pub(crate) mod random;
 pub(crate) mod storage;
 
 pub(crate) use command::Command;
 pub(crate) use errors::{CmdErrors, FrameErrors};
-pub(crate) use frame::Frame;
+pub(crate) use frame::{Frame, FrameScanner, Protocol};
 pub(crate) use handler::ConnectionHandler;
 pub(crate) use storage::Storage;
-
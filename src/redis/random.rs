@@ -0,0 +1,102 @@
+// Small and fast pseudo random numbers for sampling keys and picking random members,
+// nothing here needs to be cryptographically secure.
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+
+use crate::redis::CmdErrors;
+
+thread_local! {
+    // xorshift state must never be zero
+    static STATE: Cell<u64> = Cell::new(RandomState::new().hash_one(0u64) | 1);
+}
+
+/// xorshift64*
+pub(crate) fn next_u64() -> u64 {
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        x.wrapping_mul(0x2545F4914F6CDD1D)
+    })
+}
+
+/// Random index in `0..len`, `len` must not be zero.
+pub(crate) fn random_index(len: usize) -> usize {
+    (next_u64() % len as u64) as usize
+}
+
+/// Random elements of a `len` long collection, got with `pick` from their index, the way
+/// `HRANDFIELD` and `SRANDMEMBER` choose them: a positive `count` picks distinct elements
+/// (all of them when there are not enough), a negative one picks `-count` elements that
+/// may repeat.
+pub(crate) fn sample<T>(
+    len: usize,
+    count: i64,
+    mut pick: impl FnMut(usize) -> T,
+) -> Result<Vec<T>, CmdErrors> {
+    if len == 0 {
+        return Ok(vec![]);
+    }
+    if count < 0 {
+        let count =
+            usize::try_from(count.unsigned_abs()).map_err(|_| CmdErrors::ValueOutOfRange)?;
+        let mut picked = vec![];
+        // nothing bounds the count but the range check, it may not fit in memory
+        picked
+            .try_reserve_exact(count)
+            .map_err(|_| CmdErrors::ValueOutOfRange)?;
+        picked.extend((0..count).map(|_| pick(random_index(len))));
+        return Ok(picked);
+    }
+
+    let count = usize::try_from(count).unwrap_or(usize::MAX).min(len);
+    // most of the elements: partial Fisher-Yates shuffle of all the indexes, like Redis
+    // copies the whole collection then
+    if count * 3 > len {
+        let mut indexes: Vec<usize> = (0..len).collect();
+        for i in 0..count {
+            let j = i + random_index(len - i);
+            indexes.swap(i, j);
+        }
+        return Ok(indexes[..count].iter().map(|index| pick(*index)).collect());
+    }
+
+    // a few of many: random picks until enough of them are distinct
+    let mut picked_indexes = HashSet::with_capacity(count);
+    let mut picked = Vec::with_capacity(count);
+    while picked.len() < count {
+        let index = random_index(len);
+        if picked_indexes.insert(index) {
+            picked.push(pick(index));
+        }
+    }
+    Ok(picked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        for (len, count) in [(10, 5), (100, 5)] {
+            let mut distinct = sample(len, count, |index| index).unwrap();
+            distinct.sort_unstable();
+            distinct.dedup();
+            assert_eq!(distinct.len(), 5);
+            assert!(distinct.iter().all(|index| *index < len));
+        }
+
+        assert_eq!(sample(3, 10, |index| index).unwrap().len(), 3);
+        assert_eq!(sample(1, -4, |index| index), Ok(vec![0; 4]));
+        assert_eq!(sample(0, -4, |index| index), Ok(vec![]));
+        assert_eq!(
+            sample(1, i64::MIN, |index| index),
+            Err(CmdErrors::ValueOutOfRange)
+        );
+    }
+}
@@ -0,0 +1,487 @@
+use bytes::{Bytes, BytesMut};
+
+use super::string::STRING_MAX_LEN;
+use super::{Entry, Storage, Value};
+use crate::redis::CmdErrors;
+
+/// Bits a string can hold, offsets of `SETBIT` and `BITFIELD` stay below it.
+pub(crate) const BITMAP_MAX_BITS: usize = STRING_MAX_LEN * 8;
+
+/// Unit of the `BITCOUNT` and `BITPOS` ranges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BitUnit {
+    Byte,
+    Bit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BitOp {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+/// What `SET` and `INCRBY` of `BITFIELD` do with values that don't fit the field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BitOverflow {
+    Wrap,
+    Sat,
+    // the field isn't changed and the reply is nil
+    Fail,
+}
+
+/// Signed or unsigned integer of `bits` bits, like `i5` or `u16`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct BitfieldType {
+    pub(crate) signed: bool,
+    pub(crate) bits: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BitfieldOp {
+    Get {
+        field: BitfieldType,
+        offset: usize,
+    },
+    Set {
+        field: BitfieldType,
+        offset: usize,
+        value: i64,
+        overflow: BitOverflow,
+    },
+    IncrBy {
+        field: BitfieldType,
+        offset: usize,
+        increment: i64,
+        overflow: BitOverflow,
+    },
+}
+
+impl BitfieldType {
+    fn mask(self) -> u64 {
+        u64::MAX >> (64 - self.bits)
+    }
+
+    fn limits(self) -> (i128, i128) {
+        match self.signed {
+            true => (-(1i128 << (self.bits - 1)), (1i128 << (self.bits - 1)) - 1),
+            false => (0, (1i128 << self.bits) - 1),
+        }
+    }
+
+    /// The field read as an integer, signed fields are sign extended.
+    fn decode(self, raw: u64) -> i64 {
+        match self.signed {
+            true => ((raw << (64 - self.bits)) as i64) >> (64 - self.bits),
+            false => raw as i64,
+        }
+    }
+
+    /// `value` made to fit the field, `None` when it doesn't and `overflow` is `FAIL`.
+    fn fit(self, value: i128, overflow: BitOverflow) -> Option<i64> {
+        let (min, max) = self.limits();
+        if (min..=max).contains(&value) {
+            return Some(value as i64);
+        }
+        match overflow {
+            BitOverflow::Wrap => Some(self.decode(value as u64 & self.mask())),
+            BitOverflow::Sat => Some(value.clamp(min, max) as i64),
+            BitOverflow::Fail => None,
+        }
+    }
+}
+
+impl BitfieldOp {
+    fn end(&self) -> usize {
+        match self {
+            BitfieldOp::Get { field, offset }
+            | BitfieldOp::Set { field, offset, .. }
+            | BitfieldOp::IncrBy { field, offset, .. } => offset + field.bits,
+        }
+    }
+}
+
+// bits are numbered from the most significant one of the first byte, like in Redis
+fn get_bit(string: &[u8], offset: usize) -> bool {
+    string
+        .get(offset / 8)
+        .is_some_and(|byte| byte & (0x80 >> (offset % 8)) != 0)
+}
+
+fn set_bit(string: &mut [u8], offset: usize, bit: bool) {
+    let mask = 0x80 >> (offset % 8);
+    match bit {
+        true => string[offset / 8] |= mask,
+        false => string[offset / 8] &= !mask,
+    }
+}
+
+fn read_field(string: &[u8], offset: usize, bits: usize) -> u64 {
+    (offset..offset + bits).fold(0, |field, offset| {
+        (field << 1) | u64::from(get_bit(string, offset))
+    })
+}
+
+fn write_field(string: &mut [u8], offset: usize, bits: usize, field: u64) {
+    for bit in 0..bits {
+        set_bit(string, offset + bit, field & (1 << (bits - 1 - bit)) != 0);
+    }
+}
+
+/// First and last bit of the range, both inclusive. Negative offsets count from the
+/// end and the rest are clamped to the string, the way `BITCOUNT` does it.
+fn bit_range(start: i64, end: i64, unit: BitUnit, len: usize) -> Option<(usize, usize)> {
+    if start < 0 && end < 0 && start > end {
+        return None;
+    }
+    let scale = match unit {
+        BitUnit::Byte => 8,
+        BitUnit::Bit => 1,
+    };
+    let total = (len * 8 / scale) as i64;
+    let start = match start < 0 {
+        true => start + total,
+        false => start,
+    }
+    .max(0);
+    let end = match end < 0 {
+        true => end + total,
+        false => end,
+    }
+    .max(0)
+    .min(total - 1);
+    match start > end {
+        true => None,
+        false => Some((start as usize * scale, end as usize * scale + scale - 1)),
+    }
+}
+
+/// Bytes covering the bits from `first` to `last` with the bits outside of the
+/// range cleared, inverted first with `flip`. Paired with their index.
+fn masked_bytes(
+    string: &[u8],
+    first: usize,
+    last: usize,
+    flip: bool,
+) -> impl Iterator<Item = (usize, u8)> + '_ {
+    (first / 8..=last / 8).map(move |index| {
+        let mut mask = 0xffu8;
+        if index == first / 8 {
+            mask &= 0xff >> (first % 8);
+        }
+        if index == last / 8 {
+            mask &= 0xff << (7 - last % 8);
+        }
+        let byte = match flip {
+            true => !string[index],
+            false => string[index],
+        };
+        (index, byte & mask)
+    })
+}
+
+// the string grown with zero bytes to `len`, the buffer is reused when nothing
+// else holds the string
+fn grown(string: &mut Bytes, len: usize) -> BytesMut {
+    let mut grown = BytesMut::from(std::mem::take(string));
+    if grown.len() < len {
+        grown.resize(len, 0);
+    }
+    grown
+}
+
+impl Storage {
+    /// Sets or clears the bit, growing the string as needed. Returns the old bit.
+    pub(crate) async fn bit_set(
+        &self,
+        key: &Bytes,
+        offset: usize,
+        bit: bool,
+    ) -> Result<bool, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let string = state.get_or_insert_value::<Bytes>(key)?;
+        let mut updated = grown(string, offset / 8 + 1);
+        let old = get_bit(&updated, offset);
+        set_bit(&mut updated, offset, bit);
+        *string = updated.freeze();
+        Ok(old)
+    }
+
+    /// Bits past the end of the string are clear.
+    pub(crate) async fn bit_get(&self, key: &Bytes, offset: usize) -> Result<bool, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state
+            .get_value::<Bytes>(key)?
+            .is_some_and(|string| get_bit(string, offset)))
+    }
+
+    /// Set bits of the whole string or of the range from `start` to `end`.
+    pub(crate) async fn bit_count(
+        &self,
+        key: &Bytes,
+        range: Option<(i64, i64, BitUnit)>,
+    ) -> Result<u64, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(string) = state.get_value::<Bytes>(key)? else {
+            return Ok(0);
+        };
+        let (start, end, unit) = range.unwrap_or((0, -1, BitUnit::Byte));
+        let Some((first, last)) = bit_range(start, end, unit, string.len()) else {
+            return Ok(0);
+        };
+        Ok(masked_bytes(string, first, last, false)
+            .map(|(_, byte)| u64::from(byte.count_ones()))
+            .sum())
+    }
+
+    /// Position of the first bit equal to `bit` in the range, `-1` when there is
+    /// none. Clear bits are looked for past the string when the range has no end.
+    pub(crate) async fn bit_pos(
+        &self,
+        key: &Bytes,
+        bit: bool,
+        start: i64,
+        end: Option<i64>,
+        unit: BitUnit,
+    ) -> Result<i64, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(string) = state.get_value::<Bytes>(key)? else {
+            return Ok(match bit {
+                true => -1,
+                false => 0,
+            });
+        };
+        let Some((first, last)) = bit_range(start, end.unwrap_or(-1), unit, string.len()) else {
+            return Ok(-1);
+        };
+        let found = masked_bytes(string, first, last, !bit)
+            .find(|(_, byte)| *byte != 0)
+            .map(|(index, byte)| (index * 8 + byte.leading_zeros() as usize) as i64);
+        Ok(match found {
+            Some(position) => position,
+            None if !bit && end.is_none() => last as i64 + 1,
+            None => -1,
+        })
+    }
+
+    /// Stores the result of `op` over the strings in `destination` and returns its
+    /// length. Missing keys are empty strings, shorter strings are padded with zero
+    /// bytes, an empty result deletes `destination`.
+    pub(crate) async fn bit_op(
+        &self,
+        op: BitOp,
+        destination: &Bytes,
+        keys: &[Bytes],
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let mut strings = vec![];
+        for key in keys {
+            strings.push(state.get_value::<Bytes>(key)?.cloned().unwrap_or_default());
+        }
+        let len = strings.iter().map(Bytes::len).max().unwrap_or(0);
+
+        let mut result = BytesMut::zeroed(len);
+        for (index, byte) in result.iter_mut().enumerate() {
+            let mut bytes = strings
+                .iter()
+                .map(|string| string.get(index).copied().unwrap_or(0));
+            let first = bytes.next().unwrap_or(0);
+            *byte = match op {
+                BitOp::And => bytes.fold(first, |result, byte| result & byte),
+                BitOp::Or => bytes.fold(first, |result, byte| result | byte),
+                BitOp::Xor => bytes.fold(first, |result, byte| result ^ byte),
+                BitOp::Not => !first,
+            };
+        }
+
+        match result.is_empty() {
+            true => {
+                state.remove(destination);
+            }
+            false => state.insert(
+                destination.clone(),
+                Entry {
+                    value: Value::String(result.freeze()),
+                    expires_at: None,
+                },
+            ),
+        }
+        Ok(len)
+    }
+
+    /// Runs the operations in order, one reply each: the value for `GET`, the old
+    /// value for `SET` and the new one for `INCRBY`, `None` when `FAIL` stopped the
+    /// write. Only writes create the key and grow the string.
+    pub(crate) async fn bitfield(
+        &self,
+        key: &Bytes,
+        ops: &[BitfieldOp],
+    ) -> Result<Vec<Option<i64>>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let writes_end = ops
+            .iter()
+            .filter(|op| !matches!(op, BitfieldOp::Get { .. }))
+            .map(BitfieldOp::end)
+            .max();
+        let Some(writes_end) = writes_end else {
+            let string = state.get_value::<Bytes>(key)?.cloned().unwrap_or_default();
+            return Ok(ops
+                .iter()
+                .map(|op| match op {
+                    BitfieldOp::Get { field, offset } => {
+                        Some(field.decode(read_field(&string, *offset, field.bits)))
+                    }
+                    _ => None,
+                })
+                .collect());
+        };
+
+        let string = state.get_or_insert_value::<Bytes>(key)?;
+        let mut updated = grown(string, writes_end.div_ceil(8));
+        let mut replies = vec![];
+        for op in ops {
+            let reply = match *op {
+                BitfieldOp::Get { field, offset } => {
+                    Some(field.decode(read_field(&updated, offset, field.bits)))
+                }
+                BitfieldOp::Set {
+                    field,
+                    offset,
+                    value,
+                    overflow,
+                } => {
+                    let old = field.decode(read_field(&updated, offset, field.bits));
+                    // unsigned fields take the value as its 64 bits, like Redis
+                    let value = match field.signed {
+                        true => i128::from(value),
+                        false => i128::from(value as u64),
+                    };
+                    field.fit(value, overflow).map(|value| {
+                        write_field(
+                            &mut updated,
+                            offset,
+                            field.bits,
+                            value as u64 & field.mask(),
+                        );
+                        old
+                    })
+                }
+                BitfieldOp::IncrBy {
+                    field,
+                    offset,
+                    increment,
+                    overflow,
+                } => {
+                    let old = field.decode(read_field(&updated, offset, field.bits));
+                    let value = i128::from(old) + i128::from(increment);
+                    field.fit(value, overflow).inspect(|value| {
+                        write_field(
+                            &mut updated,
+                            offset,
+                            field.bits,
+                            *value as u64 & field.mask(),
+                        );
+                    })
+                }
+            };
+            replies.push(reply);
+        }
+        *string = updated.freeze();
+        Ok(replies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_ranges() {
+        assert_eq!(bit_range(0, -1, BitUnit::Byte, 3), Some((0, 23)));
+        assert_eq!(bit_range(1, 1, BitUnit::Byte, 3), Some((8, 15)));
+        assert_eq!(bit_range(5, 30, BitUnit::Bit, 2), Some((5, 15)));
+        assert_eq!(bit_range(-2, -1, BitUnit::Bit, 1), Some((6, 7)));
+        assert_eq!(bit_range(-1, -2, BitUnit::Byte, 3), None);
+        assert_eq!(bit_range(0, -1, BitUnit::Byte, 0), None);
+    }
+
+    #[test]
+    fn test_bitfield_overflow() {
+        let u8 = BitfieldType {
+            signed: false,
+            bits: 8,
+        };
+        assert_eq!(u8.fit(256, BitOverflow::Wrap), Some(0));
+        assert_eq!(u8.fit(300, BitOverflow::Sat), Some(255));
+        assert_eq!(u8.fit(-1, BitOverflow::Sat), Some(0));
+        assert_eq!(u8.fit(-1, BitOverflow::Fail), None);
+
+        let i8 = BitfieldType {
+            signed: true,
+            bits: 8,
+        };
+        assert_eq!(i8.fit(128, BitOverflow::Wrap), Some(-128));
+        assert_eq!(i8.fit(-129, BitOverflow::Wrap), Some(127));
+        assert_eq!(i8.fit(-200, BitOverflow::Sat), Some(-128));
+        assert_eq!(i8.decode(0xff), -1);
+
+        let i64 = BitfieldType {
+            signed: true,
+            bits: 64,
+        };
+        assert_eq!(
+            i64.fit(i128::from(i64::MAX) + 1, BitOverflow::Wrap),
+            Some(i64::MIN)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bitfield() {
+        let storage = Storage::setup();
+        let key = Bytes::from("fields");
+        let u4 = BitfieldType {
+            signed: false,
+            bits: 4,
+        };
+        let get = BitfieldOp::Get {
+            field: u4,
+            offset: 4,
+        };
+        assert_eq!(
+            storage.bitfield(&key, std::slice::from_ref(&get)).await,
+            Ok(vec![Some(0)])
+        );
+        assert_eq!(storage.key_type(&key).await, None);
+
+        let ops = [
+            BitfieldOp::Set {
+                field: u4,
+                offset: 4,
+                value: 9,
+                overflow: BitOverflow::Wrap,
+            },
+            BitfieldOp::IncrBy {
+                field: u4,
+                offset: 4,
+                increment: 10,
+                overflow: BitOverflow::Fail,
+            },
+            BitfieldOp::IncrBy {
+                field: u4,
+                offset: 4,
+                increment: 10,
+                overflow: BitOverflow::Wrap,
+            },
+            get,
+        ];
+        assert_eq!(
+            storage.bitfield(&key, &ops).await,
+            Ok(vec![Some(0), None, Some(3), Some(3)])
+        );
+        assert_eq!(
+            storage.get(&key).await,
+            Ok(Some(Bytes::from_static(b"\x03")))
+        );
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::sync::oneshot;
+
+use super::{ListEnd, State, Storage, StreamEntry, StreamId, Value, ZSetEnd};
+use crate::redis::CmdErrors;
+
+/// What a blocked client waits for, it is served by the first of its keys that can do it.
+#[derive(Debug, Clone)]
+pub(crate) enum BlockingOp {
+    // `BLPOP`, `BRPOP` and `BLMPOP`
+    ListPop {
+        end: ListEnd,
+        count: usize,
+    },
+    // `BLMOVE`
+    ListMove {
+        from: ListEnd,
+        destination: Bytes,
+        to: ListEnd,
+    },
+    // `BZPOPMIN`, `BZPOPMAX` and `BZMPOP`
+    ZSetPop {
+        end: ZSetEnd,
+        count: usize,
+    },
+    // `XREAD`, entries are only read, so every reader of the stream is served
+    StreamRead {
+        after: HashMap<Bytes, StreamId>,
+        count: Option<usize>,
+    },
+    // `XREADGROUP`, new entries are delivered to one consumer of the group
+    GroupRead {
+        group: Bytes,
+        consumer: Bytes,
+        count: Option<usize>,
+        no_ack: bool,
+    },
+}
+
+impl BlockingOp {
+    /// Whether the operation works with values of this type.
+    fn serves(&self, value: &Value) -> bool {
+        match self {
+            BlockingOp::ListPop { .. } | BlockingOp::ListMove { .. } => {
+                matches!(value, Value::List(_))
+            }
+            BlockingOp::ZSetPop { .. } => matches!(value, Value::SortedSet(_)),
+            BlockingOp::StreamRead { .. } | BlockingOp::GroupRead { .. } => {
+                matches!(value, Value::Stream(_))
+            }
+        }
+    }
+}
+
+/// Elements a blocking command got and the key they came from.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Popped {
+    pub key: Bytes,
+    pub items: PoppedItems,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PoppedItems {
+    // list elements
+    Values(Vec<Bytes>),
+    // sorted set members with their scores
+    Scored(Vec<(Bytes, f64)>),
+    // stream entries, they stay in the stream
+    Entries(Vec<StreamEntry>),
+}
+
+impl PoppedItems {
+    fn is_empty(&self) -> bool {
+        match self {
+            PoppedItems::Values(values) => values.is_empty(),
+            PoppedItems::Scored(members) => members.is_empty(),
+            PoppedItems::Entries(entries) => entries.is_empty(),
+        }
+    }
+}
+
+type Reply = Result<Popped, CmdErrors>;
+
+#[derive(Debug)]
+struct Waiter {
+    keys: Vec<Bytes>,
+    op: BlockingOp,
+    sender: oneshot::Sender<Reply>,
+}
+
+/// Clients blocked on keys. Every key has a queue of client ids, so clients
+/// are served in the order they blocked.
+#[derive(Debug, Default)]
+pub(super) struct BlockedClients {
+    waiters: HashMap<u64, Waiter>,
+    queues: HashMap<Bytes, VecDeque<u64>>,
+    // keys that got something for their clients, like `ready_keys` in Redis
+    ready: VecDeque<Bytes>,
+    // `ready` is being drained, serving a client may add more keys to it
+    serving: bool,
+}
+
+impl BlockedClients {
+    fn block(
+        &mut self,
+        client_id: u64,
+        keys: Vec<Bytes>,
+        op: BlockingOp,
+    ) -> oneshot::Receiver<Reply> {
+        let (sender, receiver) = oneshot::channel();
+        for key in &keys {
+            self.queues
+                .entry(key.clone())
+                .or_default()
+                .push_back(client_id);
+        }
+        self.waiters.insert(client_id, Waiter { keys, op, sender });
+        receiver
+    }
+
+    fn unblock(&mut self, client_id: u64) -> Option<Waiter> {
+        let waiter = self.waiters.remove(&client_id)?;
+        self.leave_queues(client_id, &waiter.keys);
+        Some(waiter)
+    }
+
+    fn leave_queues(&mut self, client_id: u64, keys: &[Bytes]) {
+        for key in keys {
+            if let Some(queue) = self.queues.get_mut(key) {
+                queue.retain(|id| *id != client_id);
+                if queue.is_empty() {
+                    self.queues.remove(key);
+                }
+            }
+        }
+    }
+
+    fn waiting(&self, key: &Bytes) -> Vec<u64> {
+        self.queues
+            .get(key)
+            .map_or(vec![], |queue| queue.iter().copied().collect())
+    }
+}
+
+impl Storage {
+    /// Runs `op` on the first of `keys` that can serve it. When none can, waits until
+    /// another client adds something to one of them. `None` means the timeout expired,
+    /// no timeout means waiting forever.
+    pub(crate) async fn blocking_pop(
+        &self,
+        client_id: u64,
+        keys: &[Bytes],
+        op: BlockingOp,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Popped>, CmdErrors> {
+        let mut receiver = {
+            let mut state = self.shared.state.lock().await;
+            for key in keys {
+                if let Some(popped) = state.serve_blocking_op(key, &op)? {
+                    return Ok(Some(popped));
+                }
+            }
+
+            let mut unique_keys: Vec<Bytes> = vec![];
+            for key in keys {
+                if !unique_keys.contains(key) {
+                    unique_keys.push(key.clone());
+                }
+            }
+            state.blocked.block(client_id, unique_keys, op)
+        };
+
+        let reply = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, &mut receiver).await.ok(),
+            None => Some((&mut receiver).await),
+        };
+        match reply {
+            Some(Ok(reply)) => reply.map(Some),
+            _ => {
+                self.unblock(client_id).await;
+                // the client could be served right before it was unblocked
+                match receiver.try_recv() {
+                    Ok(reply) => reply.map(Some),
+                    Err(_) => Ok(None),
+                }
+            }
+        }
+    }
+
+    /// Forgets a blocked client, the connection handler calls it when the client
+    /// disconnects in the middle of a blocking command.
+    pub(crate) async fn unblock(&self, client_id: u64) {
+        let mut state = self.shared.state.lock().await;
+        state.blocked.unblock(client_id);
+    }
+}
+
+impl State {
+    fn serve_blocking_op(
+        &mut self,
+        key: &Bytes,
+        op: &BlockingOp,
+    ) -> Result<Option<Popped>, CmdErrors> {
+        let items = match op {
+            BlockingOp::ListPop { end, count } => {
+                self.list_pop(key, *end, *count)?.map(PoppedItems::Values)
+            }
+            BlockingOp::ListMove {
+                from,
+                destination,
+                to,
+            } => self
+                .list_move(key, destination, *from, *to)?
+                .map(|value| PoppedItems::Values(vec![value])),
+            BlockingOp::ZSetPop { end, count } => {
+                self.zset_pop(key, *end, *count)?.map(PoppedItems::Scored)
+            }
+            BlockingOp::StreamRead { after, count } => {
+                let after = after.get(key).copied().unwrap_or(StreamId::MAX);
+                self.stream_read_after(key, after, *count)?
+                    .map(PoppedItems::Entries)
+            }
+            BlockingOp::GroupRead {
+                group,
+                consumer,
+                count,
+                no_ack,
+            } => self
+                .stream_group_deliver(key, group, consumer, *count, *no_ack)?
+                .map(PoppedItems::Entries),
+        };
+
+        Ok(items.filter(|items| !items.is_empty()).map(|items| Popped {
+            key: key.clone(),
+            items,
+        }))
+    }
+
+    /// Serves clients blocked on `key` in the order they blocked, as long as the key
+    /// has something for them. Called by every command that adds to a value.
+    /// Keys made ready while serving, like the destination of `BLMOVE`, are queued
+    /// and served by the same loop instead of recursing.
+    pub(super) fn wake_blocked(&mut self, key: &Bytes) {
+        if self.blocked.queues.contains_key(key) && !self.blocked.ready.contains(key) {
+            self.blocked.ready.push_back(key.clone());
+        }
+        if self.blocked.serving {
+            return;
+        }
+
+        self.blocked.serving = true;
+        while let Some(key) = self.blocked.ready.pop_front() {
+            self.serve_blocked(&key);
+        }
+        self.blocked.serving = false;
+    }
+
+    /// Clients that wait for another type of value stay blocked, like in Redis.
+    fn serve_blocked(&mut self, key: &Bytes) {
+        for client_id in self.blocked.waiting(key) {
+            let Some(entry) = self.entries.get(key) else {
+                return;
+            };
+            // taken out before serving, so a key it makes ready doesn't serve it again
+            let Some(waiter) = self.blocked.waiters.remove(&client_id) else {
+                continue;
+            };
+            // the client is gone, nothing should be popped for it
+            if waiter.sender.is_closed() {
+                self.blocked.leave_queues(client_id, &waiter.keys);
+                continue;
+            }
+            if !waiter.op.serves(&entry.value) {
+                self.blocked.waiters.insert(client_id, waiter);
+                continue;
+            }
+
+            let reply = match self.serve_blocking_op(key, &waiter.op) {
+                // readers of the same stream may wait for entries after different IDs,
+                // the client keeps its place in the queues
+                Ok(None) => {
+                    self.blocked.waiters.insert(client_id, waiter);
+                    continue;
+                }
+                Ok(Some(popped)) => Ok(popped),
+                Err(err) => Err(err),
+            };
+            self.blocked.leave_queues(client_id, &waiter.keys);
+            let _ = waiter.sender.send(reply);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_blocked_clients_are_served_in_order() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"queue");
+        let pop = BlockingOp::ListPop {
+            end: ListEnd::Left,
+            count: 1,
+        };
+
+        let mut blocked = vec![];
+        for client_id in 1..=2 {
+            let (client, key, pop) = (storage.clone(), key.clone(), pop.clone());
+            blocked.push(tokio::spawn(async move {
+                client.blocking_pop(client_id, &[key], pop, None).await
+            }));
+            // let the client block before the next one
+            while storage.shared.state.lock().await.blocked.waiters.len() < client_id as usize {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        let values = [Bytes::from_static(b"a"), Bytes::from_static(b"b")];
+        storage
+            .list_push(&key, &values, ListEnd::Right)
+            .await
+            .unwrap();
+        for (task, value) in blocked.into_iter().zip(values) {
+            let popped = task.await.unwrap().unwrap().unwrap();
+            assert_eq!(popped.items, PoppedItems::Values(vec![value]));
+        }
+        assert_eq!(storage.key_type(&key).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_pop_timeout() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"queue");
+        let pop = BlockingOp::ListPop {
+            end: ListEnd::Left,
+            count: 1,
+        };
+
+        let popped = storage
+            .blocking_pop(1, &[key], pop, Some(Duration::from_millis(10)))
+            .await;
+        assert_eq!(popped, Ok(None));
+
+        let state = storage.shared.state.lock().await;
+        assert!(state.blocked.waiters.is_empty());
+        assert!(state.blocked.queues.is_empty());
+    }
+
+    async fn block_client(
+        storage: &Storage,
+        client_id: u64,
+        key: &[u8],
+        op: BlockingOp,
+    ) -> tokio::task::JoinHandle<Result<Option<Popped>, CmdErrors>> {
+        let (client, key) = (storage.clone(), Bytes::copy_from_slice(key));
+        let task =
+            tokio::spawn(async move { client.blocking_pop(client_id, &[key], op, None).await });
+        while !storage
+            .shared
+            .state
+            .lock()
+            .await
+            .blocked
+            .waiters
+            .contains_key(&client_id)
+        {
+            tokio::task::yield_now().await;
+        }
+        task
+    }
+
+    fn list_move(destination: &'static [u8]) -> BlockingOp {
+        BlockingOp::ListMove {
+            from: ListEnd::Left,
+            destination: Bytes::from_static(destination),
+            to: ListEnd::Right,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocking_move_to_the_same_list() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"a");
+        let blocked = block_client(&storage, 1, b"a", list_move(b"a")).await;
+
+        let value = Bytes::from_static(b"x");
+        storage
+            .list_push(&key, std::slice::from_ref(&value), ListEnd::Left)
+            .await
+            .unwrap();
+        let popped = blocked.await.unwrap().unwrap().unwrap();
+        assert_eq!(popped.items, PoppedItems::Values(vec![value.clone()]));
+        assert_eq!(storage.list_range(&key, 0, -1).await, Ok(vec![value]));
+        assert!(storage.shared.state.lock().await.blocked.waiters.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_blocking_moves_in_a_cycle() {
+        let storage = Storage::setup();
+        let first = block_client(&storage, 1, b"a", list_move(b"b")).await;
+        let second = block_client(&storage, 2, b"b", list_move(b"a")).await;
+
+        let value = Bytes::from_static(b"x");
+        storage
+            .list_push(
+                &Bytes::from_static(b"a"),
+                std::slice::from_ref(&value),
+                ListEnd::Left,
+            )
+            .await
+            .unwrap();
+        for blocked in [first, second] {
+            let popped = blocked.await.unwrap().unwrap().unwrap();
+            assert_eq!(popped.items, PoppedItems::Values(vec![value.clone()]));
+        }
+        // moved from `a` to `b` and back
+        assert_eq!(
+            storage.list_range(&Bytes::from_static(b"a"), 0, -1).await,
+            Ok(vec![value])
+        );
+        let state = storage.shared.state.lock().await;
+        assert!(state.blocked.waiters.is_empty());
+        assert!(state.blocked.queues.is_empty());
+    }
+}
@@ -0,0 +1,517 @@
+use bytes::Bytes;
+
+use super::sorted_set::SortedSet;
+use super::{Entry, State, Storage, Value};
+use crate::redis::CmdErrors;
+
+// members are sorted set members scored with the 52 bits geohash of their position,
+// interleaving 26 bits of latitude and 26 bits of longitude like in Redis
+const GEO_STEP_MAX: u32 = 26;
+const GEO_LONG_MIN: f64 = -180.0;
+const GEO_LONG_MAX: f64 = 180.0;
+// limits of the web mercator projection
+const GEO_LAT_MIN: f64 = -85.051_128_78;
+const GEO_LAT_MAX: f64 = 85.051_128_78;
+// the standard geohash of `GEOHASH` covers every latitude
+const GEOHASH_LAT_LIMIT: f64 = 90.0;
+const GEOHASH_ALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+const EARTH_RADIUS_IN_METERS: f64 = 6_372_797.560_856;
+const MERCATOR_MAX: f64 = 20_037_726.37;
+
+/// Center of a search, a member of the sorted set or a position.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum GeoOrigin {
+    Member(Bytes),
+    Point { longitude: f64, latitude: f64 },
+}
+
+/// Area of a search around its center, in the unit of the query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum GeoShape {
+    Radius(f64),
+    Box { width: f64, height: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum GeoOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GeoQuery {
+    pub(crate) origin: GeoOrigin,
+    pub(crate) shape: GeoShape,
+    // meters in the unit of the shape and of the distances found
+    pub(crate) unit: f64,
+    pub(crate) order: Option<GeoOrder>,
+    pub(crate) count: Option<usize>,
+    // `ANY`: stop at the first `count` members found instead of the nearest ones
+    pub(crate) any: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GeoMatch {
+    pub(crate) member: Bytes,
+    // from the center of the search, in the unit of the query
+    pub(crate) distance: f64,
+    pub(crate) score: f64,
+    pub(crate) longitude: f64,
+    pub(crate) latitude: f64,
+}
+
+/// Geohash of `step` bits for each coordinate, latitude in the even bits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HashBits {
+    bits: u64,
+    step: u32,
+}
+
+/// Cell of a geohash, minimum and maximum of each coordinate.
+struct Area {
+    longitude: (f64, f64),
+    latitude: (f64, f64),
+}
+
+// the bits of `value` moved to the even positions
+fn interleave(value: u32) -> u64 {
+    let mut value = u64::from(value);
+    value = (value | (value << 16)) & 0x0000_ffff_0000_ffff;
+    value = (value | (value << 8)) & 0x00ff_00ff_00ff_00ff;
+    value = (value | (value << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    value = (value | (value << 2)) & 0x3333_3333_3333_3333;
+    (value | (value << 1)) & 0x5555_5555_5555_5555
+}
+
+// the even bits of `value` packed together
+fn deinterleave(value: u64) -> u32 {
+    let mut value = value & 0x5555_5555_5555_5555;
+    value = (value | (value >> 1)) & 0x3333_3333_3333_3333;
+    value = (value | (value >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    value = (value | (value >> 4)) & 0x00ff_00ff_00ff_00ff;
+    value = (value | (value >> 8)) & 0x0000_ffff_0000_ffff;
+    ((value | (value >> 16)) & 0x0000_0000_ffff_ffff) as u32
+}
+
+fn encode(longitude: f64, latitude: f64, step: u32, lat_limit: f64) -> HashBits {
+    let cells = (1u64 << step) as f64;
+    let lat_offset = (latitude + lat_limit) / (2.0 * lat_limit) * cells;
+    let long_offset = (longitude - GEO_LONG_MIN) / (GEO_LONG_MAX - GEO_LONG_MIN) * cells;
+    HashBits {
+        bits: interleave(lat_offset as u32) | (interleave(long_offset as u32) << 1),
+        step,
+    }
+}
+
+fn area(hash: HashBits) -> Area {
+    let cells = (1u64 << hash.step) as f64;
+    let lat_cell = f64::from(deinterleave(hash.bits));
+    let long_cell = f64::from(deinterleave(hash.bits >> 1));
+    let lat_scale = GEO_LAT_MAX - GEO_LAT_MIN;
+    let long_scale = GEO_LONG_MAX - GEO_LONG_MIN;
+    Area {
+        longitude: (
+            GEO_LONG_MIN + long_cell / cells * long_scale,
+            GEO_LONG_MIN + (long_cell + 1.0) / cells * long_scale,
+        ),
+        latitude: (
+            GEO_LAT_MIN + lat_cell / cells * lat_scale,
+            GEO_LAT_MIN + (lat_cell + 1.0) / cells * lat_scale,
+        ),
+    }
+}
+
+/// Position of a member, the center of the cell of its score.
+fn decode(score: f64) -> (f64, f64) {
+    let area = area(HashBits {
+        bits: score as u64,
+        step: GEO_STEP_MAX,
+    });
+    (
+        ((area.longitude.0 + area.longitude.1) / 2.0).clamp(GEO_LONG_MIN, GEO_LONG_MAX),
+        ((area.latitude.0 + area.latitude.1) / 2.0).clamp(GEO_LAT_MIN, GEO_LAT_MAX),
+    )
+}
+
+/// Whether the position is within the coordinates Redis can index.
+pub(crate) fn geo_valid(longitude: f64, latitude: f64) -> bool {
+    (GEO_LONG_MIN..=GEO_LONG_MAX).contains(&longitude)
+        && (GEO_LAT_MIN..=GEO_LAT_MAX).contains(&latitude)
+}
+
+/// Score of a valid position.
+pub(crate) fn geo_score(longitude: f64, latitude: f64) -> f64 {
+    encode(longitude, latitude, GEO_STEP_MAX, GEO_LAT_MAX).bits as f64
+}
+
+/// The 11 characters standard geohash of a position, the last one is always `0`
+/// since scores only hold 52 bits.
+pub(crate) fn geohash(longitude: f64, latitude: f64) -> String {
+    let hash = encode(longitude, latitude, GEO_STEP_MAX, GEOHASH_LAT_LIMIT);
+    (0..11)
+        .map(|index| {
+            let digit = match index {
+                10 => 0,
+                _ => (hash.bits >> (52 - (index + 1) * 5)) & 0x1f,
+            };
+            char::from(GEOHASH_ALPHABET[digit as usize])
+        })
+        .collect()
+}
+
+/// Distance in meters on the earth, with the haversine formula.
+pub(crate) fn geo_distance(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (long1, lat1) = (from.0.to_radians(), from.1.to_radians());
+    let (long2, lat2) = (to.0.to_radians(), to.1.to_radians());
+    let u = ((lat2 - lat1) / 2.0).sin();
+    let v = ((long2 - long1) / 2.0).sin();
+    let a = u * u + lat1.cos() * lat2.cos() * v * v;
+    2.0 * EARTH_RADIUS_IN_METERS * a.sqrt().asin()
+}
+
+/// Distance of the point from the center when it is in the shape, all in meters.
+fn distance_in_shape(shape: GeoShape, center: (f64, f64), point: (f64, f64)) -> Option<f64> {
+    match shape {
+        GeoShape::Radius(radius) => Some(geo_distance(center, point)).filter(|d| *d <= radius),
+        GeoShape::Box { width, height } => {
+            // the latitude distance is cheaper, so it is checked first
+            let lat_distance =
+                EARTH_RADIUS_IN_METERS * (point.1.to_radians() - center.1.to_radians()).abs();
+            if lat_distance > height / 2.0 {
+                return None;
+            }
+            let long_distance = geo_distance((center.0, point.1), point);
+            if long_distance > width / 2.0 {
+                return None;
+            }
+            Some(geo_distance(center, point))
+        }
+    }
+}
+
+/// Bits of the geohash cells small enough for the search not to scan much more
+/// than the radius, and big enough for the cell and its neighbors to cover it.
+fn estimate_steps(mut radius: f64, latitude: f64) -> u32 {
+    if radius == 0.0 {
+        return GEO_STEP_MAX;
+    }
+    let mut step: i32 = 1;
+    while radius < MERCATOR_MAX {
+        radius *= 2.0;
+        step += 1;
+    }
+    // make sure the range is included in most of the base cases
+    step -= 2;
+    // cells are narrower near the poles
+    if !(-66.0..=66.0).contains(&latitude) {
+        step -= 1;
+        if !(-80.0..=80.0).contains(&latitude) {
+            step -= 1;
+        }
+    }
+    step.clamp(1, GEO_STEP_MAX as i32) as u32
+}
+
+// `direction` is `1` or `-1`, longitude is in the odd bits and latitude in the even ones
+fn moved(hash: HashBits, long_direction: i8, lat_direction: i8) -> HashBits {
+    let shift = 64 - hash.step * 2;
+    let mut bits = hash.bits;
+    for (direction, moving) in [
+        (long_direction, 0xaaaa_aaaa_aaaa_aaaa_u64),
+        (lat_direction, 0x5555_5555_5555_5555_u64),
+    ] {
+        if direction == 0 {
+            continue;
+        }
+        let mut coordinate = bits & moving;
+        let kept = bits & !moving;
+        // ones everywhere but in the bits of the moving coordinate, so carries
+        // and borrows go through them
+        let others = !moving >> shift;
+        coordinate = match direction > 0 {
+            true => coordinate.wrapping_add(others + 1),
+            false => (coordinate | others).wrapping_sub(others + 1),
+        };
+        bits = (coordinate & (moving >> shift)) | kept;
+    }
+    HashBits {
+        bits,
+        step: hash.step,
+    }
+}
+
+/// Cells covering the shape: the one of the center first and its 8 neighbors, the
+/// ones out of the shape skipped.
+fn search_cells(shape: GeoShape, center: (f64, f64)) -> Vec<HashBits> {
+    let (longitude, latitude) = center;
+    let (half_width, half_height, radius) = match shape {
+        GeoShape::Radius(radius) => (radius, radius, radius),
+        GeoShape::Box { width, height } => {
+            (width / 2.0, height / 2.0, (width / 2.0).hypot(height / 2.0))
+        }
+    };
+
+    // bounding box of the shape
+    let lat_delta = (half_height / EARTH_RADIUS_IN_METERS).to_degrees();
+    // the side nearer to the equator is the widest one
+    let edge_latitude = match latitude < 0.0 {
+        true => latitude - lat_delta,
+        false => latitude + lat_delta,
+    };
+    let long_delta =
+        (half_width / EARTH_RADIUS_IN_METERS / edge_latitude.to_radians().cos()).to_degrees();
+    let (min_long, max_long) = (longitude - long_delta, longitude + long_delta);
+    let (min_lat, max_lat) = (latitude - lat_delta, latitude + lat_delta);
+
+    let mut step = estimate_steps(radius, latitude);
+    let mut hash = encode(longitude, latitude, step, GEO_LAT_MAX);
+    // near the edges of the cell its neighbors may not reach the whole shape
+    let too_small = area(moved(hash, 0, 1)).latitude.1 < max_lat
+        || area(moved(hash, 0, -1)).latitude.0 > min_lat
+        || area(moved(hash, 1, 0)).longitude.1 < max_long
+        || area(moved(hash, -1, 0)).longitude.0 > min_long;
+    if step > 1 && too_small {
+        step -= 1;
+        hash = encode(longitude, latitude, step, GEO_LAT_MAX);
+    }
+
+    let cell = area(hash);
+    // the neighbors past the shape are useless, unless cells are huge
+    let useful = |long_direction: i8, lat_direction: i8| {
+        step < 2
+            || !((lat_direction < 0 && cell.latitude.0 < min_lat)
+                || (lat_direction > 0 && cell.latitude.1 > max_lat)
+                || (long_direction < 0 && cell.longitude.0 < min_long)
+                || (long_direction > 0 && cell.longitude.1 > max_long))
+    };
+
+    let mut cells: Vec<HashBits> = vec![];
+    // the order of Redis: center, north, south, east, west and the corners
+    for (long_direction, lat_direction) in [
+        (0, 0),
+        (0, 1),
+        (0, -1),
+        (1, 0),
+        (-1, 0),
+        (1, 1),
+        (-1, 1),
+        (1, -1),
+        (-1, -1),
+    ] {
+        if !useful(long_direction, lat_direction) {
+            continue;
+        }
+        let neighbor = moved(hash, long_direction, lat_direction);
+        // with huge radiuses neighbors can be the same cell
+        if cells.last() != Some(&neighbor) {
+            cells.push(neighbor);
+        }
+    }
+    cells
+}
+
+impl State {
+    fn geo_search(&mut self, key: &Bytes, query: &GeoQuery) -> Result<Vec<GeoMatch>, CmdErrors> {
+        let Some(zset) = self.get_value::<SortedSet>(key)? else {
+            return Ok(vec![]);
+        };
+        let center = match &query.origin {
+            GeoOrigin::Member(member) => {
+                decode(zset.score(member).ok_or(CmdErrors::GeoMemberNotFound)?)
+            }
+            GeoOrigin::Point {
+                longitude,
+                latitude,
+            } => (*longitude, *latitude),
+        };
+        let shape = match query.shape {
+            GeoShape::Radius(radius) => GeoShape::Radius(radius * query.unit),
+            GeoShape::Box { width, height } => GeoShape::Box {
+                width: width * query.unit,
+                height: height * query.unit,
+            },
+        };
+        let limit = match query.any {
+            true => query.count,
+            false => None,
+        };
+
+        let mut matches = vec![];
+        'cells: for cell in search_cells(shape, center) {
+            let shift = 52 - cell.step * 2;
+            let min = (cell.bits << shift) as f64;
+            let max = ((cell.bits + 1) << shift) as f64;
+            for (member, score) in zset.score_range(min, max) {
+                if limit.is_some_and(|limit| matches.len() >= limit) {
+                    break 'cells;
+                }
+                let point = decode(score);
+                if let Some(distance) = distance_in_shape(shape, center, point) {
+                    matches.push(GeoMatch {
+                        member,
+                        distance: distance / query.unit,
+                        score,
+                        longitude: point.0,
+                        latitude: point.1,
+                    });
+                }
+            }
+        }
+
+        // the nearest ones are the ones `COUNT` keeps, unless any will do
+        let order = match (query.order, query.count) {
+            (None, Some(_)) if !query.any => Some(GeoOrder::Asc),
+            (order, _) => order,
+        };
+        match order {
+            Some(GeoOrder::Asc) => matches.sort_by(|a, b| a.distance.total_cmp(&b.distance)),
+            Some(GeoOrder::Desc) => matches.sort_by(|a, b| b.distance.total_cmp(&a.distance)),
+            None => {}
+        }
+        if let Some(count) = query.count {
+            matches.truncate(count);
+        }
+        Ok(matches)
+    }
+}
+
+impl Storage {
+    /// Positions of the members, `None` for missing ones.
+    pub(crate) async fn geo_positions(
+        &self,
+        key: &Bytes,
+        members: &[Bytes],
+    ) -> Result<Vec<Option<(f64, f64)>>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let zset = state.get_value::<SortedSet>(key)?;
+        Ok(members
+            .iter()
+            .map(|member| zset.and_then(|zset| zset.score(member)).map(decode))
+            .collect())
+    }
+
+    /// Members in the shape of the query around its origin. A missing key has no
+    /// members, a missing origin member is an error.
+    pub(crate) async fn geo_search(
+        &self,
+        key: &Bytes,
+        query: &GeoQuery,
+    ) -> Result<Vec<GeoMatch>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        state.geo_search(key, query)
+    }
+
+    /// Stores the members found in `destination` with their geohash, or with their
+    /// distance when `store_distance` is set. An empty result deletes `destination`.
+    /// Returns how many members were stored.
+    pub(crate) async fn geo_search_store(
+        &self,
+        destination: &Bytes,
+        key: &Bytes,
+        query: &GeoQuery,
+        store_distance: bool,
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let matches = state.geo_search(key, query)?;
+        if matches.is_empty() {
+            state.remove(destination);
+            return Ok(0);
+        }
+
+        let mut zset = SortedSet::default();
+        for found in matches {
+            let score = match store_distance {
+                true => found.distance,
+                false => found.score,
+            };
+            zset.insert(found.member, score);
+        }
+        let len = zset.len();
+        state.insert(
+            destination.clone(),
+            Entry {
+                value: Value::SortedSet(zset),
+                expires_at: None,
+            },
+        );
+        state.wake_blocked(destination);
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geohash_encoding() {
+        // Palermo, as Redis scores and shows it
+        let score = geo_score(13.361389, 38.115556);
+        assert_eq!(score, 3479099956230698.0);
+        let (longitude, latitude) = decode(score);
+        assert!((longitude - 13.361389).abs() < 1e-5);
+        assert!((latitude - 38.115556).abs() < 1e-5);
+        assert_eq!(geohash(longitude, latitude), "sqc8b49rny0");
+
+        assert!(!geo_valid(181.0, 0.0));
+        assert!(!geo_valid(0.0, 86.0));
+        for value in [0, 1, 0x1234_5678, u32::MAX] {
+            assert_eq!(deinterleave(interleave(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_neighbor_cells() {
+        let hash = encode(13.361389, 38.115556, 10, GEO_LAT_MAX);
+        let cell = area(hash);
+        let north = area(moved(hash, 0, 1));
+        assert_eq!(north.latitude.0, cell.latitude.1);
+        assert_eq!(north.longitude, cell.longitude);
+        let south_west = area(moved(hash, -1, -1));
+        assert_eq!(south_west.latitude.1, cell.latitude.0);
+        assert_eq!(south_west.longitude.1, cell.longitude.0);
+    }
+
+    #[tokio::test]
+    async fn test_geo_search_count() {
+        let storage = Storage::setup();
+        let key = Bytes::from("places");
+        let pairs: Vec<(f64, Bytes)> = (0..10)
+            .map(|n| {
+                let score = geo_score(13.0 + f64::from(n) / 100.0, 38.0);
+                (score, Bytes::from(format!("place{n}")))
+            })
+            .collect();
+        storage
+            .zset_add(&key, &pairs, Default::default())
+            .await
+            .unwrap();
+
+        let mut query = GeoQuery {
+            origin: GeoOrigin::Point {
+                longitude: 13.095,
+                latitude: 38.0,
+            },
+            shape: GeoShape::Radius(100.0),
+            unit: 1000.0,
+            order: None,
+            count: Some(3),
+            any: false,
+        };
+        let nearest = storage.geo_search(&key, &query).await.unwrap();
+        let members: Vec<_> = nearest.iter().map(|found| &found.member[..]).collect();
+        assert_eq!(members, [&b"place9"[..], b"place8", b"place7"]);
+
+        query.any = true;
+        assert_eq!(storage.geo_search(&key, &query).await.unwrap().len(), 3);
+        query.count = None;
+        assert_eq!(storage.geo_search(&key, &query).await.unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_distance() {
+        // Palermo to Catania, 166274.1516 meters in Redis
+        let distance = geo_distance((13.361389, 38.115556), (15.087269, 37.502669));
+        assert!((distance - 166274.15).abs() < 1.0, "{distance}");
+    }
+}
@@ -0,0 +1,514 @@
+use std::collections::{BTreeSet, HashMap};
+use std::time::SystemTime;
+
+use bytes::Bytes;
+
+use super::{Entry, ExpireCondition, KeyExpiry, KeySet, State, Storage, Value};
+use crate::redis::{random, CmdErrors};
+
+/// Hash with optional deadlines on single fields.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Hash {
+    fields: HashMap<Bytes, HashField>,
+    // the same fields by position, so random ones are picked without a scan
+    names: KeySet,
+    // deadlines in the order they come, so expired fields are found without a scan
+    deadlines: BTreeSet<(SystemTime, Bytes)>,
+}
+
+#[derive(Debug, Clone)]
+struct HashField {
+    value: Bytes,
+    expires_at: Option<SystemTime>,
+}
+
+impl Hash {
+    pub(super) fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub(super) fn has_deadlines(&self) -> bool {
+        !self.deadlines.is_empty()
+    }
+
+    fn get(&self, field: &Bytes) -> Option<&Bytes> {
+        self.fields.get(field).map(|field| &field.value)
+    }
+
+    /// Field at `index`, which must be in `0..len`, and its value.
+    fn field(&self, index: usize) -> (&Bytes, &Bytes) {
+        let field = self.names.get(index);
+        (field, &self.fields[field].value)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&Bytes, &Bytes)> {
+        self.fields
+            .iter()
+            .map(|(field, hash_field)| (field, &hash_field.value))
+    }
+
+    /// Writes a new value and drops the deadline of the field, like `HSET` does.
+    /// Returns whether the field is new.
+    fn insert(&mut self, field: Bytes, value: Bytes) -> bool {
+        let old = self.fields.insert(
+            field.clone(),
+            HashField {
+                value,
+                expires_at: None,
+            },
+        );
+        match old {
+            Some(old) => {
+                if let Some(at) = old.expires_at {
+                    self.deadlines.remove(&(at, field));
+                }
+                false
+            }
+            None => {
+                self.names.insert(&field);
+                true
+            }
+        }
+    }
+
+    /// Writes a new value and keeps the deadline of the field, like increments do.
+    fn update(&mut self, field: &Bytes, value: Bytes) {
+        match self.fields.get_mut(field) {
+            Some(hash_field) => hash_field.value = value,
+            None => {
+                self.insert(field.clone(), value);
+            }
+        }
+    }
+
+    fn remove(&mut self, field: &Bytes) -> bool {
+        let Some(removed) = self.fields.remove(field) else {
+            return false;
+        };
+        self.names.remove(field);
+        if let Some(at) = removed.expires_at {
+            self.deadlines.remove(&(at, field.clone()));
+        }
+        true
+    }
+
+    fn expiry(&self, field: &Bytes) -> KeyExpiry {
+        match self.fields.get(field) {
+            None => KeyExpiry::Missing,
+            Some(HashField {
+                expires_at: None, ..
+            }) => KeyExpiry::Persistent,
+            Some(HashField {
+                expires_at: Some(at),
+                ..
+            }) => KeyExpiry::At(*at),
+        }
+    }
+
+    fn set_expiry(&mut self, field: &Bytes, expires_at: Option<SystemTime>) {
+        let Some(hash_field) = self.fields.get_mut(field) else {
+            return;
+        };
+        if let Some(at) = hash_field.expires_at {
+            self.deadlines.remove(&(at, field.clone()));
+        }
+        if let Some(at) = expires_at {
+            self.deadlines.insert((at, field.clone()));
+        }
+        hash_field.expires_at = expires_at;
+    }
+
+    /// Deletes fields whose deadline has come, returns how many.
+    pub(super) fn remove_expired(&mut self, now: SystemTime) -> u64 {
+        let mut removed = 0;
+        while self.deadlines.first().is_some_and(|(at, _)| *at <= now) {
+            if let Some((_, field)) = self.deadlines.pop_first() {
+                self.fields.remove(&field);
+                self.names.remove(&field);
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
+
+/// What `HEXPIRE` did with one field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum FieldExpire {
+    NoSuchField,
+    ConditionNotMet,
+    Set,
+    // the deadline was not in the future
+    Deleted,
+}
+
+impl Storage {
+    /// Returns how many of the fields are new.
+    pub(crate) async fn hash_set(
+        &self,
+        key: &Bytes,
+        pairs: &[(Bytes, Bytes)],
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let hash = state.live_hash_or_insert(key)?;
+        Ok(pairs
+            .iter()
+            .filter(|(field, value)| hash.insert(field.clone(), value.clone()))
+            .count())
+    }
+
+    /// Returns `false` when the field already exists and nothing was written.
+    pub(crate) async fn hash_set_if_missing(
+        &self,
+        key: &Bytes,
+        field: &Bytes,
+        value: &Bytes,
+    ) -> Result<bool, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let hash = state.live_hash_or_insert(key)?;
+        if hash.get(field).is_some() {
+            return Ok(false);
+        }
+        hash.insert(field.clone(), value.clone());
+        Ok(true)
+    }
+
+    /// Values of the fields in the same order, `None` for missing ones.
+    pub(crate) async fn hash_get(
+        &self,
+        key: &Bytes,
+        fields: &[Bytes],
+    ) -> Result<Vec<Option<Bytes>>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let hash = state.live_hash(key)?;
+        Ok(fields
+            .iter()
+            .map(|field| hash.as_ref().and_then(|hash| hash.get(field)).cloned())
+            .collect())
+    }
+
+    /// Returns how many of the fields existed.
+    pub(crate) async fn hash_delete(
+        &self,
+        key: &Bytes,
+        fields: &[Bytes],
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(hash) = state.live_hash(key)? else {
+            return Ok(0);
+        };
+        let deleted = fields.iter().filter(|field| hash.remove(field)).count();
+        state.remove_if_empty(key);
+        Ok(deleted)
+    }
+
+    pub(crate) async fn hash_get_all(&self, key: &Bytes) -> Result<Vec<(Bytes, Bytes)>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let hash = state.live_hash(key)?;
+        Ok(hash
+            .into_iter()
+            .flat_map(|hash| hash.iter())
+            .map(|(field, value)| (field.clone(), value.clone()))
+            .collect())
+    }
+
+    pub(crate) async fn hash_len(&self, key: &Bytes) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state.live_hash(key)?.map_or(0, |hash| hash.len()))
+    }
+
+    /// Replaces the value of the field with what `update` makes of the current one,
+    /// all under one lock, so concurrent increments never lose updates.
+    pub(crate) async fn hash_update(
+        &self,
+        key: &Bytes,
+        field: &Bytes,
+        update: impl FnOnce(Option<&Bytes>) -> Result<Bytes, CmdErrors>,
+    ) -> Result<(), CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        // the key isn't created when the update fails
+        let current = state.live_hash(key)?.and_then(|hash| hash.get(field));
+        let value = update(current)?;
+        state.live_hash_or_insert(key)?.update(field, value);
+        Ok(())
+    }
+
+    /// Random fields and their values, `count` works as in `random::sample`.
+    pub(crate) async fn hash_random_fields(
+        &self,
+        key: &Bytes,
+        count: i64,
+    ) -> Result<Vec<(Bytes, Bytes)>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(hash) = state.live_hash(key)? else {
+            return Ok(vec![]);
+        };
+        random::sample(hash.len(), count, |index| {
+            let (field, value) = hash.field(index);
+            (field.clone(), value.clone())
+        })
+    }
+
+    /// Sets deadlines of single fields, a deadline in the past deletes the field.
+    pub(crate) async fn hash_expire(
+        &self,
+        key: &Bytes,
+        fields: &[Bytes],
+        at: SystemTime,
+        condition: ExpireCondition,
+    ) -> Result<Vec<FieldExpire>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(hash) = state.live_hash(key)? else {
+            return Ok(vec![FieldExpire::NoSuchField; fields.len()]);
+        };
+
+        let now = SystemTime::now();
+        let results: Vec<FieldExpire> = fields
+            .iter()
+            .map(|field| {
+                let current = match hash.expiry(field) {
+                    KeyExpiry::Missing => return FieldExpire::NoSuchField,
+                    KeyExpiry::Persistent => None,
+                    KeyExpiry::At(at) => Some(at),
+                };
+                if !condition.allows(current, at) {
+                    FieldExpire::ConditionNotMet
+                } else if at <= now {
+                    hash.remove(field);
+                    FieldExpire::Deleted
+                } else {
+                    hash.set_expiry(field, Some(at));
+                    FieldExpire::Set
+                }
+            })
+            .collect();
+
+        if results.contains(&FieldExpire::Set) {
+            state.volatile_hashes.insert(key);
+        }
+        state.remove_if_empty(key);
+        Ok(results)
+    }
+
+    pub(crate) async fn hash_expiry(
+        &self,
+        key: &Bytes,
+        fields: &[Bytes],
+    ) -> Result<Vec<KeyExpiry>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let hash = state.live_hash(key)?;
+        Ok(fields
+            .iter()
+            .map(|field| {
+                hash.as_ref()
+                    .map_or(KeyExpiry::Missing, |hash| hash.expiry(field))
+            })
+            .collect())
+    }
+
+    /// Removes deadlines of the fields, returns what they were before.
+    pub(crate) async fn hash_persist(
+        &self,
+        key: &Bytes,
+        fields: &[Bytes],
+    ) -> Result<Vec<KeyExpiry>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(hash) = state.live_hash(key)? else {
+            return Ok(vec![KeyExpiry::Missing; fields.len()]);
+        };
+        Ok(fields
+            .iter()
+            .map(|field| {
+                let expiry = hash.expiry(field);
+                hash.set_expiry(field, None);
+                expiry
+            })
+            .collect())
+    }
+}
+
+impl State {
+    /// Hash of the key with expired fields deleted first. A hash whose fields
+    /// all expired is gone together with its key.
+    fn live_hash(&mut self, key: &Bytes) -> Result<Option<&mut Hash>, CmdErrors> {
+        let expired = match self.get_value_mut::<Hash>(key)? {
+            Some(hash) => hash.remove_expired(SystemTime::now()),
+            None => return Ok(None),
+        };
+        if expired > 0 {
+            self.stats.expired_subkeys += expired;
+            self.remove_if_empty(key);
+        }
+        self.get_value_mut::<Hash>(key)
+    }
+
+    fn live_hash_or_insert(&mut self, key: &Bytes) -> Result<&mut Hash, CmdErrors> {
+        self.live_hash(key)?;
+        self.get_or_insert_value::<Hash>(key)
+    }
+
+    /// Checks up to `count` random hashes with field deadlines and deletes expired
+    /// fields, returns how many hashes were checked and how many had expired fields.
+    pub(super) fn expire_random_hash_fields(&mut self, count: usize) -> (usize, usize) {
+        let now = SystemTime::now();
+        let sampled = count.min(self.volatile_hashes.len());
+        let mut expired = 0;
+
+        for _ in 0..sampled {
+            let Some(key) = self.volatile_hashes.random().cloned() else {
+                break;
+            };
+            let Some(Entry {
+                value: Value::Hash(hash),
+                ..
+            }) = self.entries.get_mut(&key)
+            else {
+                self.volatile_hashes.remove(&key);
+                continue;
+            };
+
+            let removed = hash.remove_expired(now);
+            // deadlines could also be gone because of `HPERSIST` or `HSET`
+            if !hash.has_deadlines() {
+                self.volatile_hashes.remove(&key);
+            }
+            if removed > 0 {
+                self.stats.expired_subkeys += removed;
+                self.remove_if_empty(&key);
+                expired += 1;
+            }
+        }
+
+        (sampled, expired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_hash_update() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"hash");
+        let field = Bytes::from_static(b"field");
+
+        let failed = storage
+            .hash_update(&key, &field, |_| Err(CmdErrors::NotAnInteger))
+            .await;
+        assert_eq!(failed, Err(CmdErrors::NotAnInteger));
+        assert_eq!(storage.key_type(&key).await, None);
+
+        for _ in 0..2 {
+            storage
+                .hash_update(&key, &field, |current| {
+                    let mut value = current.cloned().unwrap_or_default().to_vec();
+                    value.push(b'x');
+                    Ok(Bytes::from(value))
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            storage.hash_get(&key, &[field]).await,
+            Ok(vec![Some(Bytes::from_static(b"xx"))])
+        );
+    }
+
+    // moves the deadline of a field into the past, `HEXPIRE` would delete it right away
+    async fn pass_deadline(storage: &Storage, key: &Bytes, field: &Bytes) {
+        let mut state = storage.shared.state.lock().await;
+        let hash = state.get_value_mut::<Hash>(key).unwrap().unwrap();
+        hash.set_expiry(field, Some(SystemTime::now() - Duration::from_secs(1)));
+    }
+
+    #[tokio::test]
+    async fn test_hash_field_expiry() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"flags");
+        let (old, new) = (Bytes::from_static(b"old"), Bytes::from_static(b"new"));
+        let val = Bytes::from_static(b"1");
+        storage
+            .hash_set(
+                &key,
+                &[(old.clone(), val.clone()), (new.clone(), val.clone())],
+            )
+            .await
+            .unwrap();
+
+        let soon = SystemTime::now() + Duration::from_secs(50);
+        let later = SystemTime::now() + Duration::from_secs(100);
+        let fields = [old.clone(), new.clone(), Bytes::from_static(b"missing")];
+        assert_eq!(
+            storage
+                .hash_expire(&key, &fields, soon, ExpireCondition::Always)
+                .await,
+            Ok(vec![
+                FieldExpire::Set,
+                FieldExpire::Set,
+                FieldExpire::NoSuchField
+            ])
+        );
+        assert_eq!(
+            storage
+                .hash_expire(&key, &fields[1..2], later, ExpireCondition::LessThan)
+                .await,
+            Ok(vec![FieldExpire::ConditionNotMet])
+        );
+        assert_eq!(
+            storage.hash_persist(&key, &fields[1..]).await,
+            Ok(vec![KeyExpiry::At(soon), KeyExpiry::Missing])
+        );
+
+        // a field with a deadline is gone on the next read, lazily
+        pass_deadline(&storage, &key, &old).await;
+        assert_eq!(storage.hash_len(&key).await, Ok(1));
+        assert_eq!(storage.info().await.stats.expired_subkeys, 1);
+
+        // and the active cycle reclaims fields nobody reads
+        storage
+            .hash_expire(&key, &fields[1..2], soon, ExpireCondition::Always)
+            .await
+            .unwrap();
+        storage.active_expire_cycle().await;
+        assert_eq!(storage.info().await.stats.expired_subkeys, 1);
+        pass_deadline(&storage, &key, &new).await;
+        storage.active_expire_cycle().await;
+        let info = storage.info().await;
+        assert_eq!(info.stats.expired_subkeys, 2);
+        assert_eq!(info.keys, 0);
+    }
+
+    #[tokio::test]
+    async fn test_hash_random_fields() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"sessions");
+        let pairs: Vec<(Bytes, Bytes)> = (0..100)
+            .map(|id| {
+                (
+                    Bytes::from(format!("session:{id}")),
+                    Bytes::from(id.to_string()),
+                )
+            })
+            .collect();
+        storage.hash_set(&key, &pairs).await.unwrap();
+        let removed: Vec<Bytes> = pairs[..50].iter().map(|(field, _)| field.clone()).collect();
+        storage.hash_delete(&key, &removed).await.unwrap();
+
+        let mut sampled = storage.hash_random_fields(&key, 20).await.unwrap();
+        sampled.sort();
+        sampled.dedup();
+        assert_eq!(sampled.len(), 20);
+        // removed fields are never picked, and every field comes with its own value
+        assert!(sampled.iter().all(|pair| pairs[50..].contains(pair)));
+        assert_eq!(
+            storage.hash_random_fields(&key, 100).await.unwrap().len(),
+            50
+        );
+    }
+}
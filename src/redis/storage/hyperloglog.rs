@@ -0,0 +1,513 @@
+use bytes::{Bytes, BytesMut};
+
+use super::Storage;
+use crate::redis::CmdErrors;
+
+// the layout is the one of Redis, so values can be dumped and restored across both:
+// a 16 bytes header and either 6 bits per register or the sparse opcodes
+const HLL_MAGIC: &[u8] = b"HYLL";
+const HLL_HEADER_SIZE: usize = 16;
+const HLL_DENSE: u8 = 0;
+const HLL_SPARSE: u8 = 1;
+const HLL_P: u32 = 14;
+const HLL_REGISTERS: usize = 1 << HLL_P;
+const HLL_BITS: usize = 6;
+const HLL_DENSE_SIZE: usize = HLL_HEADER_SIZE + (HLL_REGISTERS * HLL_BITS).div_ceil(8);
+// bits of the hash left for the run of zeros once the register index is taken
+const HLL_Q: usize = 64 - HLL_P as usize;
+const HLL_ALPHA_INF: f64 = 0.721_347_520_444_481_7;
+const HLL_HASH_SEED: u64 = 0xadc8_3b19;
+// sparse values are promoted to dense past this size, like with Redis default
+// `hll-sparse-max-bytes`
+const HLL_SPARSE_MAX_BYTES: usize = 3000;
+// sparse opcodes can't hold bigger values
+const HLL_SPARSE_VAL_MAX: u8 = 32;
+const HLL_SPARSE_ZERO_MAX_LEN: usize = 64;
+const HLL_SPARSE_VAL_MAX_LEN: usize = 4;
+
+/// MurmurHash2 with 64 bits output, reading the data as little endian words so the
+/// hash is the same on every platform.
+fn murmur_hash64a(data: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4_a793_5bd1_e995;
+    const R: u32 = 47;
+
+    let mut hash = seed ^ (data.len() as u64).wrapping_mul(M);
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let mut k = u64::from_le_bytes(chunk.try_into().unwrap());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        hash ^= k;
+        hash = hash.wrapping_mul(M);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (index, byte) in tail.iter().enumerate() {
+            hash ^= u64::from(*byte) << (8 * index);
+        }
+        hash = hash.wrapping_mul(M);
+    }
+
+    hash ^= hash >> R;
+    hash = hash.wrapping_mul(M);
+    hash ^ (hash >> R)
+}
+
+/// Register of the element and the length of the run of zeros after the index bits,
+/// plus one.
+fn pattern(element: &[u8]) -> (usize, u8) {
+    let hash = murmur_hash64a(element, HLL_HASH_SEED);
+    let index = hash as usize & (HLL_REGISTERS - 1);
+    // the bit past the run makes it end before the bits run out
+    let run = (hash >> HLL_P) | (1 << HLL_Q);
+    (index, run.trailing_zeros() as u8 + 1)
+}
+
+// registers are packed from the least significant bit of each byte
+fn dense_get(registers: &[u8], index: usize) -> u8 {
+    let (byte, shift) = (index * HLL_BITS / 8, index * HLL_BITS % 8);
+    let low = u16::from(registers[byte]);
+    // the last register doesn't spill into a next byte
+    let high = u16::from(registers.get(byte + 1).copied().unwrap_or(0));
+    (((low | (high << 8)) >> shift) & 0x3f) as u8
+}
+
+fn dense_set(registers: &mut [u8], index: usize, value: u8) {
+    let (byte, shift) = (index * HLL_BITS / 8, index * HLL_BITS % 8);
+    registers[byte] &= !(0x3f << shift);
+    registers[byte] |= value << shift;
+    if shift > 2 {
+        registers[byte + 1] &= !(0x3f >> (8 - shift));
+        registers[byte + 1] |= value >> (8 - shift);
+    }
+}
+
+/// Whether the string is a dense HyperLogLog, those are updated in place.
+fn is_dense(string: &[u8]) -> bool {
+    string.len() == HLL_DENSE_SIZE && string.starts_with(HLL_MAGIC) && string[4] == HLL_DENSE
+}
+
+/// Adds the elements to the registers of a dense value in its own buffer, the way
+/// Redis does. Returns whether a register changed.
+fn dense_add(string: &mut Bytes, elements: &[Bytes]) -> bool {
+    // no copy unless someone else holds the value, like a reply being written
+    let mut dense = BytesMut::from(std::mem::take(string));
+    let registers = &mut dense[HLL_HEADER_SIZE..];
+    let mut updated = false;
+    for element in elements {
+        let (index, count) = pattern(element);
+        if dense_get(registers, index) < count {
+            dense_set(registers, index, count);
+            updated = true;
+        }
+    }
+    if updated {
+        // top bit of the cached cardinality
+        dense[HLL_HEADER_SIZE - 1] |= 0x80;
+    }
+    *string = dense.freeze();
+    updated
+}
+
+/// A HyperLogLog read from a string value, with its registers unpacked.
+struct HyperLogLog {
+    dense: bool,
+    // cardinality of the last count, little endian, invalid with the top bit set
+    cache: [u8; 8],
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    fn new() -> HyperLogLog {
+        HyperLogLog {
+            dense: false,
+            cache: [0; 8],
+            registers: vec![0; HLL_REGISTERS],
+        }
+    }
+
+    fn parse(string: &[u8]) -> Result<HyperLogLog, CmdErrors> {
+        if string.len() < HLL_HEADER_SIZE || &string[..4] != HLL_MAGIC {
+            return Err(CmdErrors::NotAHyperLogLog);
+        }
+        let dense = match string[4] {
+            HLL_DENSE if string.len() == HLL_DENSE_SIZE => true,
+            HLL_SPARSE => false,
+            _ => return Err(CmdErrors::NotAHyperLogLog),
+        };
+        let mut hll = HyperLogLog {
+            dense,
+            cache: string[8..HLL_HEADER_SIZE].try_into().unwrap(),
+            registers: vec![0; HLL_REGISTERS],
+        };
+        let body = &string[HLL_HEADER_SIZE..];
+        match dense {
+            true => {
+                for index in 0..HLL_REGISTERS {
+                    hll.registers[index] = dense_get(body, index);
+                }
+            }
+            false => hll.parse_sparse(body)?,
+        }
+        Ok(hll)
+    }
+
+    /// The opcodes must cover every register exactly.
+    fn parse_sparse(&mut self, mut body: &[u8]) -> Result<(), CmdErrors> {
+        let mut index = 0;
+        while let Some((&opcode, rest)) = body.split_first() {
+            body = rest;
+            let (value, len) = match opcode {
+                // ZERO: 00xxxxxx
+                _ if opcode & 0xc0 == 0 => (0, usize::from(opcode & 0x3f) + 1),
+                // XZERO: 01xxxxxx yyyyyyyy
+                _ if opcode & 0xc0 == 0x40 => {
+                    let (&low, rest) = body.split_first().ok_or(CmdErrors::CorruptedHyperLogLog)?;
+                    body = rest;
+                    (
+                        0,
+                        ((usize::from(opcode & 0x3f) << 8) | usize::from(low)) + 1,
+                    )
+                }
+                // VAL: 1vvvvvxx
+                _ => (((opcode >> 2) & 0x1f) + 1, usize::from(opcode & 0x03) + 1),
+            };
+            let registers = self
+                .registers
+                .get_mut(index..index + len)
+                .ok_or(CmdErrors::CorruptedHyperLogLog)?;
+            registers.fill(value);
+            index += len;
+        }
+        match index == HLL_REGISTERS {
+            true => Ok(()),
+            false => Err(CmdErrors::CorruptedHyperLogLog),
+        }
+    }
+
+    /// Returns whether a register changed.
+    fn add(&mut self, element: &[u8]) -> bool {
+        let (index, count) = pattern(element);
+        if self.registers[index] >= count {
+            return false;
+        }
+        self.registers[index] = count;
+        true
+    }
+
+    /// Keeps the biggest register of both, a dense input makes the result dense.
+    fn merge(&mut self, other: &HyperLogLog) {
+        for (register, other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other);
+        }
+        self.dense |= other.dense;
+    }
+
+    fn cached(&self) -> Option<u64> {
+        match self.cache[7] & 0x80 {
+            0 => Some(u64::from_le_bytes(self.cache)),
+            _ => None,
+        }
+    }
+
+    fn invalidate_cache(&mut self) {
+        self.cache[7] |= 0x80;
+    }
+
+    /// Cardinality estimate of Ertl's "New cardinality estimation algorithms for
+    /// HyperLogLog sketches", the one Redis uses.
+    fn count(&self) -> u64 {
+        // registers of dense values written elsewhere may hold any 6 bits value
+        let mut histogram = [0u32; 64];
+        for register in &self.registers {
+            histogram[usize::from(*register)] += 1;
+        }
+
+        let m = HLL_REGISTERS as f64;
+        let mut z = m * tau((m - f64::from(histogram[HLL_Q + 1])) / m);
+        for count in histogram[1..=HLL_Q].iter().rev() {
+            z += f64::from(*count);
+            z *= 0.5;
+        }
+        z += m * sigma(f64::from(histogram[0]) / m);
+        (HLL_ALPHA_INF * m * m / z).round() as u64
+    }
+
+    /// The string value, sparse unless it was dense already or the registers don't
+    /// fit the sparse opcodes anymore.
+    fn encode(&mut self) -> Bytes {
+        let sparse = match self.dense {
+            true => None,
+            false => self.sparse_body(),
+        };
+        let body = sparse.unwrap_or_else(|| {
+            self.dense = true;
+            let mut body = vec![0; HLL_DENSE_SIZE - HLL_HEADER_SIZE];
+            for (index, register) in self.registers.iter().enumerate() {
+                dense_set(&mut body, index, *register);
+            }
+            body
+        });
+
+        let mut string = BytesMut::with_capacity(HLL_HEADER_SIZE + body.len());
+        string.extend_from_slice(HLL_MAGIC);
+        string.extend_from_slice(&[if self.dense { HLL_DENSE } else { HLL_SPARSE }, 0, 0, 0]);
+        string.extend_from_slice(&self.cache);
+        string.extend_from_slice(&body);
+        string.freeze()
+    }
+
+    /// Runs of zeros as `ZERO` or `XZERO`, runs of a value as `VAL` opcodes. `None`
+    /// when a register is too big or the result too long.
+    fn sparse_body(&self) -> Option<Vec<u8>> {
+        let mut body = vec![];
+        let mut index = 0;
+        while index < HLL_REGISTERS {
+            let value = self.registers[index];
+            let run = self.registers[index..]
+                .iter()
+                .take_while(|register| **register == value)
+                .count();
+            index += run;
+
+            if value > HLL_SPARSE_VAL_MAX {
+                return None;
+            }
+            if value == 0 && run > HLL_SPARSE_ZERO_MAX_LEN {
+                body.push(0x40 | ((run - 1) >> 8) as u8);
+                body.push((run - 1) as u8);
+            } else if value == 0 {
+                body.push((run - 1) as u8);
+            } else {
+                let mut left = run;
+                while left > 0 {
+                    let len = left.min(HLL_SPARSE_VAL_MAX_LEN);
+                    body.push(0x80 | ((value - 1) << 2) | (len - 1) as u8);
+                    left -= len;
+                }
+            }
+        }
+        (HLL_HEADER_SIZE + body.len() <= HLL_SPARSE_MAX_BYTES).then_some(body)
+    }
+}
+
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let (mut y, mut z) = (1.0, x);
+    loop {
+        x *= x;
+        let previous = z;
+        z += x * y;
+        y += y;
+        if z == previous {
+            return z;
+        }
+    }
+}
+
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let (mut y, mut z) = (1.0, 1.0 - x);
+    loop {
+        x = x.sqrt();
+        let previous = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+        if z == previous {
+            return z / 3.0;
+        }
+    }
+}
+
+impl Storage {
+    /// Adds the elements, creating the key when missing. Returns whether the
+    /// HyperLogLog changed, the cached cardinality is invalidated when it did.
+    pub(crate) async fn hll_add(&self, key: &Bytes, elements: &[Bytes]) -> Result<bool, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        if let Some(string) = state.get_value_mut::<Bytes>(key)? {
+            if is_dense(string) {
+                return Ok(dense_add(string, elements));
+            }
+        }
+        // sparse values are rewritten, they may turn dense
+        let (mut hll, created) = match state.get_value::<Bytes>(key)? {
+            Some(string) => (HyperLogLog::parse(string)?, false),
+            None => (HyperLogLog::new(), true),
+        };
+        let mut updated = false;
+        for element in elements {
+            updated |= hll.add(element);
+        }
+        if !updated && !created {
+            return Ok(false);
+        }
+        if updated {
+            hll.invalidate_cache();
+        }
+        // the key keeps its expiry
+        *state.get_or_insert_value::<Bytes>(key)? = hll.encode();
+        Ok(true)
+    }
+
+    /// Cardinality of the union of the HyperLogLogs, missing keys are empty. The
+    /// count of a single key is cached in its header.
+    pub(crate) async fn hll_count(&self, keys: &[Bytes]) -> Result<u64, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        if let [key] = keys {
+            let Some(string) = state.get_value_mut::<Bytes>(key)? else {
+                return Ok(0);
+            };
+            let hll = HyperLogLog::parse(string)?;
+            if let Some(count) = hll.cached() {
+                return Ok(count);
+            }
+            let count = hll.count();
+            let mut cached = BytesMut::from(std::mem::take(string));
+            cached[8..HLL_HEADER_SIZE].copy_from_slice(&count.to_le_bytes());
+            *string = cached.freeze();
+            return Ok(count);
+        }
+
+        let mut union = HyperLogLog::new();
+        for key in keys {
+            if let Some(string) = state.get_value::<Bytes>(key)? {
+                union.merge(&HyperLogLog::parse(string)?);
+            }
+        }
+        Ok(union.count())
+    }
+
+    /// Stores the union of `destination` and the sources in `destination`.
+    pub(crate) async fn hll_merge(
+        &self,
+        destination: &Bytes,
+        keys: &[Bytes],
+    ) -> Result<(), CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let mut union = match state.get_value::<Bytes>(destination)? {
+            Some(string) => HyperLogLog::parse(string)?,
+            None => HyperLogLog::new(),
+        };
+        for key in keys {
+            if let Some(string) = state.get_value::<Bytes>(key)? {
+                union.merge(&HyperLogLog::parse(string)?);
+            }
+        }
+        union.invalidate_cache();
+        *state.get_or_insert_value::<Bytes>(destination)? = union.encode();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_murmur_hash() {
+        // values of the Redis implementation
+        assert_eq!(murmur_hash64a(b"", HLL_HASH_SEED), 15627466953755236146);
+        assert_eq!(murmur_hash64a(b"a", HLL_HASH_SEED), 6039968161137406375);
+        assert_eq!(murmur_hash64a(b"hello", HLL_HASH_SEED), 1109414937308947456);
+        assert_eq!(
+            murmur_hash64a(b"0123456789abcdefXYZ", HLL_HASH_SEED),
+            216464458254671902
+        );
+    }
+
+    #[test]
+    fn test_dense_registers() {
+        let mut body = vec![0; HLL_DENSE_SIZE - HLL_HEADER_SIZE];
+        for index in 0..HLL_REGISTERS {
+            dense_set(&mut body, index, (index % 64) as u8);
+        }
+        for index in 0..HLL_REGISTERS {
+            assert_eq!(dense_get(&body, index), (index % 64) as u8);
+        }
+    }
+
+    #[test]
+    fn test_sparse_encoding() {
+        let mut hll = HyperLogLog::new();
+        assert_eq!(&hll.encode()[..], b"HYLL\x01\0\0\0\0\0\0\0\0\0\0\0\x7f\xff");
+
+        hll.registers[100] = 3;
+        hll.registers[101] = 3;
+        let string = hll.encode();
+        // XZERO of 100, VAL 3 of 2, XZERO of 16282
+        assert_eq!(&string[HLL_HEADER_SIZE..], b"\x40\x63\x89\x7f\x99");
+        assert_eq!(
+            HyperLogLog::parse(&string).unwrap().registers,
+            hll.registers
+        );
+
+        hll.registers[200] = HLL_SPARSE_VAL_MAX + 1;
+        let string = hll.encode();
+        assert_eq!(string.len(), HLL_DENSE_SIZE);
+        assert_eq!(
+            HyperLogLog::parse(&string).unwrap().registers,
+            hll.registers
+        );
+
+        assert_eq!(
+            HyperLogLog::parse(b"HYLL\x01\0\0\0\0\0\0\0\0\0\0\0\x7f\xfe").err(),
+            Some(CmdErrors::CorruptedHyperLogLog)
+        );
+        assert_eq!(
+            HyperLogLog::parse(b"HYLL\0\0\0\0\0\0\0\0\0\0\0\0\x7f\xff").err(),
+            Some(CmdErrors::NotAHyperLogLog)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hll_count() {
+        let storage = Storage::setup();
+        let (first, second) = (Bytes::from("first"), Bytes::from("second"));
+        let elements: Vec<Bytes> = (0..10000).map(|n| Bytes::from(n.to_string())).collect();
+        assert_eq!(storage.hll_add(&first, &elements[..6000]).await, Ok(true));
+        assert_eq!(storage.hll_add(&first, &elements[..10]).await, Ok(false));
+        assert_eq!(storage.hll_add(&second, &elements[4000..]).await, Ok(true));
+
+        // the standard error of 16384 registers is 0.81%
+        let count = storage
+            .hll_count(&[first.clone(), second.clone()])
+            .await
+            .unwrap();
+        assert!(count.abs_diff(10000) < 250, "{count}");
+        let count = storage
+            .hll_count(std::slice::from_ref(&first))
+            .await
+            .unwrap();
+        assert!(count.abs_diff(6000) < 150, "{count}");
+        assert_eq!(
+            storage.hll_count(std::slice::from_ref(&first)).await,
+            Ok(count)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dense_add_in_place() {
+        let storage = Storage::setup();
+        let key = Bytes::from("visitors");
+        let elements: Vec<Bytes> = (0..7000).map(|n| Bytes::from(n.to_string())).collect();
+        storage.hll_add(&key, &elements[..6000]).await.unwrap();
+        storage.hll_count(std::slice::from_ref(&key)).await.unwrap();
+        assert_eq!(storage.hll_add(&key, &elements[6000..]).await, Ok(true));
+
+        let mut expected = HyperLogLog::new();
+        for element in &elements {
+            expected.add(element);
+        }
+        let mut state = storage.shared.state.lock().await;
+        let string = state.get_value::<Bytes>(&key).unwrap().unwrap();
+        assert!(is_dense(string));
+        let hll = HyperLogLog::parse(string).unwrap();
+        assert_eq!(hll.registers, expected.registers);
+        assert_eq!(hll.cached(), None);
+    }
+}
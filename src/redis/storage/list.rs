@@ -0,0 +1,378 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+
+use super::{State, Storage};
+use crate::redis::CmdErrors;
+
+pub(super) type List = VecDeque<Bytes>;
+
+/// Which end of a list a command works with, the left one is the head.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ListEnd {
+    Left,
+    Right,
+}
+
+/// Position of a possibly negative index, negative ones count from the tail.
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let index = match index < 0 {
+        true => index + len,
+        false => index,
+    };
+    (0..len).contains(&index).then_some(index as usize)
+}
+
+/// Inclusive `start..=stop` range clamped to the list, `None` when no element is in it.
+pub(super) fn normalize_range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = match start < 0 {
+        true => (start + len).max(0),
+        false => start,
+    };
+    let stop = match stop < 0 {
+        true => stop + len,
+        false => stop.min(len - 1),
+    };
+    match start > stop || start >= len {
+        true => None,
+        false => Some((start as usize, stop as usize)),
+    }
+}
+
+fn push(list: &mut List, end: ListEnd, value: Bytes) {
+    match end {
+        ListEnd::Left => list.push_front(value),
+        ListEnd::Right => list.push_back(value),
+    }
+}
+
+fn pop(list: &mut List, end: ListEnd) -> Option<Bytes> {
+    match end {
+        ListEnd::Left => list.pop_front(),
+        ListEnd::Right => list.pop_back(),
+    }
+}
+
+impl Storage {
+    /// Returns the length of the list after the push.
+    pub(crate) async fn list_push(
+        &self,
+        key: &Bytes,
+        values: &[Bytes],
+        end: ListEnd,
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        state.list_push(key, values, end)
+    }
+
+    /// Pops up to `count` elements, `None` when there is no such key.
+    pub(crate) async fn list_pop(
+        &self,
+        key: &Bytes,
+        end: ListEnd,
+        count: usize,
+    ) -> Result<Option<Vec<Bytes>>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        state.list_pop(key, end, count)
+    }
+
+    pub(crate) async fn list_len(&self, key: &Bytes) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state.get_value::<List>(key)?.map_or(0, List::len))
+    }
+
+    pub(crate) async fn list_range(
+        &self,
+        key: &Bytes,
+        start: i64,
+        stop: i64,
+    ) -> Result<Vec<Bytes>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(list) = state.get_value::<List>(key)? else {
+            return Ok(vec![]);
+        };
+        Ok(match normalize_range(start, stop, list.len()) {
+            Some((start, stop)) => list.range(start..=stop).cloned().collect(),
+            None => vec![],
+        })
+    }
+
+    pub(crate) async fn list_index(
+        &self,
+        key: &Bytes,
+        index: i64,
+    ) -> Result<Option<Bytes>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(list) = state.get_value::<List>(key)? else {
+            return Ok(None);
+        };
+        Ok(normalize_index(index, list.len()).map(|index| list[index].clone()))
+    }
+
+    pub(crate) async fn list_set(
+        &self,
+        key: &Bytes,
+        index: i64,
+        value: &Bytes,
+    ) -> Result<(), CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let list = state
+            .get_value_mut::<List>(key)?
+            .ok_or(CmdErrors::NoSuchKey)?;
+        let index = normalize_index(index, list.len()).ok_or(CmdErrors::IndexOutOfRange)?;
+        list[index] = value.clone();
+        Ok(())
+    }
+
+    /// Removes up to `count` elements equal to `value`, starting from the head when
+    /// `count` is positive and from the tail when it is negative, zero removes all of them.
+    pub(crate) async fn list_remove(
+        &self,
+        key: &Bytes,
+        count: i64,
+        value: &Bytes,
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(list) = state.get_value_mut::<List>(key)? else {
+            return Ok(0);
+        };
+
+        let limit = match count {
+            0 => usize::MAX,
+            count => usize::try_from(count.unsigned_abs()).unwrap_or(usize::MAX),
+        };
+        let len = list.len();
+        let mut removed: Vec<usize> = (0..len)
+            .map(|i| if count < 0 { len - 1 - i } else { i })
+            .filter(|&i| list[i] == value)
+            .take(limit)
+            .collect();
+        removed.sort_unstable();
+
+        let mut index = 0;
+        list.retain(|_| {
+            let keep = removed.binary_search(&index).is_err();
+            index += 1;
+            keep
+        });
+        state.remove_if_empty(key);
+        Ok(removed.len())
+    }
+
+    /// Keeps only the elements in the `start..=stop` range.
+    pub(crate) async fn list_trim(
+        &self,
+        key: &Bytes,
+        start: i64,
+        stop: i64,
+    ) -> Result<(), CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(list) = state.get_value_mut::<List>(key)? else {
+            return Ok(());
+        };
+        match normalize_range(start, stop, list.len()) {
+            Some((start, stop)) => {
+                list.truncate(stop + 1);
+                list.drain(..start);
+            }
+            None => list.clear(),
+        }
+        state.remove_if_empty(key);
+        Ok(())
+    }
+
+    /// Inserts `value` next to the first `pivot`. Returns the new length of the list,
+    /// `-1` when there is no pivot and `0` when there is no such key.
+    pub(crate) async fn list_insert(
+        &self,
+        key: &Bytes,
+        before: bool,
+        pivot: &Bytes,
+        value: &Bytes,
+    ) -> Result<i64, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(list) = state.get_value_mut::<List>(key)? else {
+            return Ok(0);
+        };
+        let Some(position) = list.iter().position(|item| item == pivot) else {
+            return Ok(-1);
+        };
+        let position = match before {
+            true => position,
+            false => position + 1,
+        };
+        list.insert(position, value.clone());
+        Ok(list.len() as i64)
+    }
+
+    /// Indexes of elements equal to `value`, as `LPOS` looks for them: `rank` picks the
+    /// first match to report and the direction, at most `count` are reported (zero
+    /// means all), at most `max_len` elements are compared (zero means the whole list).
+    pub(crate) async fn list_position(
+        &self,
+        key: &Bytes,
+        value: &Bytes,
+        rank: i64,
+        count: usize,
+        max_len: usize,
+    ) -> Result<Vec<usize>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(list) = state.get_value::<List>(key)? else {
+            return Ok(vec![]);
+        };
+
+        let len = list.len();
+        let compared = match max_len {
+            0 => len,
+            max_len => max_len.min(len),
+        };
+        let skipped = usize::try_from(rank.unsigned_abs() - 1).unwrap_or(usize::MAX);
+        let count = match count {
+            0 => usize::MAX,
+            count => count,
+        };
+        Ok((0..compared)
+            .map(|i| if rank < 0 { len - 1 - i } else { i })
+            .filter(|&i| list[i] == value)
+            .skip(skipped)
+            .take(count)
+            .collect())
+    }
+
+    /// Pops an element from one list and pushes it to another one, atomically.
+    pub(crate) async fn list_move(
+        &self,
+        source: &Bytes,
+        destination: &Bytes,
+        from: ListEnd,
+        to: ListEnd,
+    ) -> Result<Option<Bytes>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        state.list_move(source, destination, from, to)
+    }
+}
+
+impl State {
+    fn list_push(
+        &mut self,
+        key: &Bytes,
+        values: &[Bytes],
+        end: ListEnd,
+    ) -> Result<usize, CmdErrors> {
+        let list = self.get_or_insert_value::<List>(key)?;
+        for value in values {
+            push(list, end, value.clone());
+        }
+        let len = list.len();
+        self.wake_blocked(key);
+        Ok(len)
+    }
+
+    pub(super) fn list_pop(
+        &mut self,
+        key: &Bytes,
+        end: ListEnd,
+        count: usize,
+    ) -> Result<Option<Vec<Bytes>>, CmdErrors> {
+        let Some(list) = self.get_value_mut::<List>(key)? else {
+            return Ok(None);
+        };
+        let popped = (0..count).map_while(|_| pop(list, end)).collect();
+        self.remove_if_empty(key);
+        Ok(Some(popped))
+    }
+
+    pub(super) fn list_move(
+        &mut self,
+        source: &Bytes,
+        destination: &Bytes,
+        from: ListEnd,
+        to: ListEnd,
+    ) -> Result<Option<Bytes>, CmdErrors> {
+        // nothing is popped when the element can't be pushed
+        self.get_value::<List>(destination)?;
+        let Some(list) = self.get_value_mut::<List>(source)? else {
+            return Ok(None);
+        };
+        let Some(value) = pop(list, from) else {
+            return Ok(None);
+        };
+
+        push(
+            self.get_or_insert_value::<List>(destination)?,
+            to,
+            value.clone(),
+        );
+        // after the push, so rotating a single element list doesn't delete it
+        self.remove_if_empty(source);
+        self.wake_blocked(destination);
+        Ok(Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_range() {
+        assert_eq!(normalize_range(0, -1, 3), Some((0, 2)));
+        assert_eq!(normalize_range(-100, 100, 3), Some((0, 2)));
+        assert_eq!(normalize_range(1, 1, 3), Some((1, 1)));
+        assert_eq!(normalize_range(-2, -1, 3), Some((1, 2)));
+        assert_eq!(normalize_range(2, 1, 3), None);
+        assert_eq!(normalize_range(3, 5, 3), None);
+        assert_eq!(normalize_range(0, -4, 3), None);
+        assert_eq!(normalize_range(0, -1, 0), None);
+    }
+
+    #[tokio::test]
+    async fn test_list_remove_and_position() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"list");
+        let (a, b) = (Bytes::from_static(b"a"), Bytes::from_static(b"b"));
+        let values = [&a, &b, &a, &b, &a].map(Bytes::clone);
+        storage
+            .list_push(&key, &values, ListEnd::Right)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            storage.list_position(&key, &a, 1, 0, 0).await,
+            Ok(vec![0, 2, 4])
+        );
+        assert_eq!(storage.list_position(&key, &a, -2, 1, 0).await, Ok(vec![2]));
+        assert_eq!(storage.list_position(&key, &a, 1, 0, 2).await, Ok(vec![0]));
+
+        assert_eq!(storage.list_remove(&key, -2, &a).await, Ok(2));
+        assert_eq!(
+            storage.list_range(&key, 0, -1).await,
+            Ok(vec![a.clone(), b.clone(), b.clone()])
+        );
+        assert_eq!(storage.list_remove(&key, 0, &b).await, Ok(2));
+        assert_eq!(storage.list_remove(&key, 1, &a).await, Ok(1));
+        // the last element took the key with it
+        assert_eq!(storage.key_type(&key).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_list_move_rotates() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"list");
+        let a = Bytes::from_static(b"a");
+        storage
+            .list_push(&key, std::slice::from_ref(&a), ListEnd::Right)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            storage
+                .list_move(&key, &key, ListEnd::Left, ListEnd::Right)
+                .await,
+            Ok(Some(a.clone()))
+        );
+        assert_eq!(storage.list_range(&key, 0, -1).await, Ok(vec![a]));
+    }
+}
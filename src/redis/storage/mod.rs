@@ -0,0 +1,889 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime};
+
+use bytes::Bytes;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::redis::{random, CmdErrors};
+
+mod bitmap;
+pub(crate) use bitmap::{BitOp, BitOverflow, BitUnit, BitfieldOp, BitfieldType, BITMAP_MAX_BITS};
+mod blocking;
+use blocking::BlockedClients;
+pub(crate) use blocking::{BlockingOp, Popped, PoppedItems};
+mod geo;
+pub(crate) use geo::{
+    geo_distance, geo_score, geo_valid, geohash, GeoMatch, GeoOrder, GeoOrigin, GeoQuery, GeoShape,
+};
+mod hash;
+mod hyperloglog;
+pub(crate) use hash::FieldExpire;
+use hash::Hash;
+mod list;
+use list::List;
+pub(crate) use list::ListEnd;
+mod set;
+use set::Set;
+pub(crate) use set::SetOp;
+mod skiplist;
+mod sorted_set;
+use sorted_set::SortedSet;
+mod stream;
+mod stream_group;
+mod string;
+pub(crate) use sorted_set::{
+    Aggregate, LexBound, ScoreBound, ZAddOptions, ZAdded, ZRange, ZSetEnd,
+};
+use stream::Stream;
+pub(crate) use stream::{NewStreamId, ReadFrom, StreamEntry, StreamId, StreamTrim, TrimStrategy};
+pub(crate) use stream_group::{
+    AutoClaimed, ClaimOptions, ConsumerInfo, GroupInfo, GroupReadFrom, PendingFilter, PendingInfo,
+    PendingSummary, StreamInfo,
+};
+
+// active expire cycle runs 10 times per second, like Redis with default `hz`
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+// keys with an expiry checked per one lock acquisition
+const ACTIVE_EXPIRE_KEYS_PER_LOOP: usize = 20;
+// cycle keeps sampling while more than this percent of sampled keys were expired
+const ACTIVE_EXPIRE_ACCEPTABLE_STALE_PERCENT: usize = 10;
+// but never runs longer than a quarter of the interval
+const ACTIVE_EXPIRE_CYCLE_TIME_LIMIT: Duration = Duration::from_millis(25);
+
+#[derive(Debug, Clone)]
+pub(crate) struct Storage {
+    shared: Arc<Shared>,
+}
+
+/// What `SET` does with the time to live of the key it overwrites.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SetExpiry {
+    Discard,
+    Keep,
+    At(SystemTime),
+}
+
+/// `NX | XX | IFEQ` options of `SET`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SetCondition {
+    Always,
+    NotExists,
+    Exists,
+    // only when the key holds exactly this value
+    Equals(Bytes),
+}
+
+/// `NX | XX | GT | LT` options of the `EXPIRE` family.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ExpireCondition {
+    Always,
+    // only when the key has no expiry
+    NotExists,
+    // only when the key already has an expiry
+    Exists,
+    // only when the new expiry is later than the current one, no expiry is infinitely late
+    GreaterThan,
+    // only when the new expiry is earlier than the current one
+    LessThan,
+    // `XX` with `LT`: like `LessThan`, but a key without expiry is left alone
+    ExistsLessThan,
+}
+
+impl ExpireCondition {
+    /// Whether a deadline `at` can replace the `current` one.
+    fn allows(self, current: Option<SystemTime>, at: SystemTime) -> bool {
+        match self {
+            ExpireCondition::Always => true,
+            ExpireCondition::NotExists => current.is_none(),
+            ExpireCondition::Exists => current.is_some(),
+            ExpireCondition::GreaterThan => current.is_some_and(|current| at > current),
+            ExpireCondition::LessThan => current.is_none_or(|current| at < current),
+            ExpireCondition::ExistsLessThan => current.is_some_and(|current| at < current),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum KeyExpiry {
+    Missing,
+    Persistent,
+    At(SystemTime),
+}
+
+/// Keyspace size and expiry counters, reported by `INFO`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct StorageInfo {
+    pub keys: usize,
+    pub volatile_keys: usize,
+    pub stats: Stats,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Stats {
+    // keys deleted because of expiry, both lazily and by the active expire cycle
+    pub expired_keys: u64,
+    // hash fields deleted because of expiry
+    pub expired_subkeys: u64,
+    pub active_expire_cycles: u64,
+    // estimated percent of keys with an expiry that are expired but not deleted yet
+    pub expired_stale_perc: f64,
+}
+
+impl Storage {
+    pub(crate) fn setup() -> Storage {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                entries: HashMap::new(),
+                volatile_keys: KeySet::default(),
+                volatile_hashes: KeySet::default(),
+                stats: Stats::default(),
+                blocked: BlockedClients::default(),
+            }),
+        });
+
+        Storage { shared }
+    }
+
+    pub(crate) async fn get(&self, key: &Bytes) -> Result<Option<Bytes>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state.get_value::<Bytes>(key)?.cloned())
+    }
+
+    /// Checks the condition and writes the value under one lock, so nobody can change
+    /// the key in between. Returns whether the value was written and the old value.
+    /// `SET` overwrites a value of any type, unless it has to look at the old value:
+    /// then only a string will do.
+    pub(crate) async fn set(
+        &self,
+        key: &Bytes,
+        val: &Bytes,
+        expiry: SetExpiry,
+        condition: &SetCondition,
+        get_old_value: bool,
+    ) -> Result<(bool, Option<Bytes>), CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let (exists, old_value, old_expires_at) = match state.live_entry(key) {
+            None => (false, None, None),
+            Some(Entry {
+                value: Value::String(data),
+                expires_at,
+            }) => (true, Some(data.clone()), *expires_at),
+            Some(_) if get_old_value || matches!(condition, SetCondition::Equals(_)) => {
+                return Err(CmdErrors::WrongType)
+            }
+            // `NX | XX` only care whether there is a key
+            Some(entry) => (true, None, entry.expires_at),
+        };
+
+        let allowed = match condition {
+            SetCondition::Always => true,
+            SetCondition::NotExists => !exists,
+            SetCondition::Exists => exists,
+            SetCondition::Equals(expected) => old_value.as_ref() == Some(expected),
+        };
+        if !allowed {
+            return Ok((false, old_value));
+        }
+
+        let expires_at = match expiry {
+            SetExpiry::Discard => None,
+            SetExpiry::Keep => old_expires_at,
+            SetExpiry::At(at) => Some(at),
+        };
+        state.insert(
+            key.clone(),
+            Entry {
+                value: Value::String(val.clone()),
+                expires_at,
+            },
+        );
+
+        Ok((true, old_value))
+    }
+
+    /// Name of the type of the value, `None` when there is no such key.
+    pub(crate) async fn key_type(&self, key: &Bytes) -> Option<&'static str> {
+        let mut state = self.shared.state.lock().await;
+        state.live_entry(key).map(|entry| entry.value.type_name())
+    }
+
+    /// Copies the value and its expiry, `destination` must not exist unless `replace`
+    /// is set. Returns whether the value was copied.
+    pub(crate) async fn copy(&self, source: &Bytes, destination: &Bytes, replace: bool) -> bool {
+        let mut state = self.shared.state.lock().await;
+        let Some(entry) = state.live_entry(source).cloned() else {
+            return false;
+        };
+        if !replace && state.live_entry(destination).is_some() {
+            return false;
+        }
+        state.insert(destination.clone(), entry);
+        state.wake_blocked(destination);
+        true
+    }
+
+    /// Returns `false` when the key is missing or the condition is not met.
+    /// Deadline in the past deletes the key right away.
+    pub(crate) async fn expire(
+        &self,
+        key: &Bytes,
+        at: SystemTime,
+        condition: ExpireCondition,
+    ) -> bool {
+        let mut state = self.shared.state.lock().await;
+        let Some(entry) = state.live_entry(key) else {
+            return false;
+        };
+
+        if !condition.allows(entry.expires_at, at) {
+            return false;
+        }
+
+        match at <= SystemTime::now() {
+            true => {
+                state.remove(key);
+            }
+            false => state.set_expiry(key, Some(at)),
+        }
+        true
+    }
+
+    /// Removes the expiry, returns `false` when there was nothing to remove.
+    pub(crate) async fn persist(&self, key: &Bytes) -> bool {
+        let mut state = self.shared.state.lock().await;
+        let had_expiry = state
+            .live_entry(key)
+            .is_some_and(|entry| entry.expires_at.is_some());
+        if had_expiry {
+            state.set_expiry(key, None);
+        }
+        had_expiry
+    }
+
+    pub(crate) async fn expiry(&self, key: &Bytes) -> KeyExpiry {
+        let mut state = self.shared.state.lock().await;
+        match state.live_entry(key) {
+            None => KeyExpiry::Missing,
+            Some(Entry {
+                expires_at: None, ..
+            }) => KeyExpiry::Persistent,
+            Some(Entry {
+                expires_at: Some(at),
+                ..
+            }) => KeyExpiry::At(*at),
+        }
+    }
+
+    pub(crate) async fn info(&self) -> StorageInfo {
+        let state = self.shared.state.lock().await;
+        StorageInfo {
+            keys: state.entries.len(),
+            volatile_keys: state.volatile_keys.len(),
+            stats: state.stats,
+        }
+    }
+
+    /// Deletes keys that expired but nobody looked them up since, runs forever.
+    pub(crate) async fn run_active_expire(self) {
+        let mut interval = tokio::time::interval(ACTIVE_EXPIRE_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.active_expire_cycle().await;
+        }
+    }
+
+    /// Samples random keys with an expiry and deletes expired ones, hashes with
+    /// field deadlines are sampled the same way for expired fields. Effort adapts
+    /// to how many expired keys there are: the cycle keeps sampling while a big share
+    /// of sampled keys turn out to be expired, and gives up quickly when few are.
+    /// Lock is released between samples, so clients are not blocked for the whole cycle.
+    pub(crate) async fn active_expire_cycle(&self) {
+        let started = Instant::now();
+        let (mut total_sampled, mut total_expired) = (0, 0);
+
+        loop {
+            let (sampled, expired) = {
+                let mut state = self.shared.state.lock().await;
+                let (keys_sampled, keys_expired) =
+                    state.expire_random_keys(ACTIVE_EXPIRE_KEYS_PER_LOOP);
+                let (hashes_sampled, hashes_expired) =
+                    state.expire_random_hash_fields(ACTIVE_EXPIRE_KEYS_PER_LOOP);
+                (keys_sampled + hashes_sampled, keys_expired + hashes_expired)
+            };
+            total_sampled += sampled;
+            total_expired += expired;
+
+            if sampled == 0
+                || expired * 100 <= sampled * ACTIVE_EXPIRE_ACCEPTABLE_STALE_PERCENT
+                || started.elapsed() > ACTIVE_EXPIRE_CYCLE_TIME_LIMIT
+            {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+
+        let mut state = self.shared.state.lock().await;
+        state.stats.active_expire_cycles += 1;
+        let current_perc = match total_sampled {
+            0 => 0.0,
+            _ => total_expired as f64 * 100.0 / total_sampled as f64,
+        };
+        // moving average, a single unlucky sample doesn't change much
+        state.stats.expired_stale_perc =
+            current_perc * 0.05 + state.stats.expired_stale_perc * 0.95;
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    entries: HashMap<Bytes, Entry>,
+    // keys that have an expiry, so the active expire cycle doesn't scan all keys
+    volatile_keys: KeySet,
+    // hashes that have fields with an expiry, the same for hash fields
+    volatile_hashes: KeySet,
+    stats: Stats,
+    blocked: BlockedClients,
+}
+
+impl State {
+    /// Expired keys are deleted lazily, when somebody looks them up.
+    fn live_entry(&mut self, key: &Bytes) -> Option<&mut Entry> {
+        let now = SystemTime::now();
+        if self
+            .entries
+            .get(key)
+            .is_some_and(|entry| entry.is_expired(now))
+        {
+            self.expire_key(key);
+        }
+
+        self.entries.get_mut(key)
+    }
+
+    /// Value of the key when it is a `T`, WRONGTYPE error when the key holds something else.
+    fn get_value<T: ValueType>(&mut self, key: &Bytes) -> Result<Option<&T>, CmdErrors> {
+        match self.live_entry(key) {
+            None => Ok(None),
+            Some(entry) => T::from_value_mut(&mut entry.value)
+                .map(|value| Some(&*value))
+                .ok_or(CmdErrors::WrongType),
+        }
+    }
+
+    fn get_value_mut<T: ValueType>(&mut self, key: &Bytes) -> Result<Option<&mut T>, CmdErrors> {
+        match self.live_entry(key) {
+            None => Ok(None),
+            Some(entry) => T::from_value_mut(&mut entry.value)
+                .map(Some)
+                .ok_or(CmdErrors::WrongType),
+        }
+    }
+
+    /// Like `get_value_mut`, but a missing key is created with an empty value first.
+    fn get_or_insert_value<T: ValueType + Default>(
+        &mut self,
+        key: &Bytes,
+    ) -> Result<&mut T, CmdErrors> {
+        if self.live_entry(key).is_none() {
+            self.insert(
+                key.clone(),
+                Entry {
+                    value: T::default().into_value(),
+                    expires_at: None,
+                },
+            );
+        }
+        self.get_value_mut(key)?.ok_or(CmdErrors::WrongType)
+    }
+
+    /// Collections never stay empty, the key goes away together with the last element.
+    fn remove_if_empty(&mut self, key: &Bytes) {
+        if self
+            .entries
+            .get(key)
+            .is_some_and(|entry| entry.value.is_empty())
+        {
+            self.remove(key);
+        }
+    }
+
+    // entries are added and removed only through `insert` and `remove`, and expiry
+    // is changed only through `set_expiry`, so `volatile_keys` is always in sync.
+    // `volatile_hashes` may also keep hashes that lost their field deadlines,
+    // the active expire cycle drops them when it comes across them
+
+    fn insert(&mut self, key: Bytes, entry: Entry) {
+        match entry.expires_at {
+            Some(_) => self.volatile_keys.insert(&key),
+            None => self.volatile_keys.remove(&key),
+        };
+        match &entry.value {
+            Value::Hash(hash) if hash.has_deadlines() => self.volatile_hashes.insert(&key),
+            _ => self.volatile_hashes.remove(&key),
+        };
+        self.entries.insert(key, entry);
+    }
+
+    fn remove(&mut self, key: &Bytes) -> Option<Entry> {
+        self.volatile_keys.remove(key);
+        self.volatile_hashes.remove(key);
+        self.entries.remove(key)
+    }
+
+    fn set_expiry(&mut self, key: &Bytes, expires_at: Option<SystemTime>) {
+        if let Some(entry) = self.entries.get_mut(key) {
+            entry.expires_at = expires_at;
+            match expires_at {
+                Some(_) => self.volatile_keys.insert(key),
+                None => self.volatile_keys.remove(key),
+            };
+        }
+    }
+
+    fn expire_key(&mut self, key: &Bytes) {
+        self.remove(key);
+        self.stats.expired_keys += 1;
+    }
+
+    /// Checks up to `count` random keys with an expiry, returns how many keys were
+    /// checked and how many of them were expired and deleted.
+    fn expire_random_keys(&mut self, count: usize) -> (usize, usize) {
+        let now = SystemTime::now();
+        let sampled = count.min(self.volatile_keys.len());
+        let mut expired = 0;
+
+        for _ in 0..sampled {
+            let Some(key) = self.volatile_keys.random().cloned() else {
+                break;
+            };
+            if self
+                .entries
+                .get(&key)
+                .is_some_and(|entry| entry.is_expired(now))
+            {
+                self.expire_key(&key);
+                expired += 1;
+            }
+        }
+
+        (sampled, expired)
+    }
+}
+
+/// Set of keys with O(1) insert, remove and random pick. Members of big sets and
+/// fields of hashes are kept the same way, so random ones are picked without a scan.
+#[derive(Debug, Clone, Default)]
+struct KeySet {
+    keys: Vec<Bytes>,
+    positions: HashMap<Bytes, usize>,
+}
+
+impl KeySet {
+    /// Returns whether the key is new.
+    fn insert(&mut self, key: &Bytes) -> bool {
+        if self.positions.contains_key(key) {
+            return false;
+        }
+        self.positions.insert(key.clone(), self.keys.len());
+        self.keys.push(key.clone());
+        true
+    }
+
+    /// Returns whether the key was in the set.
+    fn remove(&mut self, key: &Bytes) -> bool {
+        let Some(position) = self.positions.remove(key) else {
+            return false;
+        };
+        self.keys.swap_remove(position);
+        // the last key took the place of the removed one
+        if let Some(moved) = self.keys.get(position) {
+            self.positions.insert(moved.clone(), position);
+        }
+        true
+    }
+
+    fn contains(&self, key: &Bytes) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    fn random(&self) -> Option<&Bytes> {
+        match self.keys.len() {
+            0 => None,
+            len => self.keys.get(random::random_index(len)),
+        }
+    }
+
+    /// Key at `index`, which must be in `0..len`. The order changes when keys are removed.
+    fn get(&self, index: usize) -> &Bytes {
+        &self.keys[index]
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Bytes> {
+        self.keys.iter()
+    }
+
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    value: Value,
+    expires_at: Option<SystemTime>,
+}
+
+/// Every key holds a value of one of these types, commands of one type
+/// refuse to work with keys of another one.
+#[derive(Debug, Clone)]
+enum Value {
+    String(Bytes),
+    List(List),
+    Hash(Hash),
+    Set(Set),
+    SortedSet(SortedSet),
+    Stream(Stream),
+}
+
+impl Value {
+    /// Name reported by `TYPE`.
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Hash(_) => "hash",
+            Value::Set(_) => "set",
+            Value::SortedSet(_) => "zset",
+            Value::Stream(_) => "stream",
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Value::String(_) => false,
+            Value::List(list) => list.is_empty(),
+            Value::Hash(hash) => hash.is_empty(),
+            Value::Set(set) => set.is_empty(),
+            Value::SortedSet(zset) => zset.is_empty(),
+            // streams stay when their last entry is deleted, like in Redis
+            Value::Stream(_) => false,
+        }
+    }
+}
+
+/// Typed access to values, commands of one type get a WRONGTYPE error
+/// instead of a value of another type.
+trait ValueType: Sized {
+    fn from_value_mut(value: &mut Value) -> Option<&mut Self>;
+    fn into_value(self) -> Value;
+}
+
+macro_rules! value_type {
+    ($type:ty, $variant:ident) => {
+        impl ValueType for $type {
+            fn from_value_mut(value: &mut Value) -> Option<&mut Self> {
+                match value {
+                    Value::$variant(value) => Some(value),
+                    _ => None,
+                }
+            }
+
+            fn into_value(self) -> Value {
+                Value::$variant(self)
+            }
+        }
+    };
+}
+
+value_type!(Bytes, String);
+value_type!(List, List);
+value_type!(Hash, Hash);
+value_type!(Set, Set);
+value_type!(SortedSet, SortedSet);
+value_type!(Stream, Stream);
+
+impl Entry {
+    fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_expired_key_is_missing() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"key");
+        let past = SystemTime::now() - Duration::from_millis(1);
+
+        storage
+            .set(
+                &key,
+                &Bytes::from_static(b"val"),
+                SetExpiry::At(past),
+                &SetCondition::Always,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(storage.get(&key).await, Ok(None));
+        assert_eq!(storage.expiry(&key).await, KeyExpiry::Missing);
+    }
+
+    #[tokio::test]
+    async fn test_set_keeps_or_discards_ttl() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"key");
+        let val = Bytes::from_static(b"val");
+        let later = SystemTime::now() + Duration::from_secs(100);
+
+        storage
+            .set(
+                &key,
+                &val,
+                SetExpiry::At(later),
+                &SetCondition::Always,
+                false,
+            )
+            .await
+            .unwrap();
+        storage
+            .set(&key, &val, SetExpiry::Keep, &SetCondition::Always, false)
+            .await
+            .unwrap();
+        assert_eq!(storage.expiry(&key).await, KeyExpiry::At(later));
+
+        storage
+            .set(&key, &val, SetExpiry::Discard, &SetCondition::Always, false)
+            .await
+            .unwrap();
+        assert_eq!(storage.expiry(&key).await, KeyExpiry::Persistent);
+    }
+
+    #[tokio::test]
+    async fn test_expire_conditions() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"key");
+        let now = SystemTime::now();
+        let soon = now + Duration::from_secs(10);
+        let later = now + Duration::from_secs(100);
+
+        assert!(!storage.expire(&key, later, ExpireCondition::Always).await);
+
+        storage
+            .set(
+                &key,
+                &Bytes::from_static(b"val"),
+                SetExpiry::Discard,
+                &SetCondition::Always,
+                false,
+            )
+            .await
+            .unwrap();
+        assert!(!storage.expire(&key, later, ExpireCondition::Exists).await);
+        assert!(
+            !storage
+                .expire(&key, later, ExpireCondition::GreaterThan)
+                .await
+        );
+        assert!(
+            storage
+                .expire(&key, later, ExpireCondition::NotExists)
+                .await
+        );
+        assert!(!storage.expire(&key, soon, ExpireCondition::NotExists).await);
+        assert!(
+            !storage
+                .expire(&key, soon, ExpireCondition::GreaterThan)
+                .await
+        );
+        assert!(storage.expire(&key, soon, ExpireCondition::LessThan).await);
+        assert_eq!(storage.expiry(&key).await, KeyExpiry::At(soon));
+
+        assert!(storage.persist(&key).await);
+        assert!(!storage.persist(&key).await);
+
+        // deadline in the past deletes the key
+        assert!(storage.expire(&key, now, ExpireCondition::Always).await);
+        assert_eq!(storage.get(&key).await, Ok(None));
+    }
+
+    #[tokio::test]
+    async fn test_volatile_keys_are_tracked() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"key");
+        let val = Bytes::from_static(b"val");
+        let later = SystemTime::now() + Duration::from_secs(100);
+
+        storage
+            .set(
+                &key,
+                &val,
+                SetExpiry::At(later),
+                &SetCondition::Always,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(storage.info().await.volatile_keys, 1);
+        storage.persist(&key).await;
+        assert_eq!(storage.info().await.volatile_keys, 0);
+        storage.expire(&key, later, ExpireCondition::Always).await;
+        assert_eq!(storage.info().await.volatile_keys, 1);
+        storage
+            .set(&key, &val, SetExpiry::Discard, &SetCondition::Always, false)
+            .await
+            .unwrap();
+        assert_eq!(storage.info().await.volatile_keys, 0);
+    }
+
+    #[tokio::test]
+    async fn test_active_expire_cycle() {
+        let storage = Storage::setup();
+        let val = Bytes::from_static(b"val");
+        let past = SystemTime::now() - Duration::from_millis(1);
+        let later = SystemTime::now() + Duration::from_secs(100);
+
+        for i in 0..200 {
+            let key = Bytes::from(format!("expired:{}", i));
+            storage
+                .set(
+                    &key,
+                    &val,
+                    SetExpiry::At(past),
+                    &SetCondition::Always,
+                    false,
+                )
+                .await
+                .unwrap();
+        }
+        for i in 0..10 {
+            let key = Bytes::from(format!("alive:{}", i));
+            storage
+                .set(
+                    &key,
+                    &val,
+                    SetExpiry::At(later),
+                    &SetCondition::Always,
+                    false,
+                )
+                .await
+                .unwrap();
+        }
+        storage
+            .set(
+                &Bytes::from_static(b"persistent"),
+                &val,
+                SetExpiry::Discard,
+                &SetCondition::Always,
+                false,
+            )
+            .await
+            .unwrap();
+
+        // a cycle keeps going while most of the sampled keys are expired
+        let mut cycles = 0;
+        while storage.info().await.keys > 11 {
+            storage.active_expire_cycle().await;
+            cycles += 1;
+            assert!(cycles < 100, "expired keys are not reclaimed");
+        }
+
+        let info = storage.info().await;
+        assert_eq!(info.keys, 11);
+        assert_eq!(info.volatile_keys, 10);
+        assert_eq!(info.stats.expired_keys, 200);
+        assert_eq!(info.stats.active_expire_cycles, cycles);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_set() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"key");
+        let (one, two) = (Bytes::from_static(b"1"), Bytes::from_static(b"2"));
+
+        let set = |val: &Bytes, condition: SetCondition| {
+            let (storage, key, val) = (storage.clone(), key.clone(), val.clone());
+            async move {
+                storage
+                    .set(&key, &val, SetExpiry::Discard, &condition, false)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        assert_eq!(set(&one, SetCondition::Exists).await, (false, None));
+        assert_eq!(set(&one, SetCondition::NotExists).await, (true, None));
+        assert_eq!(
+            set(&two, SetCondition::NotExists).await,
+            (false, Some(one.clone()))
+        );
+        assert_eq!(
+            set(&two, SetCondition::Equals(two.clone())).await,
+            (false, Some(one.clone()))
+        );
+        assert_eq!(
+            set(&two, SetCondition::Equals(one.clone())).await,
+            (true, Some(one.clone()))
+        );
+        assert_eq!(
+            set(&one, SetCondition::Exists).await,
+            (true, Some(two.clone()))
+        );
+        assert_eq!(storage.get(&key).await, Ok(Some(one)));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_type() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"key");
+        let val = Bytes::from_static(b"val");
+        storage.shared.state.lock().await.insert(
+            key.clone(),
+            Entry {
+                value: Value::List(List::from([val.clone()])),
+                expires_at: None,
+            },
+        );
+
+        assert_eq!(storage.key_type(&key).await, Some("list"));
+        assert_eq!(storage.get(&key).await, Err(CmdErrors::WrongType));
+        assert_eq!(
+            storage
+                .set(&key, &val, SetExpiry::Discard, &SetCondition::Always, true)
+                .await,
+            Err(CmdErrors::WrongType)
+        );
+        assert_eq!(
+            storage
+                .set(
+                    &key,
+                    &val,
+                    SetExpiry::Discard,
+                    &SetCondition::NotExists,
+                    false
+                )
+                .await,
+            Ok((false, None))
+        );
+
+        // plain SET replaces a value of any type
+        assert_eq!(
+            storage
+                .set(&key, &val, SetExpiry::Discard, &SetCondition::Always, false)
+                .await,
+            Ok((true, None))
+        );
+        assert_eq!(storage.key_type(&key).await, Some("string"));
+        assert_eq!(storage.get(&key).await, Ok(Some(val)));
+    }
+}
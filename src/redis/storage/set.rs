@@ -0,0 +1,405 @@
+use bytes::Bytes;
+
+use super::{Entry, KeySet, State, Storage, Value};
+use crate::redis::{random, CmdErrors};
+
+// sets of integers up to this size are kept as an intset, like Redis
+// with default `set-max-intset-entries`
+const SET_MAX_INTSET_ENTRIES: usize = 512;
+
+/// Set of members. Small sets of integers are kept as a sorted array of numbers,
+/// the way Redis keeps them in an intset, and turn into a hash set of strings
+/// once they get a member that is not an integer or grow too big.
+#[derive(Debug, Clone)]
+pub(super) enum Set {
+    Ints(Vec<i64>),
+    Members(KeySet),
+}
+
+impl Default for Set {
+    fn default() -> Set {
+        Set::Ints(vec![])
+    }
+}
+
+/// Number the member is a canonical representation of, `"007"` and `"+7"` are
+/// strings, so they don't collide with `"7"`.
+fn as_int(member: &[u8]) -> Option<i64> {
+    let int: i64 = std::str::from_utf8(member).ok()?.parse().ok()?;
+    (int.to_string().as_bytes() == member).then_some(int)
+}
+
+fn int_member(int: i64) -> Bytes {
+    Bytes::from(int.to_string())
+}
+
+impl Set {
+    pub(super) fn len(&self) -> usize {
+        match self {
+            Set::Ints(ints) => ints.len(),
+            Set::Members(members) => members.len(),
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn contains(&self, member: &Bytes) -> bool {
+        match self {
+            Set::Ints(ints) => as_int(member).is_some_and(|int| ints.binary_search(&int).is_ok()),
+            Set::Members(members) => members.contains(member),
+        }
+    }
+
+    pub(super) fn members(&self) -> Vec<Bytes> {
+        match self {
+            Set::Ints(ints) => ints.iter().copied().map(int_member).collect(),
+            Set::Members(members) => members.iter().cloned().collect(),
+        }
+    }
+
+    /// Member at `index`, which must be in `0..len`, so random ones are picked
+    /// without copying the set.
+    fn member(&self, index: usize) -> Bytes {
+        match self {
+            Set::Ints(ints) => int_member(ints[index]),
+            Set::Members(members) => members.get(index).clone(),
+        }
+    }
+
+    /// Returns whether the member is new.
+    fn insert(&mut self, member: &Bytes) -> bool {
+        if let Set::Ints(ints) = self {
+            if let Some(int) = as_int(member) {
+                match ints.binary_search(&int) {
+                    Ok(_) => return false,
+                    Err(position) if ints.len() < SET_MAX_INTSET_ENTRIES => {
+                        ints.insert(position, int);
+                        return true;
+                    }
+                    Err(_) => {}
+                }
+            }
+            let mut members = KeySet::default();
+            for int in ints.iter() {
+                members.insert(&int_member(*int));
+            }
+            *self = Set::Members(members);
+        }
+
+        match self {
+            Set::Ints(_) => unreachable!("intset was converted above"),
+            Set::Members(members) => members.insert(member),
+        }
+    }
+
+    fn remove(&mut self, member: &Bytes) -> bool {
+        match self {
+            Set::Ints(ints) => {
+                let Some(position) = as_int(member).and_then(|int| ints.binary_search(&int).ok())
+                else {
+                    return false;
+                };
+                ints.remove(position);
+                true
+            }
+            Set::Members(members) => members.remove(member),
+        }
+    }
+}
+
+/// Operation of `SINTER`, `SUNION`, `SDIFF` and their `STORE` variants.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SetOp {
+    Intersection,
+    Union,
+    // members of the first set that are in none of the others
+    Difference,
+}
+
+/// Missing keys are empty sets.
+fn combine(sets: &[Option<&Set>], op: SetOp) -> Set {
+    let mut result = Set::default();
+    match op {
+        SetOp::Union => {
+            for set in sets.iter().flatten() {
+                for member in set.members() {
+                    result.insert(&member);
+                }
+            }
+        }
+        SetOp::Intersection => {
+            let Some(mut sets) = sets.iter().copied().collect::<Option<Vec<&Set>>>() else {
+                return result;
+            };
+            // the smallest set has the fewest members to check
+            sets.sort_by_key(|set| set.len());
+            if let Some((smallest, others)) = sets.split_first() {
+                for member in smallest.members() {
+                    if others.iter().all(|set| set.contains(&member)) {
+                        result.insert(&member);
+                    }
+                }
+            }
+        }
+        SetOp::Difference => {
+            let Some((Some(first), others)) = sets.split_first() else {
+                return result;
+            };
+            for member in first.members() {
+                if !others.iter().flatten().any(|set| set.contains(&member)) {
+                    result.insert(&member);
+                }
+            }
+        }
+    }
+    result
+}
+
+impl Storage {
+    /// Returns how many of the members are new.
+    pub(crate) async fn set_add(&self, key: &Bytes, members: &[Bytes]) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let set = state.get_or_insert_value::<Set>(key)?;
+        Ok(members.iter().filter(|member| set.insert(member)).count())
+    }
+
+    /// Returns how many of the members were in the set.
+    pub(crate) async fn set_remove(
+        &self,
+        key: &Bytes,
+        members: &[Bytes],
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(set) = state.get_value_mut::<Set>(key)? else {
+            return Ok(0);
+        };
+        let removed = members.iter().filter(|member| set.remove(member)).count();
+        state.remove_if_empty(key);
+        Ok(removed)
+    }
+
+    pub(crate) async fn set_members(&self, key: &Bytes) -> Result<Vec<Bytes>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state.get_value::<Set>(key)?.map_or(vec![], Set::members))
+    }
+
+    /// Whether each of the members is in the set, in the same order.
+    pub(crate) async fn set_contains(
+        &self,
+        key: &Bytes,
+        members: &[Bytes],
+    ) -> Result<Vec<bool>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let set = state.get_value::<Set>(key)?;
+        Ok(members
+            .iter()
+            .map(|member| set.is_some_and(|set| set.contains(member)))
+            .collect())
+    }
+
+    pub(crate) async fn set_len(&self, key: &Bytes) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state.get_value::<Set>(key)?.map_or(0, Set::len))
+    }
+
+    /// Removes up to `count` random members and returns them.
+    pub(crate) async fn set_pop(&self, key: &Bytes, count: usize) -> Result<Vec<Bytes>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(set) = state.get_value_mut::<Set>(key)? else {
+            return Ok(vec![]);
+        };
+        let mut popped = Vec::with_capacity(count.min(set.len()));
+        while popped.len() < count && !set.is_empty() {
+            let member = set.member(random::random_index(set.len()));
+            set.remove(&member);
+            popped.push(member);
+        }
+        state.remove_if_empty(key);
+        Ok(popped)
+    }
+
+    /// Random members, `count` works as in `random::sample`.
+    pub(crate) async fn set_random_members(
+        &self,
+        key: &Bytes,
+        count: i64,
+    ) -> Result<Vec<Bytes>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(set) = state.get_value::<Set>(key)? else {
+            return Ok(vec![]);
+        };
+        random::sample(set.len(), count, |index| set.member(index))
+    }
+
+    pub(crate) async fn set_combine(
+        &self,
+        keys: &[Bytes],
+        op: SetOp,
+    ) -> Result<Vec<Bytes>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let sets = state.live_sets(keys)?;
+        Ok(combine(&sets, op).members())
+    }
+
+    /// Stores the result in `destination`, overwriting whatever was there, an empty
+    /// result deletes it. Returns the size of the result.
+    pub(crate) async fn set_combine_store(
+        &self,
+        destination: &Bytes,
+        keys: &[Bytes],
+        op: SetOp,
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let sets = state.live_sets(keys)?;
+        let result = combine(&sets, op);
+        let len = result.len();
+        match len {
+            0 => {
+                state.remove(destination);
+            }
+            _ => state.insert(
+                destination.clone(),
+                Entry {
+                    value: Value::Set(result),
+                    expires_at: None,
+                },
+            ),
+        }
+        Ok(len)
+    }
+}
+
+impl State {
+    /// Sets of all the keys at once, `None` for missing keys. Fails when any
+    /// of the keys holds something else.
+    fn live_sets(&mut self, keys: &[Bytes]) -> Result<Vec<Option<&Set>>, CmdErrors> {
+        // expired keys are deleted and types are checked first, then sets are only read
+        for key in keys {
+            self.get_value::<Set>(key)?;
+        }
+        Ok(keys
+            .iter()
+            .map(
+                |key| match self.entries.get(key).map(|entry| &entry.value) {
+                    Some(Value::Set(set)) => Some(set),
+                    _ => None,
+                },
+            )
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(members: &[&'static str]) -> Vec<Bytes> {
+        members.iter().map(|member| Bytes::from(*member)).collect()
+    }
+
+    #[test]
+    fn test_intset_encoding() {
+        let mut set = Set::default();
+        for member in members(&["3", "1", "2", "1"]) {
+            set.insert(&member);
+        }
+        assert!(matches!(&set, Set::Ints(ints) if ints == &[1, 2, 3]));
+        // not canonical integers are strings
+        assert!(!set.contains(&Bytes::from("01")));
+
+        set.insert(&Bytes::from("01"));
+        assert!(matches!(set, Set::Members(_)));
+        assert_eq!(set.len(), 4);
+        assert!(set.contains(&Bytes::from("1")));
+
+        let mut set = Set::default();
+        for int in 0..=SET_MAX_INTSET_ENTRIES as i64 {
+            set.insert(&int_member(int));
+        }
+        assert!(matches!(set, Set::Members(_)));
+        assert_eq!(set.len(), SET_MAX_INTSET_ENTRIES + 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_algebra() {
+        let storage = Storage::setup();
+        let (a, b, c) = (Bytes::from("a"), Bytes::from("b"), Bytes::from("c"));
+        storage
+            .set_add(&a, &members(&["1", "2", "x"]))
+            .await
+            .unwrap();
+        storage
+            .set_add(&b, &members(&["2", "x", "y"]))
+            .await
+            .unwrap();
+
+        let sorted = |mut members: Vec<Bytes>| {
+            members.sort();
+            members
+        };
+        let keys = [a.clone(), b.clone(), c.clone()];
+        assert_eq!(
+            storage
+                .set_combine(&keys[..2], SetOp::Intersection)
+                .await
+                .map(sorted),
+            Ok(members(&["2", "x"]))
+        );
+        assert_eq!(
+            storage.set_combine(&keys, SetOp::Intersection).await,
+            Ok(vec![])
+        );
+        assert_eq!(
+            storage.set_combine(&keys, SetOp::Union).await.map(sorted),
+            Ok(members(&["1", "2", "x", "y"]))
+        );
+        assert_eq!(
+            storage.set_combine(&keys, SetOp::Difference).await,
+            Ok(members(&["1"]))
+        );
+
+        assert_eq!(
+            storage
+                .set_combine_store(&c, &keys[..2], SetOp::Union)
+                .await,
+            Ok(4)
+        );
+        assert_eq!(storage.set_len(&c).await, Ok(4));
+        assert_eq!(
+            storage
+                .set_combine_store(&c, &[a, Bytes::from("missing")], SetOp::Intersection)
+                .await,
+            Ok(0)
+        );
+        assert_eq!(storage.key_type(&c).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_random_members() {
+        let storage = Storage::setup();
+        let key = Bytes::from("events");
+        let events: Vec<Bytes> = (0..1000)
+            .map(|id| Bytes::from(format!("event:{id}")))
+            .collect();
+        storage.set_add(&key, &events).await.unwrap();
+
+        let mut sampled = storage.set_random_members(&key, 10).await.unwrap();
+        sampled.sort();
+        sampled.dedup();
+        assert_eq!(sampled.len(), 10);
+        assert_eq!(storage.set_random_members(&key, -3).await.unwrap().len(), 3);
+
+        let mut popped = storage.set_pop(&key, 10).await.unwrap();
+        popped.sort();
+        popped.dedup();
+        assert_eq!(popped.len(), 10);
+        assert_eq!(storage.set_len(&key).await, Ok(990));
+        let left = storage.set_contains(&key, &popped).await.unwrap();
+        assert!(left.iter().all(|contained| !contained));
+
+        assert_eq!(storage.set_pop(&key, 2000).await.unwrap().len(), 990);
+        assert_eq!(storage.key_type(&key).await, None);
+    }
+}
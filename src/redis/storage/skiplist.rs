@@ -0,0 +1,354 @@
+use bytes::Bytes;
+
+use crate::redis::random;
+
+// enough for 2^64 elements with one in four nodes promoted to the next level
+const MAX_LEVEL: usize = 32;
+// the head is a sentinel node, it is always the first node of the arena
+const HEAD: usize = 0;
+
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    next: Option<usize>,
+    // how many nodes the link jumps over, the node it leads to included
+    span: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    member: Bytes,
+    score: f64,
+    links: Vec<Link>,
+    prev: Option<usize>,
+}
+
+/// Members ordered by score, then by member, the way Redis keeps sorted sets.
+/// Links know how many nodes they skip, so ranks are found in O(log n) too.
+/// Nodes live in an arena and point to each other by index.
+#[derive(Debug, Clone)]
+pub(super) struct SkipList {
+    nodes: Vec<Node>,
+    // slots of removed nodes, reused by the next inserts
+    free: Vec<usize>,
+    level: usize,
+    len: usize,
+    tail: Option<usize>,
+}
+
+/// Whether `(score, member)` comes before `(other_score, other_member)`.
+fn precedes(score: f64, member: &Bytes, other_score: f64, other_member: &Bytes) -> bool {
+    score < other_score || (score == other_score && member < other_member)
+}
+
+fn random_level() -> usize {
+    let mut level = 1;
+    // promoted with 1/4 probability, like in Redis
+    while level < MAX_LEVEL && random::next_u64().is_multiple_of(4) {
+        level += 1;
+    }
+    level
+}
+
+impl Default for SkipList {
+    fn default() -> SkipList {
+        let head = Node {
+            member: Bytes::new(),
+            score: 0.0,
+            links: vec![
+                Link {
+                    next: None,
+                    span: 0
+                };
+                MAX_LEVEL
+            ],
+            prev: None,
+        };
+        SkipList {
+            nodes: vec![head],
+            free: vec![],
+            level: 1,
+            len: 0,
+            tail: None,
+        }
+    }
+}
+
+impl SkipList {
+    /// The member must not be in the list yet.
+    pub(super) fn insert(&mut self, score: f64, member: Bytes) {
+        // last node before the new one on every level and its rank
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut rank = [0; MAX_LEVEL];
+        let mut node = HEAD;
+        for level in (0..self.level).rev() {
+            if level + 1 < self.level {
+                rank[level] = rank[level + 1];
+            }
+            while let Some(next) = self.nodes[node].links[level].next {
+                let next_node = &self.nodes[next];
+                if !precedes(next_node.score, &next_node.member, score, &member) {
+                    break;
+                }
+                rank[level] += self.nodes[node].links[level].span;
+                node = next;
+            }
+            update[level] = node;
+        }
+
+        let node_level = random_level();
+        if node_level > self.level {
+            for level in self.level..node_level {
+                self.nodes[HEAD].links[level].span = self.len;
+            }
+            self.level = node_level;
+        }
+
+        let new = Node {
+            member,
+            score,
+            links: vec![
+                Link {
+                    next: None,
+                    span: 0
+                };
+                node_level
+            ],
+            prev: (update[0] != HEAD).then_some(update[0]),
+        };
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = new;
+                id
+            }
+            None => {
+                self.nodes.push(new);
+                self.nodes.len() - 1
+            }
+        };
+
+        for level in 0..node_level {
+            let before = self.nodes[update[level]].links[level];
+            let skipped = rank[0] - rank[level];
+            self.nodes[id].links[level] = Link {
+                next: before.next,
+                span: before.span - skipped,
+            };
+            self.nodes[update[level]].links[level] = Link {
+                next: Some(id),
+                span: skipped + 1,
+            };
+        }
+        // links above the new node jump over one more node now
+        for (level, before) in update.iter().enumerate().take(self.level).skip(node_level) {
+            self.nodes[*before].links[level].span += 1;
+        }
+
+        match self.nodes[id].links[0].next {
+            Some(next) => self.nodes[next].prev = Some(id),
+            None => self.tail = Some(id),
+        }
+        self.len += 1;
+    }
+
+    /// Returns `false` when there is no such member with such score.
+    pub(super) fn remove(&mut self, score: f64, member: &Bytes) -> bool {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut node = HEAD;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.nodes[node].links[level].next {
+                let next_node = &self.nodes[next];
+                if !precedes(next_node.score, &next_node.member, score, member) {
+                    break;
+                }
+                node = next;
+            }
+            update[level] = node;
+        }
+
+        let Some(id) = self.nodes[node].links[0].next else {
+            return false;
+        };
+        if self.nodes[id].score != score || self.nodes[id].member != member {
+            return false;
+        }
+
+        for (level, before) in update.iter().enumerate().take(self.level) {
+            let removed = self.nodes[id].links.get(level).copied();
+            let before = &mut self.nodes[*before].links[level];
+            match removed {
+                Some(removed) if before.next == Some(id) => {
+                    before.span = before.span + removed.span - 1;
+                    before.next = removed.next;
+                }
+                // links above the removed node jump over one node less
+                _ => before.span -= 1,
+            }
+        }
+
+        let prev = self.nodes[id].prev;
+        match self.nodes[id].links[0].next {
+            Some(next) => self.nodes[next].prev = prev,
+            None => self.tail = prev,
+        }
+        while self.level > 1 && self.nodes[HEAD].links[self.level - 1].next.is_none() {
+            self.level -= 1;
+        }
+
+        // the member is dropped right away, not when the slot is reused
+        self.nodes[id].member = Bytes::new();
+        self.nodes[id].links.clear();
+        self.free.push(id);
+        self.len -= 1;
+        true
+    }
+
+    /// How many elements come before the first one `before` is `false` for. `before`
+    /// has to be `true` for a prefix of the list and `false` for the rest of it.
+    pub(super) fn count_before(&self, before: impl Fn(f64, &Bytes) -> bool) -> usize {
+        let mut rank = 0;
+        let mut node = HEAD;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.nodes[node].links[level].next {
+                if !before(self.nodes[next].score, &self.nodes[next].member) {
+                    break;
+                }
+                rank += self.nodes[node].links[level].span;
+                node = next;
+            }
+        }
+        rank
+    }
+
+    /// Zero-based rank of the member with such score.
+    pub(super) fn rank(&self, score: f64, member: &Bytes) -> usize {
+        self.count_before(|other_score, other_member| {
+            precedes(other_score, other_member, score, member)
+        })
+    }
+
+    fn node_at(&self, rank: usize) -> Option<usize> {
+        let target = rank + 1;
+        let mut traversed = 0;
+        let mut node = HEAD;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.nodes[node].links[level].next {
+                let span = self.nodes[node].links[level].span;
+                if traversed + span > target {
+                    break;
+                }
+                traversed += span;
+                node = next;
+            }
+            if traversed == target {
+                return Some(node);
+            }
+        }
+        None
+    }
+
+    /// Elements with ranks in `start..end`, from the last one when `rev` is set.
+    pub(super) fn range(&self, start: usize, end: usize, rev: bool) -> Vec<(Bytes, f64)> {
+        let end = end.min(self.len);
+        if start >= end {
+            return vec![];
+        }
+
+        let mut items = Vec::with_capacity(end - start);
+        let mut node = match rev {
+            true => self.node_at(end - 1),
+            false => self.node_at(start),
+        };
+        while let Some(id) = node {
+            if items.len() == end - start {
+                break;
+            }
+            let current = &self.nodes[id];
+            items.push((current.member.clone(), current.score));
+            node = match rev {
+                true => current.prev,
+                false => current.links[0].next,
+            };
+        }
+        items
+    }
+
+    pub(super) fn first(&self) -> Option<(Bytes, f64)> {
+        let node = &self.nodes[self.nodes[HEAD].links[0].next?];
+        Some((node.member.clone(), node.score))
+    }
+
+    pub(super) fn last(&self) -> Option<(Bytes, f64)> {
+        let node = &self.nodes[self.tail?];
+        Some((node.member.clone(), node.score))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skiplist_ranks() {
+        let mut list = SkipList::default();
+        // enough elements for several levels
+        for i in (0..1000).rev() {
+            list.insert((i / 2) as f64, Bytes::from(format!("{:04}", i)));
+        }
+        assert_eq!(list.len, 1000);
+        assert_eq!(list.rank(250.0, &Bytes::from("0500")), 500);
+        assert_eq!(list.count_before(|score, _| score < 10.0), 20);
+        assert_eq!(
+            list.range(0, 2, false),
+            vec![(Bytes::from("0000"), 0.0), (Bytes::from("0001"), 0.0)]
+        );
+        assert_eq!(list.range(998, 2000, true).len(), 2);
+        assert_eq!(list.last(), Some((Bytes::from("0999"), 499.0)));
+
+        for i in (0..1000).step_by(2) {
+            assert!(list.remove((i / 2) as f64, &Bytes::from(format!("{:04}", i))));
+        }
+        assert!(!list.remove(0.0, &Bytes::from("0000")));
+        assert_eq!(list.len, 500);
+        assert_eq!(list.first(), Some((Bytes::from("0001"), 0.0)));
+        assert_eq!(list.rank(250.0, &Bytes::from("0501")), 250);
+        assert_eq!(
+            list.range(249, 251, true),
+            vec![(Bytes::from("0501"), 250.0), (Bytes::from("0499"), 249.0)]
+        );
+    }
+
+    #[test]
+    fn test_skiplist_matches_sorted_vec() {
+        let mut list = SkipList::default();
+        let mut expected: Vec<(Bytes, f64)> = vec![];
+        for i in 0..2000 {
+            let member = Bytes::from(format!("{}", random::next_u64() % 300));
+            let score = (random::next_u64() % 50) as f64;
+            match expected.iter().position(|(other, _)| *other == member) {
+                // every third time a present member is removed instead
+                Some(position) if i % 3 == 0 => {
+                    let (member, score) = expected.remove(position);
+                    assert!(list.remove(score, &member));
+                }
+                Some(_) => {}
+                None => {
+                    list.insert(score, member.clone());
+                    expected.push((member, score));
+                }
+            }
+        }
+
+        expected.sort_by(|(a, a_score), (b, b_score)| {
+            a_score.total_cmp(b_score).then_with(|| a.cmp(b))
+        });
+        assert_eq!(list.len, expected.len());
+        assert_eq!(list.range(0, expected.len(), false), expected);
+        for (rank, (member, score)) in expected.iter().enumerate() {
+            assert_eq!(list.rank(*score, member), rank);
+            assert_eq!(
+                list.range(rank, rank + 1, false),
+                vec![(member.clone(), *score)]
+            );
+        }
+    }
+}
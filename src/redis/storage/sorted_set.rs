@@ -0,0 +1,651 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use super::list::normalize_range;
+use super::skiplist::SkipList;
+use super::{Entry, SetOp, State, Storage, Value};
+use crate::redis::CmdErrors;
+
+/// Sorted set: scores by member for lookups, and the same pairs in a skiplist
+/// for ranks and ranges.
+#[derive(Debug, Clone, Default)]
+pub(super) struct SortedSet {
+    scores: HashMap<Bytes, f64>,
+    list: SkipList,
+}
+
+impl SortedSet {
+    pub(super) fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub(super) fn score(&self, member: &Bytes) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    /// Adds the member or changes its score, returns whether the member is new.
+    pub(super) fn insert(&mut self, member: Bytes, score: f64) -> bool {
+        match self.scores.insert(member.clone(), score) {
+            Some(old) => {
+                self.list.remove(old, &member);
+                self.list.insert(score, member);
+                false
+            }
+            None => {
+                self.list.insert(score, member);
+                true
+            }
+        }
+    }
+
+    fn remove(&mut self, member: &Bytes) -> bool {
+        match self.scores.remove(member) {
+            Some(score) => self.list.remove(score, member),
+            None => false,
+        }
+    }
+
+    fn rank(&self, member: &Bytes) -> Option<usize> {
+        let score = self.score(member)?;
+        Some(self.list.rank(score, member))
+    }
+
+    /// Members with a score from `min` included to `max` excluded, by score.
+    pub(super) fn score_range(&self, min: f64, max: f64) -> Vec<(Bytes, f64)> {
+        let start = self.list.count_before(|score, _| score < min);
+        let end = self.list.count_before(|score, _| score < max);
+        self.list.range(start, end, false)
+    }
+
+    fn all(&self) -> Vec<(Bytes, f64)> {
+        self.list.range(0, self.len(), false)
+    }
+
+    /// Removes up to `count` members from one end.
+    pub(super) fn pop(&mut self, end: ZSetEnd, count: usize) -> Vec<(Bytes, f64)> {
+        let mut popped = vec![];
+        while popped.len() < count {
+            let next = match end {
+                ZSetEnd::Min => self.list.first(),
+                ZSetEnd::Max => self.list.last(),
+            };
+            let Some((member, score)) = next else {
+                break;
+            };
+            self.remove(&member);
+            popped.push((member, score));
+        }
+        popped
+    }
+}
+
+/// End of a sorted set `ZPOPMIN` and `ZPOPMAX` pop from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ZSetEnd {
+    Min,
+    Max,
+}
+
+/// One end of a score range, `(` in front of the number makes it exclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ScoreBound {
+    pub value: f64,
+    pub exclusive: bool,
+}
+
+impl ScoreBound {
+    fn below_min(self, score: f64) -> bool {
+        score < self.value || (self.exclusive && score == self.value)
+    }
+
+    fn within_max(self, score: f64) -> bool {
+        score < self.value || (!self.exclusive && score == self.value)
+    }
+}
+
+/// One end of a lexicographical range: `-`, `+`, `[member` or `(member`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum LexBound {
+    NegativeInfinity,
+    PositiveInfinity,
+    Inclusive(Bytes),
+    Exclusive(Bytes),
+}
+
+impl LexBound {
+    fn below_min(&self, member: &Bytes) -> bool {
+        match self {
+            LexBound::NegativeInfinity => false,
+            LexBound::PositiveInfinity => true,
+            LexBound::Inclusive(min) => member < min,
+            LexBound::Exclusive(min) => member <= min,
+        }
+    }
+
+    fn within_max(&self, member: &Bytes) -> bool {
+        match self {
+            LexBound::NegativeInfinity => false,
+            LexBound::PositiveInfinity => true,
+            LexBound::Inclusive(max) => member <= max,
+            LexBound::Exclusive(max) => member < max,
+        }
+    }
+}
+
+/// What `ZRANGE` selects. Lexicographical ranges expect all members to have
+/// the same score, like in Redis.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ZRange {
+    Rank { start: i64, stop: i64 },
+    Score { min: ScoreBound, max: ScoreBound },
+    Lex { min: LexBound, max: LexBound },
+}
+
+/// `NX | XX | GT | LT | INCR` options of `ZADD`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct ZAddOptions {
+    // only add new members
+    pub only_new: bool,
+    // only update existing members
+    pub only_existing: bool,
+    // only update when the new score is greater
+    pub greater: bool,
+    // only update when the new score is less
+    pub less: bool,
+    // the score is an increment of the current one
+    pub increment: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct ZAdded {
+    pub added: usize,
+    // existing members with a new score
+    pub updated: usize,
+    // score of the last member, `None` when the options didn't let it change
+    pub score: Option<f64>,
+}
+
+/// `AGGREGATE` option of `ZUNIONSTORE` and `ZINTERSTORE`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Aggregate {
+    Sum,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    fn apply(self, left: f64, right: f64) -> f64 {
+        match self {
+            // `inf + -inf` is zero, like in Redis
+            Aggregate::Sum => match left + right {
+                sum if sum.is_nan() => 0.0,
+                sum => sum,
+            },
+            Aggregate::Min => left.min(right),
+            Aggregate::Max => left.max(right),
+        }
+    }
+}
+
+/// Ranks `start..end` of what `range` selects. With `rev` rank ranges count from the last
+/// member, score and lexicographical ranges don't depend on the direction.
+fn range_ranks(zset: &SortedSet, range: &ZRange, rev: bool) -> (usize, usize) {
+    let len = zset.len();
+    match range {
+        ZRange::Rank { start, stop } => match normalize_range(*start, *stop, len) {
+            None => (0, 0),
+            Some((start, stop)) if rev => (len - 1 - stop, len - start),
+            Some((start, stop)) => (start, stop + 1),
+        },
+        ZRange::Score { min, max } => (
+            zset.list.count_before(|score, _| min.below_min(score)),
+            zset.list.count_before(|score, _| max.within_max(score)),
+        ),
+        ZRange::Lex { min, max } => (
+            zset.list.count_before(|_, member| min.below_min(member)),
+            zset.list.count_before(|_, member| max.within_max(member)),
+        ),
+    }
+}
+
+/// Missing keys are empty, weights multiply the scores of the matching keys.
+fn combine(
+    sources: Vec<Option<Vec<(Bytes, f64)>>>,
+    weights: &[f64],
+    aggregate: Aggregate,
+    op: SetOp,
+) -> SortedSet {
+    let weighted = sources.into_iter().enumerate().map(|(i, members)| {
+        let weight = weights.get(i).copied().unwrap_or(1.0);
+        members.map(|members| {
+            members
+                .into_iter()
+                .map(|(member, score)| match score * weight {
+                    // `0 * inf` is zero, like in Redis
+                    score if score.is_nan() => (member, 0.0),
+                    score => (member, score),
+                })
+                .collect::<HashMap<Bytes, f64>>()
+        })
+    });
+
+    let mut result: Option<HashMap<Bytes, f64>> = None;
+    for members in weighted {
+        let members = members.unwrap_or_default();
+        result = Some(match result {
+            None => members,
+            Some(mut result) => {
+                match op {
+                    SetOp::Union => {
+                        for (member, score) in members {
+                            let combined = match result.get(&member) {
+                                Some(current) => aggregate.apply(*current, score),
+                                None => score,
+                            };
+                            result.insert(member, combined);
+                        }
+                    }
+                    SetOp::Intersection => {
+                        result.retain(|member, _| members.contains_key(member));
+                        for (member, current) in result.iter_mut() {
+                            *current = aggregate.apply(*current, members[member]);
+                        }
+                    }
+                    SetOp::Difference => result.retain(|member, _| !members.contains_key(member)),
+                }
+                result
+            }
+        });
+    }
+
+    let mut zset = SortedSet::default();
+    for (member, score) in result.unwrap_or_default() {
+        zset.insert(member, score);
+    }
+    zset
+}
+
+impl Storage {
+    /// Adds members with their scores, or changes the scores of existing ones.
+    pub(crate) async fn zset_add(
+        &self,
+        key: &Bytes,
+        pairs: &[(f64, Bytes)],
+        options: ZAddOptions,
+    ) -> Result<ZAdded, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        if options.only_existing && state.get_value::<SortedSet>(key)?.is_none() {
+            return Ok(ZAdded::default());
+        }
+        let zset = state.get_or_insert_value::<SortedSet>(key)?;
+
+        let mut result = ZAdded::default();
+        for (score, member) in pairs {
+            let Some(current) = zset.score(member) else {
+                if !options.only_existing {
+                    zset.insert(member.clone(), *score);
+                    result.added += 1;
+                    result.score = Some(*score);
+                }
+                continue;
+            };
+            if options.only_new {
+                continue;
+            }
+
+            let score = match options.increment {
+                true => current + score,
+                false => *score,
+            };
+            if score.is_nan() {
+                return Err(CmdErrors::ScoreIsNaN);
+            }
+            if (options.greater && score <= current) || (options.less && score >= current) {
+                continue;
+            }
+            if score != current {
+                zset.insert(member.clone(), score);
+                result.updated += 1;
+            }
+            result.score = Some(score);
+        }
+        if result.added > 0 {
+            state.wake_blocked(key);
+        }
+        Ok(result)
+    }
+
+    /// Returns how many of the members were in the sorted set.
+    pub(crate) async fn zset_remove(
+        &self,
+        key: &Bytes,
+        members: &[Bytes],
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(zset) = state.get_value_mut::<SortedSet>(key)? else {
+            return Ok(0);
+        };
+        let removed = members.iter().filter(|member| zset.remove(member)).count();
+        state.remove_if_empty(key);
+        Ok(removed)
+    }
+
+    pub(crate) async fn zset_score(
+        &self,
+        key: &Bytes,
+        member: &Bytes,
+    ) -> Result<Option<f64>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state
+            .get_value::<SortedSet>(key)?
+            .and_then(|zset| zset.score(member)))
+    }
+
+    /// Zero-based rank of the member from the lowest score, together with its score.
+    pub(crate) async fn zset_rank(
+        &self,
+        key: &Bytes,
+        member: &Bytes,
+    ) -> Result<Option<(usize, f64)>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(zset) = state.get_value::<SortedSet>(key)? else {
+            return Ok(None);
+        };
+        Ok(zset.rank(member).zip(zset.score(member)))
+    }
+
+    pub(crate) async fn zset_len(&self, key: &Bytes) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state.get_value::<SortedSet>(key)?.map_or(0, SortedSet::len))
+    }
+
+    pub(crate) async fn zset_count(
+        &self,
+        key: &Bytes,
+        min: ScoreBound,
+        max: ScoreBound,
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(zset) = state.get_value::<SortedSet>(key)? else {
+            return Ok(0);
+        };
+        let (start, end) = range_ranks(zset, &ZRange::Score { min, max }, false);
+        Ok(end.saturating_sub(start))
+    }
+
+    /// Members `range` selects with their scores, from the highest score when `rev`
+    /// is set. `limit` is the offset and count of `LIMIT`, a negative count means all.
+    pub(crate) async fn zset_range(
+        &self,
+        key: &Bytes,
+        range: &ZRange,
+        rev: bool,
+        limit: Option<(i64, i64)>,
+    ) -> Result<Vec<(Bytes, f64)>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(zset) = state.get_value::<SortedSet>(key)? else {
+            return Ok(vec![]);
+        };
+
+        let (mut start, mut end) = range_ranks(zset, range, rev);
+        if start >= end {
+            return Ok(vec![]);
+        }
+        if let Some((offset, count)) = limit {
+            let Ok(offset) = usize::try_from(offset) else {
+                return Ok(vec![]);
+            };
+            let offset = offset.min(end - start);
+            let count = usize::try_from(count).map_or(end - start - offset, |count| {
+                count.min(end - start - offset)
+            });
+            match rev {
+                true => {
+                    end -= offset;
+                    start = end - count;
+                }
+                false => {
+                    start += offset;
+                    end = start + count;
+                }
+            }
+        }
+        Ok(zset.list.range(start, end, rev))
+    }
+
+    /// Pops up to `count` members with the lowest or the highest scores.
+    pub(crate) async fn zset_pop(
+        &self,
+        key: &Bytes,
+        end: ZSetEnd,
+        count: usize,
+    ) -> Result<Vec<(Bytes, f64)>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state.zset_pop(key, end, count)?.unwrap_or_default())
+    }
+
+    /// Stores the union or the intersection of sorted sets and sets in `destination`,
+    /// overwriting whatever was there, an empty result deletes it. Members of sets
+    /// have score 1. Returns the size of the result.
+    pub(crate) async fn zset_combine_store(
+        &self,
+        destination: &Bytes,
+        keys: &[Bytes],
+        weights: &[f64],
+        aggregate: Aggregate,
+        op: SetOp,
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let sources = keys
+            .iter()
+            .map(|key| state.scored_members(key))
+            .collect::<Result<Vec<_>, CmdErrors>>()?;
+        let result = combine(sources, weights, aggregate, op);
+        let len = result.len();
+        match len {
+            0 => {
+                state.remove(destination);
+            }
+            _ => {
+                state.insert(
+                    destination.clone(),
+                    Entry {
+                        value: Value::SortedSet(result),
+                        expires_at: None,
+                    },
+                );
+                state.wake_blocked(destination);
+            }
+        }
+        Ok(len)
+    }
+}
+
+impl State {
+    /// `None` when there is no such key.
+    pub(super) fn zset_pop(
+        &mut self,
+        key: &Bytes,
+        end: ZSetEnd,
+        count: usize,
+    ) -> Result<Option<Vec<(Bytes, f64)>>, CmdErrors> {
+        let Some(zset) = self.get_value_mut::<SortedSet>(key)? else {
+            return Ok(None);
+        };
+        let popped = zset.pop(end, count);
+        self.remove_if_empty(key);
+        Ok(Some(popped))
+    }
+
+    /// Members and scores of a sorted set, or members of a set with score 1.
+    fn scored_members(&mut self, key: &Bytes) -> Result<Option<Vec<(Bytes, f64)>>, CmdErrors> {
+        match self.live_entry(key).map(|entry| &entry.value) {
+            None => Ok(None),
+            Some(Value::SortedSet(zset)) => Ok(Some(zset.all())),
+            Some(Value::Set(set)) => Ok(Some(
+                set.members()
+                    .into_iter()
+                    .map(|member| (member, 1.0))
+                    .collect(),
+            )),
+            Some(_) => Err(CmdErrors::WrongType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scored(pairs: &[(&'static str, f64)]) -> Vec<(Bytes, f64)> {
+        pairs
+            .iter()
+            .map(|(member, score)| (Bytes::from(*member), *score))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_zset_range() {
+        let storage = Storage::setup();
+        let key = Bytes::from("board");
+        let pairs: Vec<(f64, Bytes)> = scored(&[("a", 1.0), ("b", 2.0), ("c", 3.0), ("d", 4.0)])
+            .into_iter()
+            .map(|(member, score)| (score, member))
+            .collect();
+        storage
+            .zset_add(&key, &pairs, ZAddOptions::default())
+            .await
+            .unwrap();
+
+        let by_rank = ZRange::Rank { start: 0, stop: 1 };
+        assert_eq!(
+            storage.zset_range(&key, &by_rank, true, None).await,
+            Ok(scored(&[("d", 4.0), ("c", 3.0)]))
+        );
+        let by_score = ZRange::Score {
+            min: ScoreBound {
+                value: 1.0,
+                exclusive: true,
+            },
+            max: ScoreBound {
+                value: f64::INFINITY,
+                exclusive: false,
+            },
+        };
+        assert_eq!(
+            storage
+                .zset_range(&key, &by_score, false, Some((1, 1)))
+                .await,
+            Ok(scored(&[("c", 3.0)]))
+        );
+        assert_eq!(
+            storage
+                .zset_range(&key, &by_score, true, Some((1, -1)))
+                .await,
+            Ok(scored(&[("c", 3.0), ("b", 2.0)]))
+        );
+        let by_lex = ZRange::Lex {
+            min: LexBound::Exclusive(Bytes::from("a")),
+            max: LexBound::Inclusive(Bytes::from("c")),
+        };
+        assert_eq!(
+            storage.zset_range(&key, &by_lex, false, None).await,
+            Ok(scored(&[("b", 2.0), ("c", 3.0)]))
+        );
+        assert_eq!(
+            storage.zset_rank(&key, &Bytes::from("c")).await,
+            Ok(Some((2, 3.0)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_zset_add_options() {
+        let storage = Storage::setup();
+        let key = Bytes::from("board");
+        let member = Bytes::from("a");
+        let add = |score: f64, options: ZAddOptions| {
+            let pairs = [(score, member.clone())];
+            let (storage, key) = (storage.clone(), key.clone());
+            async move { storage.zset_add(&key, &pairs, options).await }
+        };
+
+        let only_existing = ZAddOptions {
+            only_existing: true,
+            ..ZAddOptions::default()
+        };
+        assert_eq!(add(1.0, only_existing).await, Ok(ZAdded::default()));
+        assert_eq!(storage.key_type(&key).await, None);
+
+        add(5.0, ZAddOptions::default()).await.unwrap();
+        let greater = ZAddOptions {
+            greater: true,
+            increment: true,
+            ..ZAddOptions::default()
+        };
+        assert_eq!(add(-1.0, greater).await.map(|added| added.score), Ok(None));
+        assert_eq!(
+            add(2.0, greater).await,
+            Ok(ZAdded {
+                added: 0,
+                updated: 1,
+                score: Some(7.0)
+            })
+        );
+
+        let increment = ZAddOptions {
+            increment: true,
+            ..ZAddOptions::default()
+        };
+        add(f64::INFINITY, increment).await.unwrap();
+        assert_eq!(
+            add(f64::NEG_INFINITY, increment).await,
+            Err(CmdErrors::ScoreIsNaN)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_zset_combine_store() {
+        let storage = Storage::setup();
+        let (zset, set, out) = (Bytes::from("z"), Bytes::from("s"), Bytes::from("out"));
+        storage
+            .zset_add(
+                &zset,
+                &[(1.0, Bytes::from("a")), (2.0, Bytes::from("b"))],
+                ZAddOptions::default(),
+            )
+            .await
+            .unwrap();
+        storage
+            .set_add(&set, &[Bytes::from("b"), Bytes::from("c")])
+            .await
+            .unwrap();
+
+        let keys = [zset.clone(), set.clone()];
+        assert_eq!(
+            storage
+                .zset_combine_store(&out, &keys, &[2.0, 10.0], Aggregate::Sum, SetOp::Union)
+                .await,
+            Ok(3)
+        );
+        let all = ZRange::Rank { start: 0, stop: -1 };
+        assert_eq!(
+            storage.zset_range(&out, &all, false, None).await,
+            Ok(scored(&[("a", 2.0), ("c", 10.0), ("b", 14.0)]))
+        );
+        assert_eq!(
+            storage
+                .zset_combine_store(&out, &keys, &[], Aggregate::Max, SetOp::Intersection)
+                .await,
+            Ok(1)
+        );
+        assert_eq!(
+            storage.zset_range(&out, &all, false, None).await,
+            Ok(scored(&[("b", 2.0)]))
+        );
+    }
+}
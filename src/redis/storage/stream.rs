@@ -0,0 +1,485 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::Bound;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+
+use super::stream_group::ConsumerGroup;
+use super::{State, Storage};
+use crate::redis::CmdErrors;
+
+// entries per radix tree node in Redis, approximate trimming removes only whole nodes
+const STREAM_NODE_MAX_ENTRIES: usize = 100;
+// default `LIMIT` of approximate trimming, like in Redis
+const STREAM_TRIM_DEFAULT_LIMIT: usize = 100 * STREAM_NODE_MAX_ENTRIES;
+
+/// `ms-seq` ID of a stream entry, entries are ordered by it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub(crate) const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub(crate) const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    /// The smallest ID after this one.
+    pub(crate) fn next(self) -> Option<StreamId> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(StreamId { ms: self.ms, seq }),
+            None => Some(StreamId {
+                ms: self.ms.checked_add(1)?,
+                seq: 0,
+            }),
+        }
+    }
+
+    /// The biggest ID before this one.
+    pub(crate) fn prev(self) -> Option<StreamId> {
+        match self.seq.checked_sub(1) {
+            Some(seq) => Some(StreamId { ms: self.ms, seq }),
+            None => Some(StreamId {
+                ms: self.ms.checked_sub(1)?,
+                seq: u64::MAX,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+/// Milliseconds since the Unix epoch, entry IDs and delivery times use them.
+pub(super) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_millis() as u64)
+}
+
+/// `fields` is empty only for an entry that was deleted from the stream while
+/// still pending in a consumer group, `XADD` never adds entries without fields.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StreamEntry {
+    pub id: StreamId,
+    pub fields: Vec<(Bytes, Bytes)>,
+}
+
+/// ID argument of `XADD`: `*`, `ms-*` or `ms-seq`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum NewStreamId {
+    Auto,
+    // milliseconds are given, the sequence number is picked
+    AutoSeq(u64),
+    Explicit(StreamId),
+}
+
+/// `MAXLEN | MINID` strategies of `XADD` and `XTRIM`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TrimStrategy {
+    MaxLen(usize),
+    // entries with smaller IDs are removed
+    MinId(StreamId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct StreamTrim {
+    pub strategy: TrimStrategy,
+    // `~`: only whole nodes are removed, so a few more entries may be kept
+    pub approximate: bool,
+    // `LIMIT`: at most this many entries are removed, zero means no limit
+    pub limit: Option<usize>,
+}
+
+/// Where `XREAD` starts reading a stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ReadFrom {
+    // `$`: only entries added after the command
+    End,
+    After(StreamId),
+}
+
+/// Append-only log of entries. Deleted entries are gone, but their IDs
+/// are never given out again. Consumer groups of the stream live in it.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Stream {
+    pub(super) entries: BTreeMap<StreamId, Vec<(Bytes, Bytes)>>,
+    pub(super) last_id: StreamId,
+    // entries ever added, deleted and trimmed ones included
+    pub(super) entries_added: u64,
+    // biggest ID deleted with `XDEL`, trimming doesn't count
+    pub(super) max_deleted_id: StreamId,
+    pub(super) groups: BTreeMap<Bytes, ConsumerGroup>,
+}
+
+impl Stream {
+    pub(super) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn new_id(&self, id: NewStreamId) -> Result<StreamId, CmdErrors> {
+        let id = match id {
+            NewStreamId::Auto => {
+                let now = now_ms();
+                // clock going backwards doesn't break the order
+                match now > self.last_id.ms {
+                    true => StreamId { ms: now, seq: 0 },
+                    false => self.last_id.next().ok_or(CmdErrors::StreamExhausted)?,
+                }
+            }
+            NewStreamId::AutoSeq(ms) if ms == self.last_id.ms => {
+                self.last_id.next().ok_or(CmdErrors::StreamIdTooSmall)?
+            }
+            NewStreamId::AutoSeq(ms) => StreamId { ms, seq: 0 },
+            NewStreamId::Explicit(id) => id,
+        };
+        match id {
+            StreamId::MIN => Err(CmdErrors::StreamIdZero),
+            id if id <= self.last_id => Err(CmdErrors::StreamIdTooSmall),
+            id => Ok(id),
+        }
+    }
+
+    fn add(&mut self, id: NewStreamId, fields: Vec<(Bytes, Bytes)>) -> Result<StreamId, CmdErrors> {
+        let id = self.new_id(id)?;
+        self.entries.insert(id, fields);
+        self.last_id = id;
+        self.entries_added += 1;
+        Ok(id)
+    }
+
+    /// Returns how many entries were removed.
+    fn trim(&mut self, trim: StreamTrim) -> usize {
+        let excess = match trim.strategy {
+            TrimStrategy::MaxLen(max_len) => self.len().saturating_sub(max_len),
+            TrimStrategy::MinId(min_id) => self.entries.range(..min_id).count(),
+        };
+        let removed = match trim.approximate {
+            true => {
+                let limit = match trim.limit {
+                    None => STREAM_TRIM_DEFAULT_LIMIT,
+                    Some(0) => usize::MAX,
+                    Some(limit) => limit,
+                };
+                excess.min(limit) / STREAM_NODE_MAX_ENTRIES * STREAM_NODE_MAX_ENTRIES
+            }
+            false => excess,
+        };
+        for _ in 0..removed {
+            self.entries.pop_first();
+        }
+        removed
+    }
+
+    pub(super) fn first_id(&self) -> StreamId {
+        self.entries
+            .first_key_value()
+            .map_or(StreamId::MIN, |(id, _)| *id)
+    }
+
+    /// Whether an entry at or after `from` was deleted with `XDEL`.
+    pub(super) fn has_tombstones(&self, from: StreamId) -> bool {
+        if self.entries.is_empty() || self.max_deleted_id == StreamId::MIN {
+            return false;
+        }
+        from.max(self.first_id()) <= self.max_deleted_id && self.max_deleted_id <= self.last_id
+    }
+
+    /// How many entries were added up to `id`, `None` when deleted entries make it unknown.
+    pub(super) fn entries_up_to(&self, id: StreamId) -> Option<u64> {
+        if self.entries_added == 0 {
+            return Some(0);
+        }
+        if self.entries.is_empty() || id >= self.last_id {
+            return Some(self.entries_added);
+        }
+        let first_id = self.first_id();
+        if self.max_deleted_id == StreamId::MIN || self.max_deleted_id < first_id {
+            let len = self.len() as u64;
+            if id < first_id {
+                return Some(self.entries_added - len);
+            }
+            if id == first_id {
+                return Some(self.entries_added - len + 1);
+            }
+        }
+        None
+    }
+
+    /// Entries in the inclusive `start..=end` range, from the end when `rev` is set.
+    fn range(
+        &self,
+        start: StreamId,
+        end: StreamId,
+        rev: bool,
+        count: Option<usize>,
+    ) -> Vec<StreamEntry> {
+        if start > end {
+            return vec![];
+        }
+        let range = self.entries.range(start..=end);
+        let count = count.unwrap_or(usize::MAX);
+        let to_entry = |(id, fields): (&StreamId, &Vec<(Bytes, Bytes)>)| StreamEntry {
+            id: *id,
+            fields: fields.clone(),
+        };
+        match rev {
+            true => range.rev().take(count).map(to_entry).collect(),
+            false => range.take(count).map(to_entry).collect(),
+        }
+    }
+
+    /// Entries with IDs greater than `after`.
+    pub(super) fn read_after(&self, after: StreamId, count: Option<usize>) -> Vec<StreamEntry> {
+        self.entries
+            .range((Bound::Excluded(after), Bound::Unbounded))
+            .take(count.unwrap_or(usize::MAX))
+            .map(|(id, fields)| StreamEntry {
+                id: *id,
+                fields: fields.clone(),
+            })
+            .collect()
+    }
+}
+
+impl Storage {
+    /// Appends an entry and trims the stream, returns the ID of the entry.
+    /// `None` when there is no stream and `no_make_stream` is set.
+    pub(crate) async fn stream_add(
+        &self,
+        key: &Bytes,
+        id: NewStreamId,
+        fields: Vec<(Bytes, Bytes)>,
+        no_make_stream: bool,
+        trim: Option<StreamTrim>,
+    ) -> Result<Option<StreamId>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        if state.get_value::<Stream>(key)?.is_none() {
+            if no_make_stream {
+                return Ok(None);
+            }
+            // a rejected ID doesn't create the stream
+            Stream::default().new_id(id)?;
+        }
+        let stream = state.get_or_insert_value::<Stream>(key)?;
+        let id = stream.add(id, fields)?;
+        if let Some(trim) = trim {
+            stream.trim(trim);
+        }
+        state.wake_blocked(key);
+        Ok(Some(id))
+    }
+
+    pub(crate) async fn stream_len(&self, key: &Bytes) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state.get_value::<Stream>(key)?.map_or(0, Stream::len))
+    }
+
+    /// Entries with IDs in the inclusive `start..=end` range.
+    pub(crate) async fn stream_range(
+        &self,
+        key: &Bytes,
+        start: StreamId,
+        end: StreamId,
+        rev: bool,
+        count: Option<usize>,
+    ) -> Result<Vec<StreamEntry>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state
+            .get_value::<Stream>(key)?
+            .map_or(vec![], |stream| stream.range(start, end, rev, count)))
+    }
+
+    /// Returns how many entries were removed.
+    pub(crate) async fn stream_trim(
+        &self,
+        key: &Bytes,
+        trim: StreamTrim,
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state
+            .get_value_mut::<Stream>(key)?
+            .map_or(0, |stream| stream.trim(trim)))
+    }
+
+    /// Returns how many of the entries existed.
+    pub(crate) async fn stream_delete(
+        &self,
+        key: &Bytes,
+        ids: &[StreamId],
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(stream) = state.get_value_mut::<Stream>(key)? else {
+            return Ok(0);
+        };
+        let mut deleted = 0;
+        for id in ids {
+            if stream.entries.remove(id).is_some() {
+                stream.max_deleted_id = stream.max_deleted_id.max(*id);
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// New entries of every stream, only streams that have some are in the result.
+    /// Also returns the IDs the streams were read after, `$` resolved to the last ID,
+    /// so a blocking read can wait for entries after them.
+    pub(crate) async fn stream_read(
+        &self,
+        keys: &[Bytes],
+        from: &[ReadFrom],
+        count: Option<usize>,
+    ) -> Result<(Vec<(Bytes, Vec<StreamEntry>)>, HashMap<Bytes, StreamId>), CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let mut result = vec![];
+        let mut after_ids = HashMap::new();
+        for (key, from) in keys.iter().zip(from) {
+            let stream = state.get_value::<Stream>(key)?;
+            let after = match from {
+                ReadFrom::End => stream.map_or(StreamId::MIN, |stream| stream.last_id),
+                ReadFrom::After(id) => *id,
+            };
+            after_ids.insert(key.clone(), after);
+
+            let entries = stream.map_or(vec![], |stream| stream.read_after(after, count));
+            if !entries.is_empty() {
+                result.push((key.clone(), entries));
+            }
+        }
+        Ok((result, after_ids))
+    }
+}
+
+impl State {
+    /// Entries after `after`, `None` when there is no such stream.
+    pub(super) fn stream_read_after(
+        &mut self,
+        key: &Bytes,
+        after: StreamId,
+        count: Option<usize>,
+    ) -> Result<Option<Vec<StreamEntry>>, CmdErrors> {
+        Ok(self
+            .get_value::<Stream>(key)?
+            .map(|stream| stream.read_after(after, count)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(ms: u64, seq: u64) -> StreamId {
+        StreamId { ms, seq }
+    }
+
+    fn fields() -> Vec<(Bytes, Bytes)> {
+        vec![(Bytes::from("field"), Bytes::from("value"))]
+    }
+
+    #[test]
+    fn test_stream_ids() {
+        let mut stream = Stream::default();
+        assert_eq!(
+            stream.add(NewStreamId::Explicit(StreamId::MIN), fields()),
+            Err(CmdErrors::StreamIdZero)
+        );
+        assert_eq!(stream.add(NewStreamId::AutoSeq(0), fields()), Ok(id(0, 1)));
+        assert_eq!(
+            stream.add(NewStreamId::Explicit(id(5, 0)), fields()),
+            Ok(id(5, 0))
+        );
+        assert_eq!(stream.add(NewStreamId::AutoSeq(5), fields()), Ok(id(5, 1)));
+        assert_eq!(
+            stream.add(NewStreamId::Explicit(id(5, 1)), fields()),
+            Err(CmdErrors::StreamIdTooSmall)
+        );
+        assert_eq!(
+            stream.add(NewStreamId::AutoSeq(4), fields()),
+            Err(CmdErrors::StreamIdTooSmall)
+        );
+        let auto = stream.add(NewStreamId::Auto, fields()).unwrap();
+        assert!(auto > id(5, 1));
+    }
+
+    #[test]
+    fn test_stream_entry_counting() {
+        let mut stream = Stream::default();
+        for ms in 1..=5 {
+            stream
+                .add(NewStreamId::Explicit(id(ms, 0)), fields())
+                .unwrap();
+        }
+        stream.trim(StreamTrim {
+            strategy: TrimStrategy::MaxLen(4),
+            approximate: false,
+            limit: None,
+        });
+        assert_eq!(stream.entries_up_to(id(1, 0)), Some(1));
+        assert_eq!(stream.entries_up_to(id(2, 0)), Some(2));
+        assert_eq!(stream.entries_up_to(id(3, 0)), None);
+        assert_eq!(stream.entries_up_to(id(5, 0)), Some(5));
+
+        stream.entries.remove(&id(3, 0));
+        stream.max_deleted_id = id(3, 0);
+        assert!(stream.has_tombstones(id(2, 0)));
+        assert!(!stream.has_tombstones(id(4, 0)));
+        // a deleted entry may be anywhere, so counting from the first one is not possible
+        assert_eq!(stream.entries_up_to(id(2, 0)), None);
+    }
+
+    #[test]
+    fn test_stream_trim() {
+        let mut stream = Stream::default();
+        for ms in 1..=250 {
+            stream
+                .add(NewStreamId::Explicit(id(ms, 0)), fields())
+                .unwrap();
+        }
+
+        let approximate = StreamTrim {
+            strategy: TrimStrategy::MaxLen(60),
+            approximate: true,
+            limit: None,
+        };
+        // only whole nodes of 100 entries go away
+        assert_eq!(stream.trim(approximate), 100);
+        assert_eq!(stream.len(), 150);
+
+        let exact = StreamTrim {
+            strategy: TrimStrategy::MinId(id(201, 0)),
+            approximate: false,
+            limit: None,
+        };
+        assert_eq!(stream.trim(exact), 100);
+        assert_eq!(
+            stream.range(StreamId::MIN, StreamId::MAX, false, Some(1))[0].id,
+            id(201, 0)
+        );
+        assert_eq!(stream.read_after(id(249, 0), None).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_add_creates_no_stream() {
+        let storage = Storage::setup();
+        let key = Bytes::from_static(b"missing");
+        let added = storage
+            .stream_add(
+                &key,
+                NewStreamId::Explicit(StreamId::MIN),
+                fields(),
+                false,
+                None,
+            )
+            .await;
+        assert_eq!(added, Err(CmdErrors::StreamIdZero));
+        assert_eq!(storage.key_type(&key).await, None);
+    }
+}
@@ -0,0 +1,879 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bytes::Bytes;
+
+use super::stream::{now_ms, Stream};
+use super::{ReadFrom, State, Storage, StreamEntry, StreamId};
+use crate::redis::CmdErrors;
+
+/// An entry delivered to a consumer and not acknowledged yet.
+#[derive(Debug, Clone)]
+struct PendingEntry {
+    consumer: Bytes,
+    // milliseconds since the Unix epoch
+    delivered_at: u64,
+    delivery_count: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Consumer {
+    // last time any command used the consumer
+    seen_at: u64,
+    // last time the consumer got entries, `None` when it never did
+    active_at: Option<u64>,
+    pending: BTreeSet<StreamId>,
+}
+
+impl Consumer {
+    fn new(now: u64) -> Consumer {
+        Consumer {
+            seen_at: now,
+            active_at: None,
+            pending: BTreeSet::new(),
+        }
+    }
+}
+
+/// Consumers sharing the entries of a stream. Every entry is delivered to one
+/// of them and stays pending until it is acknowledged, so it can be claimed by
+/// another consumer when the first one fails.
+#[derive(Debug, Clone)]
+pub(super) struct ConsumerGroup {
+    last_delivered: StreamId,
+    // entries of the stream the group has read, `None` when it is not known
+    entries_read: Option<u64>,
+    pending: BTreeMap<StreamId, PendingEntry>,
+    consumers: BTreeMap<Bytes, Consumer>,
+}
+
+impl ConsumerGroup {
+    /// Creates the consumer when it doesn't exist yet.
+    fn consumer(&mut self, name: &Bytes, now: u64) -> &mut Consumer {
+        let consumer = self
+            .consumers
+            .entry(name.clone())
+            .or_insert_with(|| Consumer::new(now));
+        consumer.seen_at = now;
+        consumer
+    }
+
+    /// Makes the entry pending for the consumer, taking it from whoever had it.
+    fn assign(&mut self, id: StreamId, consumer: &Bytes, delivered_at: u64, delivery_count: u64) {
+        let pending = PendingEntry {
+            consumer: consumer.clone(),
+            delivered_at,
+            delivery_count,
+        };
+        if let Some(previous) = self.pending.insert(id, pending) {
+            if let Some(owner) = self.consumers.get_mut(&previous.consumer) {
+                owner.pending.remove(&id);
+            }
+        }
+        self.consumers
+            .entry(consumer.clone())
+            .or_insert_with(|| Consumer::new(delivered_at))
+            .pending
+            .insert(id);
+    }
+
+    /// Returns `false` when the entry was not pending.
+    fn acknowledge(&mut self, id: StreamId) -> bool {
+        let Some(pending) = self.pending.remove(&id) else {
+            return false;
+        };
+        if let Some(owner) = self.consumers.get_mut(&pending.consumer) {
+            owner.pending.remove(&id);
+        }
+        true
+    }
+}
+
+fn no_group(key: &Bytes, group: &Bytes) -> CmdErrors {
+    CmdErrors::NoGroup {
+        key: String::from_utf8_lossy(key).into_owned(),
+        group: String::from_utf8_lossy(group).into_owned(),
+    }
+}
+
+/// IDs of `XREADGROUP`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum GroupReadFrom {
+    // `>`: entries never delivered to the group
+    New,
+    // entries pending for the consumer after the ID
+    Pending(StreamId),
+}
+
+/// `IDLE`, `TIME`, `RETRYCOUNT`, `FORCE`, `JUSTID` and `LASTID` of `XCLAIM`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct ClaimOptions {
+    // milliseconds, the delivery time is set this far in the past
+    pub idle: Option<u64>,
+    // delivery time in milliseconds since the Unix epoch
+    pub time: Option<u64>,
+    pub retry_count: Option<u64>,
+    // entries of the stream that are not pending yet are claimed too
+    pub force: bool,
+    // the delivery count doesn't change
+    pub just_id: bool,
+    pub last_id: Option<StreamId>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AutoClaimed {
+    // where the next call should start, `0-0` when the whole list was scanned
+    pub next: StreamId,
+    pub claimed: Vec<StreamEntry>,
+    // pending entries that were deleted from the stream, they are not pending anymore
+    pub deleted: Vec<StreamId>,
+}
+
+/// Summary form of `XPENDING`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct PendingSummary {
+    pub count: usize,
+    pub first: Option<StreamId>,
+    pub last: Option<StreamId>,
+    // consumers with pending entries and how many each of them has
+    pub consumers: Vec<(Bytes, usize)>,
+}
+
+/// Filters of the extended form of `XPENDING`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PendingFilter {
+    pub min_idle: Option<u64>,
+    pub start: StreamId,
+    pub end: StreamId,
+    pub count: usize,
+    pub consumer: Option<Bytes>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PendingInfo {
+    pub id: StreamId,
+    pub consumer: Bytes,
+    // milliseconds since the last delivery
+    pub idle: u64,
+    pub delivery_count: u64,
+}
+
+/// `XINFO STREAM` reply.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StreamInfo {
+    pub len: usize,
+    pub last_generated_id: StreamId,
+    pub max_deleted_id: StreamId,
+    pub entries_added: u64,
+    pub first_id: StreamId,
+    pub groups: usize,
+    pub first: Option<StreamEntry>,
+    pub last: Option<StreamEntry>,
+}
+
+/// `XINFO GROUPS` reply item.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GroupInfo {
+    pub name: Bytes,
+    pub consumers: usize,
+    pub pending: usize,
+    pub last_delivered: StreamId,
+    pub entries_read: Option<u64>,
+    // entries not delivered to the group yet, `None` when it is not known
+    pub lag: Option<u64>,
+}
+
+/// `XINFO CONSUMERS` reply item.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ConsumerInfo {
+    pub name: Bytes,
+    pub pending: usize,
+    // milliseconds since the consumer was last seen
+    pub idle: u64,
+    // milliseconds since the consumer last got entries, `None` when it never did
+    pub inactive: Option<u64>,
+}
+
+impl Stream {
+    fn group_mut(&mut self, key: &Bytes, name: &Bytes) -> Result<&mut ConsumerGroup, CmdErrors> {
+        self.groups.get_mut(name).ok_or_else(|| no_group(key, name))
+    }
+
+    fn group_lag(&self, group: &ConsumerGroup) -> Option<u64> {
+        if self.entries_added == 0 {
+            return Some(0);
+        }
+        match group.entries_read {
+            Some(read) if !self.has_tombstones(group.last_delivered) => {
+                Some(self.entries_added.saturating_sub(read))
+            }
+            _ => self
+                .entries_up_to(group.last_delivered)
+                .map(|read| self.entries_added - read),
+        }
+    }
+
+    /// Delivers entries the group has not seen yet to the consumer, they become
+    /// pending for it unless `no_ack` is set.
+    fn deliver(
+        &mut self,
+        key: &Bytes,
+        name: &Bytes,
+        consumer: &Bytes,
+        count: Option<usize>,
+        no_ack: bool,
+    ) -> Result<Vec<StreamEntry>, CmdErrors> {
+        let group = self.groups.get(name).ok_or_else(|| no_group(key, name))?;
+        let entries = self.read_after(group.last_delivered, count);
+        let mut entries_read = group.entries_read;
+        for entry in &entries {
+            entries_read = match entries_read {
+                Some(read) if !self.has_tombstones(entry.id) => Some(read + 1),
+                _ => self.entries_up_to(entry.id),
+            };
+        }
+
+        let now = now_ms();
+        let group = self.group_mut(key, name)?;
+        group.consumer(consumer, now);
+        let Some(last) = entries.last() else {
+            return Ok(entries);
+        };
+        group.last_delivered = last.id;
+        group.entries_read = entries_read;
+        group.consumer(consumer, now).active_at = Some(now);
+        if !no_ack {
+            for entry in &entries {
+                group.assign(entry.id, consumer, now, 1);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Entries pending for the consumer after `after`, they count as delivered again.
+    fn deliver_pending(
+        &mut self,
+        key: &Bytes,
+        name: &Bytes,
+        consumer: &Bytes,
+        after: StreamId,
+        count: Option<usize>,
+    ) -> Result<Vec<StreamEntry>, CmdErrors> {
+        let now = now_ms();
+        let Stream {
+            entries, groups, ..
+        } = self;
+        let group = groups.get_mut(name).ok_or_else(|| no_group(key, name))?;
+        let pending = &group.consumer(consumer, now).pending;
+        let ids: Vec<StreamId> = match after.next() {
+            Some(from) => pending
+                .range(from..)
+                .take(count.unwrap_or(usize::MAX))
+                .copied()
+                .collect(),
+            None => vec![],
+        };
+
+        let mut delivered = vec![];
+        for id in ids {
+            if let Some(pending) = group.pending.get_mut(&id) {
+                pending.delivered_at = now;
+                pending.delivery_count += 1;
+            }
+            delivered.push(StreamEntry {
+                id,
+                fields: entries.get(&id).cloned().unwrap_or_default(),
+            });
+        }
+        Ok(delivered)
+    }
+}
+
+impl Storage {
+    /// `XGROUP CREATE`, the group starts reading after `from`.
+    pub(crate) async fn stream_group_create(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+        from: ReadFrom,
+        make_stream: bool,
+        entries_read: Option<u64>,
+    ) -> Result<(), CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        if !make_stream && state.get_value::<Stream>(key)?.is_none() {
+            return Err(CmdErrors::XGroupKeyMissing);
+        }
+        let stream = state.get_or_insert_value::<Stream>(key)?;
+        if stream.groups.contains_key(name) {
+            return Err(CmdErrors::BusyGroup);
+        }
+
+        let last_delivered = match from {
+            ReadFrom::End => stream.last_id,
+            ReadFrom::After(id) => id,
+        };
+        let entries_read = entries_read.or_else(|| stream.entries_up_to(last_delivered));
+        stream.groups.insert(
+            name.clone(),
+            ConsumerGroup {
+                last_delivered,
+                entries_read,
+                pending: BTreeMap::new(),
+                consumers: BTreeMap::new(),
+            },
+        );
+        Ok(())
+    }
+
+    /// `XGROUP SETID`, entries pending for the consumers stay pending.
+    pub(crate) async fn stream_group_set_id(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+        from: ReadFrom,
+        entries_read: Option<u64>,
+    ) -> Result<(), CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let stream = state.existing_stream(key)?;
+        let last_id = stream.last_id;
+        let group = stream.group_mut(key, name)?;
+        group.last_delivered = match from {
+            ReadFrom::End => last_id,
+            ReadFrom::After(id) => id,
+        };
+        group.entries_read = entries_read;
+        Ok(())
+    }
+
+    /// Returns whether there was such group. Clients blocked reading from it get an error.
+    pub(crate) async fn stream_group_destroy(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+    ) -> Result<bool, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let destroyed = state.existing_stream(key)?.groups.remove(name).is_some();
+        if destroyed {
+            state.wake_blocked(key);
+        }
+        Ok(destroyed)
+    }
+
+    /// Returns whether the consumer is new.
+    pub(crate) async fn stream_group_create_consumer(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+        consumer: &Bytes,
+    ) -> Result<bool, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let group = state.existing_stream(key)?.group_mut(key, name)?;
+        if group.consumers.contains_key(consumer) {
+            return Ok(false);
+        }
+        group.consumer(consumer, now_ms());
+        Ok(true)
+    }
+
+    /// Returns how many entries were pending for the consumer, they are not
+    /// pending anymore.
+    pub(crate) async fn stream_group_delete_consumer(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+        consumer: &Bytes,
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let group = state.existing_stream(key)?.group_mut(key, name)?;
+        let Some(removed) = group.consumers.remove(consumer) else {
+            return Ok(0);
+        };
+        for id in &removed.pending {
+            group.pending.remove(id);
+        }
+        Ok(removed.pending.len())
+    }
+
+    /// `XREADGROUP`, new entries are left out for streams that have none, pending
+    /// entries are always there.
+    pub(crate) async fn stream_group_read(
+        &self,
+        keys: &[Bytes],
+        from: &[GroupReadFrom],
+        name: &Bytes,
+        consumer: &Bytes,
+        count: Option<usize>,
+        no_ack: bool,
+    ) -> Result<Vec<(Bytes, Vec<StreamEntry>)>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        // nothing is delivered when any of the groups is missing
+        for key in keys {
+            state.existing_group_stream(key, name)?;
+        }
+
+        let mut result = vec![];
+        for (key, from) in keys.iter().zip(from) {
+            let stream = state.existing_group_stream(key, name)?;
+            match from {
+                GroupReadFrom::New => {
+                    let entries = stream.deliver(key, name, consumer, count, no_ack)?;
+                    if !entries.is_empty() {
+                        result.push((key.clone(), entries));
+                    }
+                }
+                GroupReadFrom::Pending(after) => {
+                    let entries = stream.deliver_pending(key, name, consumer, *after, count)?;
+                    result.push((key.clone(), entries));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns how many of the entries were pending.
+    pub(crate) async fn stream_ack(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+        ids: &[StreamId],
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(group) = state
+            .get_value_mut::<Stream>(key)?
+            .and_then(|stream| stream.groups.get_mut(name))
+        else {
+            return Ok(0);
+        };
+        Ok(ids.iter().filter(|id| group.acknowledge(**id)).count())
+    }
+
+    pub(crate) async fn stream_pending_summary(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+    ) -> Result<PendingSummary, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let group = state
+            .existing_group_stream(key, name)?
+            .group_mut(key, name)?;
+        Ok(PendingSummary {
+            count: group.pending.len(),
+            first: group.pending.keys().next().copied(),
+            last: group.pending.keys().next_back().copied(),
+            consumers: group
+                .consumers
+                .iter()
+                .filter(|(_, consumer)| !consumer.pending.is_empty())
+                .map(|(name, consumer)| (name.clone(), consumer.pending.len()))
+                .collect(),
+        })
+    }
+
+    pub(crate) async fn stream_pending(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+        filter: &PendingFilter,
+    ) -> Result<Vec<PendingInfo>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let group = state
+            .existing_group_stream(key, name)?
+            .group_mut(key, name)?;
+        if filter.start > filter.end {
+            return Ok(vec![]);
+        }
+
+        let now = now_ms();
+        let ids: Box<dyn Iterator<Item = &StreamId>> = match &filter.consumer {
+            Some(consumer) => match group.consumers.get(consumer) {
+                Some(consumer) => Box::new(consumer.pending.range(filter.start..=filter.end)),
+                None => return Ok(vec![]),
+            },
+            None => Box::new(
+                group
+                    .pending
+                    .range(filter.start..=filter.end)
+                    .map(|(id, _)| id),
+            ),
+        };
+        Ok(ids
+            .filter_map(|id| {
+                let pending = group.pending.get(id)?;
+                let idle = now.saturating_sub(pending.delivered_at);
+                (idle >= filter.min_idle.unwrap_or(0)).then(|| PendingInfo {
+                    id: *id,
+                    consumer: pending.consumer.clone(),
+                    idle,
+                    delivery_count: pending.delivery_count,
+                })
+            })
+            .take(filter.count)
+            .collect())
+    }
+
+    /// Gives the consumer the pending entries that were idle for at least `min_idle`
+    /// milliseconds and returns them. Entries deleted from the stream stop being pending.
+    pub(crate) async fn stream_claim(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+        consumer: &Bytes,
+        min_idle: u64,
+        ids: &[StreamId],
+        options: ClaimOptions,
+    ) -> Result<Vec<StreamEntry>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Stream {
+            entries, groups, ..
+        } = state.existing_group_stream(key, name)?;
+        let group = groups.get_mut(name).ok_or_else(|| no_group(key, name))?;
+
+        let now = now_ms();
+        let delivered_at = options
+            .time
+            .or(options.idle.map(|idle| now.saturating_sub(idle)))
+            .unwrap_or(now);
+        group.consumer(consumer, now);
+
+        let mut claimed = vec![];
+        for id in ids {
+            let Some(fields) = entries.get(id) else {
+                group.acknowledge(*id);
+                continue;
+            };
+            let delivery_count = match group.pending.get(id) {
+                None if options.force => 0,
+                None => continue,
+                Some(pending) if now.saturating_sub(pending.delivered_at) < min_idle => continue,
+                Some(pending) => pending.delivery_count,
+            };
+            let delivery_count = match options.retry_count {
+                Some(retry_count) => retry_count,
+                None if options.just_id => delivery_count,
+                None => delivery_count + 1,
+            };
+            group.assign(*id, consumer, delivered_at, delivery_count);
+            claimed.push(StreamEntry {
+                id: *id,
+                fields: fields.clone(),
+            });
+        }
+
+        if !claimed.is_empty() {
+            group.consumer(consumer, now).active_at = Some(now);
+        }
+        if let Some(last_id) = options.last_id {
+            group.last_delivered = group.last_delivered.max(last_id);
+        }
+        Ok(claimed)
+    }
+
+    /// Claims up to `count` entries idle for at least `min_idle` milliseconds,
+    /// scanning the pending entries from `start`. Like in Redis, at most ten
+    /// times `count` entries are looked at.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn stream_auto_claim(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+        consumer: &Bytes,
+        min_idle: u64,
+        start: StreamId,
+        count: usize,
+        just_id: bool,
+    ) -> Result<AutoClaimed, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Stream {
+            entries, groups, ..
+        } = state.existing_group_stream(key, name)?;
+        let group = groups.get_mut(name).ok_or_else(|| no_group(key, name))?;
+
+        let now = now_ms();
+        group.consumer(consumer, now);
+        let max_attempts = count.saturating_mul(10);
+        let candidates: Vec<StreamId> = group
+            .pending
+            .range(start..)
+            .map(|(id, _)| *id)
+            .take(max_attempts.saturating_add(1))
+            .collect();
+
+        let mut result = AutoClaimed {
+            next: StreamId::MIN,
+            claimed: vec![],
+            deleted: vec![],
+        };
+        for (attempt, id) in candidates.into_iter().enumerate() {
+            if attempt == max_attempts || result.claimed.len() == count {
+                result.next = id;
+                break;
+            }
+            let Some(fields) = entries.get(&id) else {
+                group.acknowledge(id);
+                result.deleted.push(id);
+                continue;
+            };
+            let Some(pending) = group.pending.get(&id) else {
+                continue;
+            };
+            if now.saturating_sub(pending.delivered_at) < min_idle {
+                continue;
+            }
+            let delivery_count = pending.delivery_count + u64::from(!just_id);
+            group.assign(id, consumer, now, delivery_count);
+            result.claimed.push(StreamEntry {
+                id,
+                fields: fields.clone(),
+            });
+        }
+
+        if !result.claimed.is_empty() {
+            group.consumer(consumer, now).active_at = Some(now);
+        }
+        Ok(result)
+    }
+
+    pub(crate) async fn stream_info(&self, key: &Bytes) -> Result<StreamInfo, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let stream = state
+            .get_value::<Stream>(key)?
+            .ok_or(CmdErrors::NoSuchKey)?;
+        let to_entry = |(id, fields): (&StreamId, &Vec<(Bytes, Bytes)>)| StreamEntry {
+            id: *id,
+            fields: fields.clone(),
+        };
+        Ok(StreamInfo {
+            len: stream.len(),
+            last_generated_id: stream.last_id,
+            max_deleted_id: stream.max_deleted_id,
+            entries_added: stream.entries_added,
+            first_id: stream.first_id(),
+            groups: stream.groups.len(),
+            first: stream.entries.first_key_value().map(to_entry),
+            last: stream.entries.last_key_value().map(to_entry),
+        })
+    }
+
+    pub(crate) async fn stream_groups_info(
+        &self,
+        key: &Bytes,
+    ) -> Result<Vec<GroupInfo>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let stream = state
+            .get_value::<Stream>(key)?
+            .ok_or(CmdErrors::NoSuchKey)?;
+        Ok(stream
+            .groups
+            .iter()
+            .map(|(name, group)| GroupInfo {
+                name: name.clone(),
+                consumers: group.consumers.len(),
+                pending: group.pending.len(),
+                last_delivered: group.last_delivered,
+                entries_read: group.entries_read,
+                lag: stream.group_lag(group),
+            })
+            .collect())
+    }
+
+    pub(crate) async fn stream_consumers_info(
+        &self,
+        key: &Bytes,
+        name: &Bytes,
+    ) -> Result<Vec<ConsumerInfo>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let group = state
+            .get_value_mut::<Stream>(key)?
+            .ok_or(CmdErrors::NoSuchKey)?
+            .group_mut(key, name)?;
+        let now = now_ms();
+        Ok(group
+            .consumers
+            .iter()
+            .map(|(name, consumer)| ConsumerInfo {
+                name: name.clone(),
+                pending: consumer.pending.len(),
+                idle: now.saturating_sub(consumer.seen_at),
+                inactive: consumer
+                    .active_at
+                    .map(|active_at| now.saturating_sub(active_at)),
+            })
+            .collect())
+    }
+}
+
+impl State {
+    /// The stream for `XGROUP` subcommands, which need the key to exist.
+    fn existing_stream(&mut self, key: &Bytes) -> Result<&mut Stream, CmdErrors> {
+        self.get_value_mut::<Stream>(key)?
+            .ok_or(CmdErrors::XGroupKeyMissing)
+    }
+
+    /// The stream, when it has such group.
+    fn existing_group_stream(
+        &mut self,
+        key: &Bytes,
+        name: &Bytes,
+    ) -> Result<&mut Stream, CmdErrors> {
+        match self.get_value_mut::<Stream>(key)? {
+            Some(stream) if stream.groups.contains_key(name) => Ok(stream),
+            _ => Err(no_group(key, name)),
+        }
+    }
+
+    /// New entries for a blocked `XREADGROUP`, `None` when there is no such stream.
+    pub(super) fn stream_group_deliver(
+        &mut self,
+        key: &Bytes,
+        name: &Bytes,
+        consumer: &Bytes,
+        count: Option<usize>,
+        no_ack: bool,
+    ) -> Result<Option<Vec<StreamEntry>>, CmdErrors> {
+        match self.get_value_mut::<Stream>(key)? {
+            Some(stream) => stream.deliver(key, name, consumer, count, no_ack).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::storage::NewStreamId;
+
+    fn id(ms: u64, seq: u64) -> StreamId {
+        StreamId { ms, seq }
+    }
+
+    async fn add(storage: &Storage, key: &Bytes, ms: u64) {
+        let fields = vec![(Bytes::from("field"), Bytes::from("value"))];
+        storage
+            .stream_add(key, NewStreamId::Explicit(id(ms, 0)), fields, false, None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_group_delivery_and_claims() {
+        let storage = Storage::setup();
+        let key = Bytes::from("events");
+        let group = Bytes::from("workers");
+        let (alice, bob) = (Bytes::from("alice"), Bytes::from("bob"));
+        storage
+            .stream_group_create(&key, &group, ReadFrom::End, true, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            storage
+                .stream_group_create(&key, &group, ReadFrom::End, true, None)
+                .await,
+            Err(CmdErrors::BusyGroup)
+        );
+        for ms in 1..=3 {
+            add(&storage, &key, ms).await;
+        }
+
+        let keys = [key.clone()];
+        let read = storage
+            .stream_group_read(&keys, &[GroupReadFrom::New], &group, &alice, Some(2), false)
+            .await
+            .unwrap();
+        assert_eq!(read[0].1.len(), 2);
+        let read = storage
+            .stream_group_read(&keys, &[GroupReadFrom::New], &group, &bob, None, false)
+            .await
+            .unwrap();
+        assert_eq!(read[0].1[0].id, id(3, 0));
+
+        let summary = storage.stream_pending_summary(&key, &group).await.unwrap();
+        assert_eq!(summary.count, 3);
+        assert_eq!(
+            summary.consumers,
+            vec![(alice.clone(), 2), (bob.clone(), 1)]
+        );
+
+        // alice's first entry goes to bob, the deleted one stops being pending
+        storage.stream_delete(&key, &[id(2, 0)]).await.unwrap();
+        let claimed = storage
+            .stream_claim(
+                &key,
+                &group,
+                &bob,
+                0,
+                &[id(1, 0), id(2, 0)],
+                ClaimOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(claimed.len(), 1);
+        let filter = PendingFilter {
+            min_idle: None,
+            start: StreamId::MIN,
+            end: StreamId::MAX,
+            count: 10,
+            consumer: Some(bob.clone()),
+        };
+        let pending = storage.stream_pending(&key, &group, &filter).await.unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].delivery_count, 2);
+
+        assert_eq!(
+            storage
+                .stream_ack(&key, &group, &[id(1, 0), id(1, 0), id(3, 0)])
+                .await,
+            Ok(2)
+        );
+        let groups = storage.stream_groups_info(&key).await.unwrap();
+        assert_eq!(groups[0].pending, 0);
+        assert_eq!(groups[0].lag, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_auto_claim_scans_from_start() {
+        let storage = Storage::setup();
+        let key = Bytes::from("events");
+        let group = Bytes::from("workers");
+        let (alice, bob) = (Bytes::from("alice"), Bytes::from("bob"));
+        for ms in 1..=5 {
+            add(&storage, &key, ms).await;
+        }
+        storage
+            .stream_group_create(&key, &group, ReadFrom::After(StreamId::MIN), false, None)
+            .await
+            .unwrap();
+        storage
+            .stream_group_read(
+                std::slice::from_ref(&key),
+                &[GroupReadFrom::New],
+                &group,
+                &alice,
+                None,
+                false,
+            )
+            .await
+            .unwrap();
+        storage.stream_delete(&key, &[id(2, 0)]).await.unwrap();
+
+        let claimed = storage
+            .stream_auto_claim(&key, &group, &bob, 0, StreamId::MIN, 2, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            claimed
+                .claimed
+                .iter()
+                .map(|entry| entry.id)
+                .collect::<Vec<_>>(),
+            vec![id(1, 0), id(3, 0)]
+        );
+        assert_eq!(claimed.deleted, vec![id(2, 0)]);
+        assert_eq!(claimed.next, id(4, 0));
+
+        let claimed = storage
+            .stream_auto_claim(&key, &group, &bob, 0, claimed.next, 2, true)
+            .await
+            .unwrap();
+        assert_eq!(claimed.claimed.len(), 2);
+        assert_eq!(claimed.next, StreamId::MIN);
+    }
+}
@@ -0,0 +1,222 @@
+use bytes::{Bytes, BytesMut};
+
+use std::time::SystemTime;
+
+use super::list::normalize_range;
+use super::{Entry, SetExpiry, Storage, Value};
+use crate::redis::CmdErrors;
+
+// biggest string `SETRANGE` can make, like Redis with default `proto-max-bulk-len`
+pub(super) const STRING_MAX_LEN: usize = 512 * 1024 * 1024;
+
+impl Storage {
+    /// Replaces the string with what `update` makes of the current one, all under
+    /// one lock, so concurrent increments never lose updates. The key keeps its expiry.
+    pub(crate) async fn string_update(
+        &self,
+        key: &Bytes,
+        update: impl FnOnce(Option<&Bytes>) -> Result<Bytes, CmdErrors>,
+    ) -> Result<(), CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        // the key isn't created when the update fails
+        let value = update(state.get_value::<Bytes>(key)?)?;
+        *state.get_or_insert_value::<Bytes>(key)? = value;
+        Ok(())
+    }
+
+    /// Values in the same order as the keys, `None` for missing keys and keys of
+    /// other types.
+    pub(crate) async fn string_get_many(&self, keys: &[Bytes]) -> Vec<Option<Bytes>> {
+        let mut state = self.shared.state.lock().await;
+        keys.iter()
+            .map(|key| match state.live_entry(key) {
+                Some(Entry {
+                    value: Value::String(value),
+                    ..
+                }) => Some(value.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Writes all the pairs at once, overwriting values of any type and their expiry.
+    /// With `only_new` nothing is written when any of the keys exists. Returns
+    /// whether the pairs were written.
+    pub(crate) async fn string_set_many(&self, pairs: &[(Bytes, Bytes)], only_new: bool) -> bool {
+        let mut state = self.shared.state.lock().await;
+        if only_new && pairs.iter().any(|(key, _)| state.live_entry(key).is_some()) {
+            return false;
+        }
+        for (key, value) in pairs {
+            state.insert(
+                key.clone(),
+                Entry {
+                    value: Value::String(value.clone()),
+                    expires_at: None,
+                },
+            );
+        }
+        true
+    }
+
+    /// Deletes the key when it holds a string and returns the string.
+    pub(crate) async fn string_get_delete(&self, key: &Bytes) -> Result<Option<Bytes>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let value = state.get_value::<Bytes>(key)?.cloned();
+        if value.is_some() {
+            state.remove(key);
+        }
+        Ok(value)
+    }
+
+    /// Returns the string and changes its expiry the way `SET` would, a deadline
+    /// in the past deletes the key.
+    pub(crate) async fn string_get_expire(
+        &self,
+        key: &Bytes,
+        expiry: SetExpiry,
+    ) -> Result<Option<Bytes>, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let value = state.get_value::<Bytes>(key)?.cloned();
+        if value.is_none() {
+            return Ok(None);
+        }
+        match expiry {
+            SetExpiry::Keep => {}
+            SetExpiry::Discard => state.set_expiry(key, None),
+            SetExpiry::At(at) if at <= SystemTime::now() => {
+                state.remove(key);
+            }
+            SetExpiry::At(at) => state.set_expiry(key, Some(at)),
+        }
+        Ok(value)
+    }
+
+    /// Both strings read at once, missing keys are empty strings.
+    pub(crate) async fn string_pair(
+        &self,
+        first: &Bytes,
+        second: &Bytes,
+    ) -> Result<(Bytes, Bytes), CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let first = state
+            .get_value::<Bytes>(first)?
+            .cloned()
+            .unwrap_or_default();
+        let second = state
+            .get_value::<Bytes>(second)?
+            .cloned()
+            .unwrap_or_default();
+        Ok((first, second))
+    }
+
+    /// Appends to the string and returns its new length.
+    pub(crate) async fn string_append(
+        &self,
+        key: &Bytes,
+        value: &Bytes,
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let string = state.get_or_insert_value::<Bytes>(key)?;
+        // the buffer is reused when nothing else holds the string
+        let mut appended = BytesMut::from(std::mem::take(string));
+        appended.extend_from_slice(value);
+        *string = appended.freeze();
+        Ok(string.len())
+    }
+
+    pub(crate) async fn string_len(&self, key: &Bytes) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        Ok(state.get_value::<Bytes>(key)?.map_or(0, Bytes::len))
+    }
+
+    /// Bytes from `start` to `end`, both inclusive, negative offsets count from the end.
+    pub(crate) async fn string_range(
+        &self,
+        key: &Bytes,
+        start: i64,
+        end: i64,
+    ) -> Result<Bytes, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        let Some(string) = state.get_value::<Bytes>(key)? else {
+            return Ok(Bytes::new());
+        };
+        Ok(match normalize_range(start, end, string.len()) {
+            Some((start, end)) => string.slice(start..=end),
+            None => Bytes::new(),
+        })
+    }
+
+    /// Overwrites the string from `offset` on, padding it with zero bytes when it is
+    /// shorter. Returns the new length, an empty value doesn't create the key.
+    pub(crate) async fn string_set_range(
+        &self,
+        key: &Bytes,
+        offset: usize,
+        value: &Bytes,
+    ) -> Result<usize, CmdErrors> {
+        let mut state = self.shared.state.lock().await;
+        if value.is_empty() {
+            return Ok(state.get_value::<Bytes>(key)?.map_or(0, Bytes::len));
+        }
+        let end = offset
+            .checked_add(value.len())
+            .filter(|end| *end <= STRING_MAX_LEN)
+            .ok_or(CmdErrors::StringTooLong)?;
+
+        let string = state.get_or_insert_value::<Bytes>(key)?;
+        let mut updated = BytesMut::from(std::mem::take(string));
+        if updated.len() < end {
+            updated.resize(end, 0);
+        }
+        updated[offset..end].copy_from_slice(value);
+        *string = updated.freeze();
+        Ok(string.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_string_ranges() {
+        let storage = Storage::setup();
+        let key = Bytes::from("greeting");
+        assert_eq!(
+            storage.string_set_range(&key, 3, &Bytes::from("lo")).await,
+            Ok(5)
+        );
+        assert_eq!(storage.get(&key).await, Ok(Some(Bytes::from("\0\0\0lo"))));
+        assert_eq!(
+            storage.string_set_range(&key, 0, &Bytes::from("hel")).await,
+            Ok(5)
+        );
+        assert_eq!(
+            storage.string_append(&key, &Bytes::from(" world")).await,
+            Ok(11)
+        );
+        assert_eq!(
+            storage.string_range(&key, -5, -1).await,
+            Ok(Bytes::from("world"))
+        );
+        assert_eq!(
+            storage.string_range(&key, 3, 100).await,
+            Ok(Bytes::from("lo world"))
+        );
+        assert_eq!(storage.string_range(&key, 5, 2).await, Ok(Bytes::new()));
+
+        let missing = Bytes::from("missing");
+        assert_eq!(
+            storage.string_set_range(&missing, 10, &Bytes::new()).await,
+            Ok(0)
+        );
+        assert_eq!(storage.key_type(&missing).await, None);
+        assert_eq!(
+            storage
+                .string_set_range(&missing, STRING_MAX_LEN, &Bytes::from("x"))
+                .await,
+            Err(CmdErrors::StringTooLong)
+        );
+    }
+}
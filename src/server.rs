@@ -1,5 +1,6 @@
 use anyhow::Result;
 use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
 
 use crate::redis::ConnectionHandler;
 use crate::redis::Storage;
@@ -14,8 +15,8 @@ pub(crate) struct Server {
 impl Server {
     pub fn setup(addr: &'static str, buffer_size: usize) -> Server {
         Server {
-            addr: addr,
-            buffer_size: buffer_size,
+            addr,
+            buffer_size,
             storage: Storage::setup(),
         }
     }
@@ -23,6 +24,7 @@ impl Server {
     pub async fn run(&self) -> Result<()> {
         // TODO: add gracefull shutdown
         let listener = TcpListener::bind(self.addr).await?;
+        let _active_expire = BackgroundTask(tokio::spawn(self.storage.clone().run_active_expire()));
 
         loop {
             let (socket, _) = listener.accept().await?;
@@ -34,6 +36,15 @@ impl Server {
     }
 }
 
+/// Aborts the task when dropped, so background tasks stop together with `Server::run`.
+struct BackgroundTask(JoinHandle<()>);
+
+impl Drop for BackgroundTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,6 +73,21 @@ mod tests {
         }
     }
 
+    // sends the request and reads until the reply is as long as expected
+    async fn assert_reply(socket: &mut TcpStream, request: &[u8], expected: &[u8]) {
+        socket.write_all(request).await.unwrap();
+        let mut buf = Vec::with_capacity(expected.len());
+        while buf.len() < expected.len() {
+            if socket.read_buf(&mut buf).await.unwrap() == 0 {
+                break;
+            }
+        }
+        assert_eq!(
+            String::from_utf8_lossy(&buf),
+            String::from_utf8_lossy(expected)
+        );
+    }
+
     #[tokio::test]
     async fn test_ping_pong() -> Result<(), Box<dyn std::error::Error>> {
         let addr = "127.0.0.1:6379";
@@ -90,7 +116,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_set() -> Result<(), Box<dyn std::error::Error>> {
-        let addr = "127.0.0.1:6379";
+        let addr = "127.0.0.1:6380";
         let server_handler = run_server(addr)
             .await
             .map_err(|e| format!("Failed to start server: {}", e))?;
@@ -122,4 +148,1364 @@ mod tests {
         server_handler.abort();
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_pipelined_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6381";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        socket
+            .write_all(b"*1\r\n$4\r\nPING\r\n*3\r\n$3\r\nSET\r\n$1\r\na\r\n$1\r\nb\r\n*2\r\n$3\r\nGET\r\n$1\r\na\r\n")
+            .await
+            .unwrap();
+
+        let expected = b"+PONG\r\n+OK\r\n$1\r\nb\r\n";
+        let mut buf = Vec::with_capacity(64);
+        while buf.len() < expected.len() {
+            socket.read_buf(&mut buf).await.unwrap();
+        }
+        assert_eq!(&buf, expected);
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_frame_split_across_writes() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6382";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let mut buf = Vec::with_capacity(16);
+        socket.write_all(b"*2\r\n$4\r\nEC").await.unwrap();
+        socket.flush().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        socket.write_all(b"HO\r\n$3\r\nhey\r\n").await.unwrap();
+        socket.read_buf(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"$3\r\nhey\r\n");
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hello_switches_protocol() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6383";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let mut buf = Vec::with_capacity(256);
+
+        socket
+            .write_all(b"*2\r\n$5\r\nHELLO\r\n$1\r\n3\r\n")
+            .await
+            .unwrap();
+        socket.read_buf(&mut buf).await.unwrap();
+        assert!(buf.starts_with(b"%7\r\n$6\r\nserver\r\n$6\r\nrredis\r\n"));
+        buf.clear();
+
+        // nulls are RESP3 nulls now
+        socket
+            .write_all(b"*2\r\n$3\r\nGET\r\n$3\r\nhey\r\n")
+            .await
+            .unwrap();
+        socket.read_buf(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"_\r\n");
+        buf.clear();
+
+        // and back to RESP2
+        socket
+            .write_all(b"*2\r\n$5\r\nHELLO\r\n$1\r\n2\r\n")
+            .await
+            .unwrap();
+        socket.read_buf(&mut buf).await.unwrap();
+        assert!(buf.starts_with(b"*14\r\n"));
+        buf.clear();
+
+        socket
+            .write_all(b"*2\r\n$3\r\nGET\r\n$3\r\nhey\r\n")
+            .await
+            .unwrap();
+        socket.read_buf(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"$-1\r\n");
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_error_replies() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6384";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let mut buf = Vec::with_capacity(128);
+
+        socket.write_all(b"*1\r\n$3\r\nFOO\r\n").await.unwrap();
+        socket.read_buf(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"-ERR unknown command 'foo'\r\n");
+        buf.clear();
+
+        socket.write_all(b"*1\r\n$3\r\nGET\r\n").await.unwrap();
+        socket.read_buf(&mut buf).await.unwrap();
+        assert_eq!(
+            &buf,
+            b"-ERR wrong number of arguments for 'get' command\r\n"
+        );
+        buf.clear();
+
+        socket
+            .write_all(b"*2\r\n$5\r\nHELLO\r\n$1\r\n4\r\n")
+            .await
+            .unwrap();
+        socket.read_buf(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"-NOPROTO unsupported protocol version\r\n");
+        buf.clear();
+
+        // connection is still usable after command errors
+        socket.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+        socket.read_buf(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"+PONG\r\n");
+        buf.clear();
+
+        // but not after a protocol error
+        socket.write_all(b"*1\r\n$x\r\n").await.unwrap();
+        socket.read_buf(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"-ERR Protocol error: invalid integer\r\n");
+        buf.clear();
+        assert_eq!(socket.read_buf(&mut buf).await.unwrap(), 0);
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inline_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6385";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        socket
+            .write_all(b"PING\r\n\r\nSET foo \"bar baz\"\nGET foo\r\n")
+            .await
+            .unwrap();
+
+        let expected = b"+PONG\r\n+OK\r\n$7\r\nbar baz\r\n";
+        let mut buf = Vec::with_capacity(64);
+        while buf.len() < expected.len() {
+            socket.read_buf(&mut buf).await.unwrap();
+        }
+        assert_eq!(&buf, expected);
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_key_expiration() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6386";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut socket, b"SET a 1 EX 100\r\n", b"+OK\r\n").await;
+        assert_reply(&mut socket, b"TTL a\r\n", b":100\r\n").await;
+        assert_reply(&mut socket, b"SET a 2 KEEPTTL\r\n", b"+OK\r\n").await;
+        assert_reply(&mut socket, b"TTL a\r\n", b":100\r\n").await;
+        assert_reply(&mut socket, b"PERSIST a\r\n", b":1\r\n").await;
+        assert_reply(&mut socket, b"TTL a\r\n", b":-1\r\n").await;
+        assert_reply(&mut socket, b"TTL missing\r\n", b":-2\r\n").await;
+        assert_reply(&mut socket, b"EXPIRE a 100 XX\r\n", b":0\r\n").await;
+        assert_reply(&mut socket, b"EXPIREAT a 4102444800\r\n", b":1\r\n").await;
+        assert_reply(&mut socket, b"EXPIRETIME a\r\n", b":4102444800\r\n").await;
+        assert_reply(&mut socket, b"PEXPIRETIME a\r\n", b":4102444800000\r\n").await;
+        assert_reply(&mut socket, b"EXPIRE a 100 XX GT\r\n", b":0\r\n").await;
+        assert_reply(&mut socket, b"EXPIRE a 100 XX LT\r\n", b":1\r\n").await;
+        assert_reply(&mut socket, b"PERSIST a\r\n", b":1\r\n").await;
+        assert_reply(&mut socket, b"EXPIRE a 100 LT XX\r\n", b":0\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"EXPIRE a 100 XX NX\r\n",
+            b"-ERR NX and XX, GT or LT options at the same time are not compatible\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"EXPIRE a 100 GT LT\r\n",
+            b"-ERR GT and LT options at the same time are not compatible\r\n",
+        )
+        .await;
+
+        assert_reply(&mut socket, b"SET b 1 PX 30\r\n", b"+OK\r\n").await;
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_reply(&mut socket, b"GET b\r\n", b"$-1\r\n").await;
+
+        assert_reply(
+            &mut socket,
+            b"SET a 1 EX 0\r\n",
+            b"-ERR invalid expire time in 'set' command\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"SET a 1 EX 10 PX 10\r\n",
+            b"-SYNTAX syntax error, unexpected `PX` for 'set' command\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"EXPIRE a ten\r\n",
+            b"-ERR value is not an integer or out of range\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_active_expire_task() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6387";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut socket, b"SET a 1 PX 10\r\n", b"+OK\r\n").await;
+        assert_reply(&mut socket, b"SET b 1 PX 10\r\n", b"+OK\r\n").await;
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+        // keys were never read again, but the background task deleted them
+        socket.write_all(b"INFO stats keyspace\r\n").await.unwrap();
+        let mut buf = Vec::with_capacity(256);
+        socket.read_buf(&mut buf).await.unwrap();
+        let info = String::from_utf8_lossy(&buf);
+        assert!(info.contains("expired_keys:2\r\n"), "{}", info);
+        assert!(info.ends_with("# Keyspace\r\n\r\n"), "{}", info);
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conditional_set() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6388";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut socket, b"SET lock me NX PX 30000\r\n", b"+OK\r\n").await;
+        assert_reply(&mut socket, b"SET lock you NX PX 30000\r\n", b"$-1\r\n").await;
+        assert_reply(&mut socket, b"SET lock you XX GET\r\n", b"$2\r\nme\r\n").await;
+        assert_reply(&mut socket, b"SET missing v XX GET\r\n", b"$-1\r\n").await;
+        assert_reply(&mut socket, b"GET missing\r\n", b"$-1\r\n").await;
+        assert_reply(&mut socket, b"SET lock them IFEQ me\r\n", b"$-1\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"SET lock them IFEQ you GET\r\n",
+            b"$3\r\nyou\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"GET lock\r\n", b"$4\r\nthem\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"SET lock x NX XX\r\n",
+            b"-SYNTAX syntax error, unexpected `XX` for 'set' command\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_type() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6389";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+
+        assert_reply(&mut socket, b"TYPE a\r\n", b"+none\r\n").await;
+        assert_reply(&mut socket, b"SET a 1\r\n", b"+OK\r\n").await;
+        assert_reply(&mut socket, b"TYPE a\r\n", b"+string\r\n").await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6390";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut socket, b"RPUSH jobs a b c\r\n", b":3\r\n").await;
+        assert_reply(&mut socket, b"LPUSH jobs z\r\n", b":4\r\n").await;
+        assert_reply(&mut socket, b"TYPE jobs\r\n", b"+list\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"LRANGE jobs 1 -2\r\n",
+            b"*2\r\n$1\r\na\r\n$1\r\nb\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"LINDEX jobs -1\r\n", b"$1\r\nc\r\n").await;
+        assert_reply(&mut socket, b"LINDEX jobs 10\r\n", b"$-1\r\n").await;
+        assert_reply(&mut socket, b"LSET jobs 0 y\r\n", b"+OK\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"LSET jobs 10 y\r\n",
+            b"-ERR index out of range\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"LINSERT jobs AFTER a x\r\n", b":5\r\n").await;
+        assert_reply(&mut socket, b"LPOS jobs x\r\n", b":2\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"LPOS jobs x RANK 0\r\n",
+            b"-ERR RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"LMOVE jobs done RIGHT LEFT\r\n",
+            b"$1\r\nc\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"LPOP jobs 2\r\n",
+            b"*2\r\n$1\r\ny\r\n$1\r\na\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"RPOP jobs\r\n", b"$1\r\nb\r\n").await;
+        assert_reply(&mut socket, b"LLEN jobs\r\n", b":1\r\n").await;
+        assert_reply(&mut socket, b"LTRIM jobs 1 -1\r\n", b"+OK\r\n").await;
+        assert_reply(&mut socket, b"LPOP jobs\r\n", b"$-1\r\n").await;
+        assert_reply(&mut socket, b"LPOP jobs 1\r\n", b"*-1\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"LPOP done -1\r\n",
+            b"-ERR value is out of range, must be positive\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"GET done\r\n",
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blocking_list_pops() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6391";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+        let blocked = || tokio::time::sleep(std::time::Duration::from_millis(50));
+
+        let mut producer = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut producer, b"BLPOP jobs 0.05\r\n", b"*-1\r\n").await;
+
+        // a client that disconnects while blocked doesn't take anything
+        let mut gone = TcpStream::connect(addr).await.unwrap();
+        gone.write_all(b"BLPOP jobs 0\r\n").await.unwrap();
+        blocked().await;
+        drop(gone);
+
+        let mut worker = TcpStream::connect(addr).await.unwrap();
+        worker.write_all(b"BLPOP other jobs 0\r\n").await.unwrap();
+        blocked().await;
+        assert_reply(&mut producer, b"RPUSH jobs a\r\n", b":1\r\n").await;
+        assert_reply(&mut worker, b"", b"*2\r\n$4\r\njobs\r\n$1\r\na\r\n").await;
+        assert_reply(&mut producer, b"LLEN jobs\r\n", b":0\r\n").await;
+
+        worker
+            .write_all(b"BLMOVE jobs done RIGHT LEFT 0\r\n")
+            .await
+            .unwrap();
+        blocked().await;
+        assert_reply(&mut producer, b"LPUSH jobs b\r\n", b":1\r\n").await;
+        assert_reply(&mut worker, b"", b"$1\r\nb\r\n").await;
+        assert_reply(&mut producer, b"LRANGE done 0 -1\r\n", b"*1\r\n$1\r\nb\r\n").await;
+
+        assert_reply(
+            &mut worker,
+            b"BLMPOP 0 2 jobs done RIGHT COUNT 2\r\n",
+            b"*2\r\n$4\r\ndone\r\n*1\r\n$1\r\nb\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut worker,
+            b"BLPOP jobs -1\r\n",
+            b"-ERR timeout is negative\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hash_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6392";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut socket, b"HSET user name ann visits 1\r\n", b":2\r\n").await;
+        assert_reply(&mut socket, b"HSET user name bob\r\n", b":0\r\n").await;
+        assert_reply(&mut socket, b"HSETNX user name cid\r\n", b":0\r\n").await;
+        assert_reply(&mut socket, b"HGET user name\r\n", b"$3\r\nbob\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"HMGET user name missing\r\n",
+            b"*2\r\n$3\r\nbob\r\n$-1\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"HINCRBY user visits 41\r\n", b":42\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"HINCRBY user name 1\r\n",
+            b"-ERR hash value is not an integer\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"HINCRBY user visits 9223372036854775807\r\n",
+            b"-ERR increment or decrement would overflow\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"HINCRBYFLOAT user score 10.5\r\n",
+            b"$4\r\n10.5\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"HINCRBYFLOAT user score nan\r\n",
+            b"-ERR value is not a valid float\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"HLEN user\r\n", b":3\r\n").await;
+        assert_reply(&mut socket, b"HEXISTS user score\r\n", b":1\r\n").await;
+        assert_reply(&mut socket, b"HDEL user score visits nope\r\n", b":2\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"HGETALL user\r\n",
+            b"*2\r\n$4\r\nname\r\n$3\r\nbob\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"HKEYS user\r\n", b"*1\r\n$4\r\nname\r\n").await;
+        assert_reply(&mut socket, b"HVALS user\r\n", b"*1\r\n$3\r\nbob\r\n").await;
+        assert_reply(&mut socket, b"HRANDFIELD user\r\n", b"$4\r\nname\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"HRANDFIELD user -2 WITHVALUES\r\n",
+            b"*4\r\n$4\r\nname\r\n$3\r\nbob\r\n$4\r\nname\r\n$3\r\nbob\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"HRANDFIELD user -9223372036854775808\r\n",
+            b"-ERR value is out of range\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"HDEL user name\r\n", b":1\r\n").await;
+        assert_reply(&mut socket, b"TYPE user\r\n", b"+none\r\n").await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hash_field_expiry() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6393";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut socket, b"HSET session a 1 b 2\r\n", b":2\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"HEXPIRE session 100 FIELDS 2 a c\r\n",
+            b"*2\r\n:1\r\n:-2\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"HEXPIRE session 200 LT FIELDS 1 a\r\n",
+            b"*1\r\n:0\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"HTTL session FIELDS 2 a b\r\n",
+            b"*2\r\n:100\r\n:-1\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"HPERSIST session FIELDS 2 a b\r\n",
+            b"*2\r\n:1\r\n:-1\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"HTTL session FIELDS 1 a\r\n",
+            b"*1\r\n:-1\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"HPEXPIRE session 0 FIELDS 1 b\r\n",
+            b"*1\r\n:2\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"HLEN session\r\n", b":1\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"HEXPIRE session 100 FIELDS 2 a\r\n",
+            b"-ERR The `numfields` parameter must match the number of arguments\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"HTTL session 1 a\r\n",
+            b"-ERR Mandatory argument FIELDS is missing or not at the right position\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6394";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut socket, b"SADD seen 3 1 2 1\r\n", b":3\r\n").await;
+        // integers only, so members come back in order
+        assert_reply(
+            &mut socket,
+            b"SMEMBERS seen\r\n",
+            b"*3\r\n$1\r\n1\r\n$1\r\n2\r\n$1\r\n3\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"SISMEMBER seen 2\r\n", b":1\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"SMISMEMBER seen 1 9\r\n",
+            b"*2\r\n:1\r\n:0\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"SADD other 2 3 x\r\n", b":3\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"SINTER seen other\r\n",
+            b"*2\r\n$1\r\n2\r\n$1\r\n3\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"SDIFF seen other\r\n", b"*1\r\n$1\r\n1\r\n").await;
+        assert_reply(&mut socket, b"SUNIONSTORE all seen other\r\n", b":4\r\n").await;
+        assert_reply(&mut socket, b"SCARD all\r\n", b":4\r\n").await;
+        assert_reply(&mut socket, b"SREM seen 1 2 9\r\n", b":2\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"SRANDMEMBER seen -2\r\n",
+            b"*2\r\n$1\r\n3\r\n$1\r\n3\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"SRANDMEMBER seen -9223372036854775808\r\n",
+            b"-ERR value is out of range\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"SPOP seen\r\n", b"$1\r\n3\r\n").await;
+        assert_reply(&mut socket, b"TYPE seen\r\n", b"+none\r\n").await;
+        assert_reply(&mut socket, b"SPOP seen 1\r\n", b"*0\r\n").await;
+        assert_reply(&mut socket, b"SET name bob\r\n", b"+OK\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"SUNION all name\r\n",
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"SINTERSTORE name all missing\r\n", b":0\r\n").await;
+        assert_reply(&mut socket, b"TYPE name\r\n", b"+none\r\n").await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sorted_set_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6395";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        assert_reply(
+            &mut socket,
+            b"ZADD board 10 ann 20 bob 30 cid\r\n",
+            b":3\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"ZADD board GT CH 5 ann 25 bob\r\n", b":1\r\n").await;
+        assert_reply(&mut socket, b"ZINCRBY board 1.5 ann\r\n", b"$4\r\n11.5\r\n").await;
+        assert_reply(&mut socket, b"ZSCORE board bob\r\n", b"$2\r\n25\r\n").await;
+        assert_reply(&mut socket, b"ZRANK board cid\r\n", b":2\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"ZRANGE board 0 -1 WITHSCORES\r\n",
+            b"*6\r\n$3\r\nann\r\n$4\r\n11.5\r\n$3\r\nbob\r\n$2\r\n25\r\n$3\r\ncid\r\n$2\r\n30\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"ZRANGE board +inf (20 BYSCORE REV LIMIT 0 1\r\n",
+            b"*1\r\n$3\r\ncid\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"ZRANGE board [b (c BYLEX\r\n",
+            b"*1\r\n$3\r\nbob\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"ZCOUNT board (11.5 30\r\n", b":2\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"ZPOPMIN board\r\n",
+            b"*2\r\n$3\r\nann\r\n$4\r\n11.5\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"SADD bonus bob\r\n", b":1\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"ZUNIONSTORE total 2 board bonus WEIGHTS 1 100\r\n",
+            b":2\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"ZINTERSTORE both 2 board bonus AGGREGATE MAX\r\n",
+            b":1\r\n",
+        )
+        .await;
+        assert_reply(&mut socket, b"ZSCORE total bob\r\n", b"$3\r\n125\r\n").await;
+        assert_reply(&mut socket, b"ZREM board bob cid nope\r\n", b":2\r\n").await;
+        assert_reply(&mut socket, b"ZCARD board\r\n", b":0\r\n").await;
+        assert_reply(
+            &mut socket,
+            b"ZADD board NX XX 1 a\r\n",
+            b"-ERR XX and NX options at the same time are not compatible\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"ZRANGE board 0 1 LIMIT 0 1\r\n",
+            b"-ERR syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut socket,
+            b"ZCOUNT board x 1\r\n",
+            b"-ERR min or max is not a float\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blocking_sorted_set_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6396";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+        let blocked = || tokio::time::sleep(std::time::Duration::from_millis(50));
+
+        let mut producer = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut producer, b"BZPOPMIN tasks 0.05\r\n", b"*-1\r\n").await;
+
+        // a list client blocked on the same key waits for a list, not for a sorted set
+        let mut list_worker = TcpStream::connect(addr).await.unwrap();
+        list_worker.write_all(b"BLPOP tasks 0\r\n").await.unwrap();
+        blocked().await;
+        let mut worker = TcpStream::connect(addr).await.unwrap();
+        worker
+            .write_all(b"BZPOPMIN other tasks 0\r\n")
+            .await
+            .unwrap();
+        blocked().await;
+        assert_reply(&mut producer, b"ZADD tasks 2 b 1 a\r\n", b":2\r\n").await;
+        assert_reply(
+            &mut worker,
+            b"",
+            b"*3\r\n$5\r\ntasks\r\n$1\r\na\r\n$1\r\n1\r\n",
+        )
+        .await;
+        assert_reply(&mut producer, b"ZCARD tasks\r\n", b":1\r\n").await;
+
+        assert_reply(
+            &mut worker,
+            b"BZPOPMAX tasks 0\r\n",
+            b"*3\r\n$5\r\ntasks\r\n$1\r\nb\r\n$1\r\n2\r\n",
+        )
+        .await;
+        worker
+            .write_all(b"BZMPOP 0 1 tasks MAX COUNT 2\r\n")
+            .await
+            .unwrap();
+        blocked().await;
+        assert_reply(&mut producer, b"ZADD tasks 3 c 4 d 5 e\r\n", b":3\r\n").await;
+        assert_reply(
+            &mut worker,
+            b"",
+            b"*2\r\n$5\r\ntasks\r\n*2\r\n*2\r\n$1\r\ne\r\n$1\r\n5\r\n*2\r\n$1\r\nd\r\n$1\r\n4\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut producer,
+            b"ZPOPMIN tasks\r\n",
+            b"*2\r\n$1\r\nc\r\n$1\r\n3\r\n",
+        )
+        .await;
+        assert_reply(&mut producer, b"RPUSH tasks job\r\n", b":1\r\n").await;
+        assert_reply(&mut list_worker, b"", b"*2\r\n$5\r\ntasks\r\n$3\r\njob\r\n").await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6397";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+        let blocked = || tokio::time::sleep(std::time::Duration::from_millis(50));
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut client, b"XADD events 1-1 a 1\r\n", b"$3\r\n1-1\r\n").await;
+        assert_reply(&mut client, b"XADD events 1-* b 2\r\n", b"$3\r\n1-2\r\n").await;
+        assert_reply(
+            &mut client,
+            b"XADD events 1-2 c 3\r\n",
+            b"-ERR The ID specified in XADD is equal or smaller than the target stream top item\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"XADD events 2-0 c 3\r\n", b"$3\r\n2-0\r\n").await;
+        assert_reply(
+            &mut client,
+            b"XADD missing NOMKSTREAM * a 1\r\n",
+            b"$-1\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"XLEN events\r\n", b":3\r\n").await;
+
+        assert_reply(
+            &mut client,
+            b"XRANGE events (1-1 + COUNT 1\r\n",
+            b"*1\r\n*2\r\n$3\r\n1-2\r\n*2\r\n$1\r\nb\r\n$1\r\n2\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"XREVRANGE events 1 -\r\n",
+            b"*2\r\n*2\r\n$3\r\n1-2\r\n*2\r\n$1\r\nb\r\n$1\r\n2\r\n*2\r\n$3\r\n1-1\r\n*2\r\n$1\r\na\r\n$1\r\n1\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"XDEL events 1-1 9-9\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"XTRIM events MAXLEN = 1\r\n", b":1\r\n").await;
+        assert_reply(
+            &mut client,
+            b"XREAD COUNT 5 STREAMS events missing 0 0\r\n",
+            b"*1\r\n*2\r\n$6\r\nevents\r\n*1\r\n*2\r\n$3\r\n2-0\r\n*2\r\n$1\r\nc\r\n$1\r\n3\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"XREAD BLOCK 50 STREAMS events $\r\n",
+            b"*-1\r\n",
+        )
+        .await;
+
+        // every reader gets the new entry, it stays in the stream
+        let mut readers = vec![];
+        for _ in 0..2 {
+            let mut reader = TcpStream::connect(addr).await.unwrap();
+            reader
+                .write_all(b"XREAD BLOCK 0 STREAMS events $\r\n")
+                .await
+                .unwrap();
+            readers.push(reader);
+        }
+        blocked().await;
+        assert_reply(&mut client, b"XADD events 3-0 d 4\r\n", b"$3\r\n3-0\r\n").await;
+        for reader in &mut readers {
+            assert_reply(
+                reader,
+                b"",
+                b"*1\r\n*2\r\n$6\r\nevents\r\n*1\r\n*2\r\n$3\r\n3-0\r\n*2\r\n$1\r\nd\r\n$1\r\n4\r\n",
+            )
+            .await;
+        }
+        assert_reply(&mut client, b"XLEN events\r\n", b":2\r\n").await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_consumer_groups() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6398";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+        let blocked = || tokio::time::sleep(std::time::Duration::from_millis(50));
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        assert_reply(
+            &mut client,
+            b"XGROUP CREATE jobs workers $\r\n",
+            b"-ERR The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically.\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"XGROUP CREATE jobs workers $ MKSTREAM\r\n",
+            b"+OK\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"XGROUP CREATE jobs workers 0\r\n",
+            b"-BUSYGROUP Consumer Group name already exists\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"XREADGROUP GROUP missing alice STREAMS jobs >\r\n",
+            b"-NOGROUP No such key 'jobs' or consumer group 'missing'\r\n",
+        )
+        .await;
+
+        // a blocked consumer gets the next entry, it becomes pending for it
+        let mut alice = TcpStream::connect(addr).await.unwrap();
+        alice
+            .write_all(b"XREADGROUP GROUP workers alice BLOCK 0 STREAMS jobs >\r\n")
+            .await
+            .unwrap();
+        blocked().await;
+        assert_reply(&mut client, b"XADD jobs 1-0 task a\r\n", b"$3\r\n1-0\r\n").await;
+        assert_reply(
+            &mut alice,
+            b"",
+            b"*1\r\n*2\r\n$4\r\njobs\r\n*1\r\n*2\r\n$3\r\n1-0\r\n*2\r\n$4\r\ntask\r\n$1\r\na\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"XADD jobs 2-0 task b\r\n", b"$3\r\n2-0\r\n").await;
+        assert_reply(
+            &mut client,
+            b"XREADGROUP GROUP workers bob COUNT 5 STREAMS jobs >\r\n",
+            b"*1\r\n*2\r\n$4\r\njobs\r\n*1\r\n*2\r\n$3\r\n2-0\r\n*2\r\n$4\r\ntask\r\n$1\r\nb\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"XPENDING jobs workers\r\n",
+            b"*4\r\n:2\r\n$3\r\n1-0\r\n$3\r\n2-0\r\n*2\r\n*2\r\n$5\r\nalice\r\n$1\r\n1\r\n*2\r\n$3\r\nbob\r\n$1\r\n1\r\n",
+        )
+        .await;
+
+        // bob takes over alice's entry, then acknowledges both
+        assert_reply(
+            &mut client,
+            b"XCLAIM jobs workers bob 0 1-0 JUSTID\r\n",
+            b"*1\r\n$3\r\n1-0\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"XAUTOCLAIM jobs workers bob 0 0 JUSTID\r\n",
+            b"*3\r\n$3\r\n0-0\r\n*2\r\n$3\r\n1-0\r\n$3\r\n2-0\r\n*0\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"XREADGROUP GROUP workers alice STREAMS jobs 0\r\n",
+            b"*1\r\n*2\r\n$4\r\njobs\r\n*0\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"XACK jobs workers 1-0 2-0 3-0\r\n", b":2\r\n").await;
+        assert_reply(
+            &mut client,
+            b"XINFO GROUPS jobs\r\n",
+            b"*1\r\n*12\r\n$4\r\nname\r\n$7\r\nworkers\r\n$9\r\nconsumers\r\n:2\r\n$7\r\npending\r\n:0\r\n$17\r\nlast-delivered-id\r\n$3\r\n2-0\r\n$12\r\nentries-read\r\n:2\r\n$3\r\nlag\r\n:0\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"XGROUP DELCONSUMER jobs workers alice\r\n",
+            b":0\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"XGROUP DESTROY jobs workers\r\n", b":1\r\n").await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_string_counters_and_ranges() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6399";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+
+        // concurrent increments never lose updates
+        let mut workers = vec![];
+        for _ in 0..10 {
+            workers.push(tokio::spawn(async move {
+                let mut client = TcpStream::connect(addr).await.unwrap();
+                for _ in 0..20 {
+                    client.write_all(b"INCR counter\r\n").await.unwrap();
+                    let mut reply = [0; 16];
+                    let _ = client.read(&mut reply).await.unwrap();
+                }
+            }));
+        }
+        for worker in workers {
+            worker.await?;
+        }
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        assert_reply(&mut client, b"GET counter\r\n", b"$3\r\n200\r\n").await;
+        assert_reply(&mut client, b"DECRBY counter 250\r\n", b":-50\r\n").await;
+        assert_reply(
+            &mut client,
+            b"INCRBY counter 9223372036854775807\r\n",
+            b":9223372036854775757\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"INCRBY counter 100\r\n",
+            b"-ERR increment or decrement would overflow\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"SET number 010\r\n", b"+OK\r\n").await;
+        assert_reply(
+            &mut client,
+            b"INCR number\r\n",
+            b"-ERR value is not an integer or out of range\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"INCRBYFLOAT number 0.5\r\n",
+            b"$4\r\n10.5\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"INCRBYFLOAT number 5.0e3\r\n",
+            b"$6\r\n5010.5\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"SET fraction 0.1\r\n", b"+OK\r\n").await;
+        assert_reply(
+            &mut client,
+            b"INCRBYFLOAT fraction 0.2\r\n",
+            b"$3\r\n0.3\r\n",
+        )
+        .await;
+
+        assert_reply(&mut client, b"SETRANGE padded 5 end\r\n", b":8\r\n").await;
+        assert_reply(&mut client, b"GET padded\r\n", b"$8\r\n\0\0\0\0\0end\r\n").await;
+        assert_reply(&mut client, b"APPEND padded !\r\n", b":9\r\n").await;
+        assert_reply(&mut client, b"GETRANGE padded -4 -1\r\n", b"$4\r\nend!\r\n").await;
+        assert_reply(&mut client, b"STRLEN padded\r\n", b":9\r\n").await;
+        assert_reply(&mut client, b"STRLEN missing\r\n", b":0\r\n").await;
+        assert_reply(
+            &mut client,
+            b"SETRANGE padded -1 x\r\n",
+            b"-ERR offset is out of range\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_multi_key_string_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6400";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+        let mut client = TcpStream::connect(addr).await.unwrap();
+
+        assert_reply(&mut client, b"MSET a 1 b 2\r\n", b"+OK\r\n").await;
+        assert_reply(&mut client, b"RPUSH list x\r\n", b":1\r\n").await;
+        assert_reply(
+            &mut client,
+            b"MGET a list missing b\r\n",
+            b"*4\r\n$1\r\n1\r\n$-1\r\n$-1\r\n$1\r\n2\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"MSETNX c 3 a 4\r\n", b":0\r\n").await;
+        assert_reply(&mut client, b"MGET a c\r\n", b"*2\r\n$1\r\n1\r\n$-1\r\n").await;
+        assert_reply(&mut client, b"MSETNX c 3 d 4\r\n", b":1\r\n").await;
+        assert_reply(
+            &mut client,
+            b"MSET a 1 b\r\n",
+            b"-ERR wrong number of arguments for 'mset' command\r\n",
+        )
+        .await;
+
+        assert_reply(&mut client, b"GETSET a 10\r\n", b"$1\r\n1\r\n").await;
+        assert_reply(&mut client, b"GETDEL a\r\n", b"$2\r\n10\r\n").await;
+        assert_reply(&mut client, b"GETDEL a\r\n", b"$-1\r\n").await;
+
+        assert_reply(&mut client, b"GETEX b EX 100\r\n", b"$1\r\n2\r\n").await;
+        assert_reply(&mut client, b"TTL b\r\n", b":100\r\n").await;
+        assert_reply(&mut client, b"GETEX b PERSIST\r\n", b"$1\r\n2\r\n").await;
+        assert_reply(&mut client, b"TTL b\r\n", b":-1\r\n").await;
+
+        assert_reply(&mut client, b"COPY list copied\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"COPY b copied\r\n", b":0\r\n").await;
+        assert_reply(&mut client, b"COPY b copied REPLACE\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"GET copied\r\n", b"$1\r\n2\r\n").await;
+        assert_reply(
+            &mut client,
+            b"COPY b b\r\n",
+            b"-ERR source and destination objects are the same\r\n",
+        )
+        .await;
+
+        assert_reply(
+            &mut client,
+            b"MSET k1 ohmytext k2 mynewtext\r\n",
+            b"+OK\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"LCS k1 k2\r\n", b"$6\r\nmytext\r\n").await;
+        assert_reply(&mut client, b"LCS k1 k2 LEN\r\n", b":6\r\n").await;
+        assert_reply(
+            &mut client,
+            b"LCS k1 k2 IDX MINMATCHLEN 4 WITHMATCHLEN\r\n",
+            b"*4\r\n$7\r\nmatches\r\n*1\r\n*3\r\n*2\r\n:4\r\n:7\r\n*2\r\n:5\r\n:8\r\n:4\r\n$3\r\nlen\r\n:6\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bitmap_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6401";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+        let mut client = TcpStream::connect(addr).await.unwrap();
+
+        // daily active users, one bit per user id
+        assert_reply(&mut client, b"SETBIT monday 3 1\r\n", b":0\r\n").await;
+        assert_reply(&mut client, b"SETBIT monday 3 1\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"SETBIT monday 10 1\r\n", b":0\r\n").await;
+        assert_reply(&mut client, b"SETBIT tuesday 10 1\r\n", b":0\r\n").await;
+        assert_reply(&mut client, b"GETBIT monday 10\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"GETBIT monday 1000\r\n", b":0\r\n").await;
+        assert_reply(&mut client, b"STRLEN monday\r\n", b":2\r\n").await;
+        assert_reply(
+            &mut client,
+            b"SETBIT monday -1 1\r\n",
+            b"-ERR bit offset is not an integer or out of range\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"SETBIT monday 1 2\r\n",
+            b"-ERR bit is not an integer or out of range\r\n",
+        )
+        .await;
+
+        assert_reply(&mut client, b"BITCOUNT monday\r\n", b":2\r\n").await;
+        assert_reply(&mut client, b"BITCOUNT monday 1 1\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"BITCOUNT monday 4 15 BIT\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"BITPOS monday 1\r\n", b":3\r\n").await;
+        assert_reply(&mut client, b"BITPOS monday 1 4 -1 BIT\r\n", b":10\r\n").await;
+        assert_reply(&mut client, b"BITPOS monday 0 1\r\n", b":8\r\n").await;
+        assert_reply(&mut client, b"SET full \xff\r\n", b"+OK\r\n").await;
+        assert_reply(&mut client, b"BITPOS full 0\r\n", b":8\r\n").await;
+        assert_reply(&mut client, b"BITPOS full 0 0 -1\r\n", b":-1\r\n").await;
+        assert_reply(&mut client, b"BITPOS missing 1\r\n", b":-1\r\n").await;
+
+        assert_reply(&mut client, b"BITOP AND both monday tuesday\r\n", b":2\r\n").await;
+        assert_reply(&mut client, b"BITCOUNT both\r\n", b":1\r\n").await;
+        assert_reply(
+            &mut client,
+            b"BITOP OR any monday tuesday missing\r\n",
+            b":2\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"BITCOUNT any\r\n", b":2\r\n").await;
+        assert_reply(&mut client, b"BITOP NOT inverted full\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"BITCOUNT inverted\r\n", b":0\r\n").await;
+        assert_reply(
+            &mut client,
+            b"BITOP NOT inverted full monday\r\n",
+            b"-ERR BITOP NOT must be called with a single source key.\r\n",
+        )
+        .await;
+
+        assert_reply(
+            &mut client,
+            b"BITFIELD fields SET i8 #1 -100 GET u8 8 INCRBY i8 #1 -100\r\n",
+            b"*3\r\n:0\r\n:156\r\n:56\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"BITFIELD fields OVERFLOW SAT INCRBY u4 0 100 OVERFLOW FAIL INCRBY u4 0 1\r\n",
+            b"*2\r\n:15\r\n$-1\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"BITFIELD_RO fields GET i64 0\r\n",
+            b"*1\r\n:-1137158905911050240\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"BITFIELD_RO fields SET u8 0 1\r\n",
+            b"-ERR BITFIELD_RO only supports the GET subcommand\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"BITFIELD fields GET u64 0\r\n",
+            b"-ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hyperloglog_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6402";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+        let mut client = TcpStream::connect(addr).await.unwrap();
+
+        assert_reply(&mut client, b"PFADD home a b c d e f g\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"PFADD home a b\r\n", b":0\r\n").await;
+        assert_reply(&mut client, b"PFCOUNT home\r\n", b":7\r\n").await;
+        assert_reply(&mut client, b"PFADD about e f g h i\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"PFCOUNT home about missing\r\n", b":9\r\n").await;
+        assert_reply(&mut client, b"PFMERGE site home about\r\n", b"+OK\r\n").await;
+        assert_reply(&mut client, b"PFCOUNT site\r\n", b":9\r\n").await;
+        assert_reply(&mut client, b"PFADD empty\r\n", b":1\r\n").await;
+        assert_reply(&mut client, b"PFCOUNT empty\r\n", b":0\r\n").await;
+        assert_reply(
+            &mut client,
+            b"GETRANGE empty 0 4\r\n",
+            b"$5\r\nHYLL\x01\r\n",
+        )
+        .await;
+
+        assert_reply(&mut client, b"SET plain text\r\n", b"+OK\r\n").await;
+        assert_reply(
+            &mut client,
+            b"PFCOUNT plain\r\n",
+            b"-WRONGTYPE Key is not a valid HyperLogLog string value.\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"PFMERGE site plain\r\n",
+            b"-WRONGTYPE Key is not a valid HyperLogLog string value.\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"RPUSH list x\r\n", b":1\r\n").await;
+        assert_reply(
+            &mut client,
+            b"PFADD list a\r\n",
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_geo_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "127.0.0.1:6403";
+        let server_handler = run_server(addr)
+            .await
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+        let mut client = TcpStream::connect(addr).await.unwrap();
+
+        assert_reply(
+            &mut client,
+            b"GEOADD Sicily 13.361389 38.115556 Palermo 15.087269 37.502669 Catania\r\n",
+            b":2\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOADD Sicily CH 13.361389 38.115556 Palermo\r\n",
+            b":0\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOADD Sicily 13.361389 88 Nowhere\r\n",
+            b"-ERR invalid longitude,latitude pair 13.361389,88.000000\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEODIST Sicily Palermo Catania\r\n",
+            b"$11\r\n166274.1516\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEODIST Sicily Palermo Catania km\r\n",
+            b"$8\r\n166.2742\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"GEODIST Sicily Palermo Rome\r\n", b"$-1\r\n").await;
+        assert_reply(
+            &mut client,
+            b"GEODIST Sicily Palermo Catania yd\r\n",
+            b"-ERR unsupported unit provided. please use M, KM, FT, MI\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOHASH Sicily Palermo Catania Rome\r\n",
+            b"*3\r\n$11\r\nsqc8b49rny0\r\n$11\r\nsqdtr74hyu0\r\n$-1\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOPOS Sicily Palermo Rome\r\n",
+            b"*2\r\n*2\r\n$20\r\n13.36138933897018433\r\n$20\r\n38.11555639549629859\r\n*-1\r\n",
+        )
+        .await;
+
+        assert_reply(
+            &mut client,
+            b"GEOADD Sicily 12.758489 38.788135 edge1 17.241510 38.788135 edge2\r\n",
+            b":2\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOSEARCH Sicily FROMLONLAT 15 37 BYRADIUS 200 km ASC\r\n",
+            b"*2\r\n$7\r\nCatania\r\n$7\r\nPalermo\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOSEARCH Sicily FROMLONLAT 15 37 BYBOX 400 400 km DESC WITHDIST\r\n",
+            b"*4\r\n*2\r\n$5\r\nedge1\r\n$8\r\n279.7405\r\n*2\r\n$5\r\nedge2\r\n$8\r\n279.7403\r\n\
+              *2\r\n$7\r\nPalermo\r\n$8\r\n190.4424\r\n*2\r\n$7\r\nCatania\r\n$7\r\n56.4413\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOSEARCH Sicily FROMMEMBER Palermo BYRADIUS 500 km COUNT 2 WITHHASH\r\n",
+            b"*2\r\n*2\r\n$7\r\nPalermo\r\n:3479099956230698\r\n*2\r\n$5\r\nedge1\r\n:3479273021651468\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOSEARCH Sicily FROMMEMBER Rome BYRADIUS 500 km\r\n",
+            b"-ERR could not decode requested zset member\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOSEARCH Sicily BYRADIUS 500 km\r\n",
+            b"-ERR exactly one of FROMMEMBER or FROMLONLAT can be specified for GEOSEARCH\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOSEARCH Sicily FROMLONLAT 15 37 BYRADIUS 200 km ANY\r\n",
+            b"-SYNTAX syntax error, unexpected `ANY` for 'geosearch' command\r\n",
+        )
+        .await;
+
+        assert_reply(
+            &mut client,
+            b"GEOSEARCHSTORE near Sicily FROMLONLAT 15 37 BYRADIUS 200 km STOREDIST\r\n",
+            b":2\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"ZRANGE near 0 -1\r\n",
+            b"*2\r\n$7\r\nCatania\r\n$7\r\nPalermo\r\n",
+        )
+        .await;
+        assert_reply(
+            &mut client,
+            b"GEOSEARCHSTORE near Sicily FROMLONLAT 15 37 BYRADIUS 1 m\r\n",
+            b":0\r\n",
+        )
+        .await;
+        assert_reply(&mut client, b"ZCARD near\r\n", b":0\r\n").await;
+        assert_reply(
+            &mut client,
+            b"GEOSEARCHSTORE near Sicily FROMLONLAT 15 37 BYRADIUS 1 m WITHDIST\r\n",
+            b"-ERR GEOSEARCHSTORE is not compatible with WITHDIST, WITHHASH and WITHCOORD options\r\n",
+        )
+        .await;
+
+        server_handler.abort();
+        Ok(())
+    }
 }